@@ -0,0 +1,6 @@
+use cloud_notepad::cli;
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    cli::run(&args)
+}
@@ -0,0 +1,63 @@
+use std::io::{BufRead, IsTerminal, Write};
+use cloud_notepad::server::{Server, ServerStore};
+use cloud_notepad::utils::logger;
+
+const USAGE: &str = "Usage: cloud-notepad-server [--addr HOST:PORT] [--db PATH] [--add-user NAME]...
+  --add-user reads the password from stdin, one line per user in the given order";
+
+fn main() -> anyhow::Result<()> {
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut db_path = "server.db".to_string();
+    let mut users = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = args.next().ok_or_else(|| anyhow::anyhow!(USAGE))?,
+            "--db" => db_path = args.next().ok_or_else(|| anyhow::anyhow!(USAGE))?,
+            "--add-user" => {
+                let name = args.next().ok_or_else(|| anyhow::anyhow!(USAGE))?;
+                users.push(name);
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => return Err(anyhow::anyhow!("unknown argument: {}\n{}", arg, USAGE)),
+        }
+    }
+
+    // 日志写在数据库所在目录下
+    let data_dir = std::path::Path::new(&db_path).parent().unwrap_or(std::path::Path::new("."));
+    logger::init(data_dir)?;
+
+    let mut store = ServerStore::open(&db_path)?;
+    for name in users.iter() {
+        let password = read_password(name)?;
+        store.add_user(name, &password)?;
+        println!("user {} saved", name);
+    }
+
+    let server = Server::bind(&addr, store)?;
+    println!("Cloud Notepad server listening on http://{}", addr);
+    server.run();
+    Ok(())
+}
+
+// 从标准输入读取密码，避免出现在进程参数中
+fn read_password(name: &str) -> anyhow::Result<String> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("password for {}: ", name);
+        std::io::stderr().flush()?;
+    }
+    let mut line = String::new();
+    if stdin.lock().read_line(&mut line)? == 0 {
+        return Err(anyhow::anyhow!("missing password for user {} on stdin", name));
+    }
+    let password = line.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        return Err(anyhow::anyhow!("empty password for user {}", name));
+    }
+    Ok(password.to_string())
+}
@@ -0,0 +1,416 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::AppState;
+use crate::data::{Database, ExportNote, Note};
+use crate::io::ExportFormat;
+use crate::services::{IoService, NoteService, SettingsService, TrashService};
+
+pub const USAGE: &str = "\
+Usage: cloud-notepad-cli [--db PATH] [--json] <command> [args]
+
+Commands:
+  list [--tag TAG] [--trash]          list notes (or notes in the trash)
+  search QUERY                        search titles and contents
+  show ID                             print a note
+  create [--title TITLE] [--tag TAG]...
+                                      create a note with content read from stdin
+  append ID                           append stdin to a note
+  tag ID TAG...                       add tags
+  untag ID TAG...                     remove tags
+  export [--format FORMAT] ID PATH    export a note (md, md-plain, json, html, pdf, docx, odt)
+  export [--format FORMAT] --all DIR  export all notes
+  import PATH                         import a file or folder
+  trash ID                            move a note to the trash
+  restore ID                          restore a note from the trash
+
+Options:
+  --db PATH      database file (default: notes.db)
+  --json         print results as JSON
+The vault passphrase is read from CLOUD_NOTEPAD_PASSPHRASE when the database is encrypted.";
+
+// 保险库口令的环境变量
+const PASSPHRASE_ENV: &str = "CLOUD_NOTEPAD_PASSPHRASE";
+
+/// 命令行子命令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    List { tag: Option<String>, trash: bool },
+    Search(String),
+    Show(String),
+    Create { title: Option<String>, tags: Vec<String> },
+    Append(String),
+    Tag { id: String, tags: Vec<String>, remove: bool },
+    Export { format: ExportFormat, id: Option<String>, path: PathBuf },
+    Import(PathBuf),
+    Trash(String),
+    Restore(String),
+}
+
+/// 解析后的命令行参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub db: PathBuf,
+    pub json: bool,
+    pub command: Command,
+}
+
+impl Cli {
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let mut db = PathBuf::from("notes.db");
+        let mut json = false;
+        let mut rest = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--db" => db = PathBuf::from(next(&mut args, "--db")?),
+                "--json" => json = true,
+                _ => rest.push(arg.clone()),
+            }
+        }
+
+        let mut args = rest.iter();
+        let name = args.next().ok_or_else(|| anyhow!(USAGE))?;
+        let command = match name.as_str() {
+            "list" => {
+                let (mut tag, mut trash) = (None, false);
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--tag" => tag = Some(next(&mut args, "--tag")?),
+                        "--trash" => trash = true,
+                        _ => return Err(unknown(arg)),
+                    }
+                }
+                Command::List { tag, trash }
+            }
+            "search" => Command::Search(positional(&mut args, "QUERY")?),
+            "show" => Command::Show(positional(&mut args, "ID")?),
+            "create" => {
+                let (mut title, mut tags) = (None, Vec::new());
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--title" => title = Some(next(&mut args, "--title")?),
+                        "--tag" => tags.push(next(&mut args, "--tag")?),
+                        _ => return Err(unknown(arg)),
+                    }
+                }
+                Command::Create { title, tags }
+            }
+            "append" => Command::Append(positional(&mut args, "ID")?),
+            "tag" | "untag" => {
+                let id = positional(&mut args, "ID")?;
+                let tags: Vec<String> = args.by_ref().cloned().collect();
+                if tags.is_empty() {
+                    return Err(anyhow!("missing TAG\n{}", USAGE));
+                }
+                Command::Tag { id, tags, remove: name == "untag" }
+            }
+            "export" => {
+                let (mut format, mut all, mut operands) = (ExportFormat::Markdown(true), false, Vec::new());
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--format" => format = parse_format(&next(&mut args, "--format")?)?,
+                        "--all" => all = true,
+                        _ => operands.push(arg.clone()),
+                    }
+                }
+                match (all, operands.as_slice()) {
+                    (true, [dir]) => Command::Export { format, id: None, path: PathBuf::from(dir) },
+                    (false, [id, path]) => Command::Export { format, id: Some(id.clone()), path: PathBuf::from(path) },
+                    _ => return Err(anyhow!("export needs ID PATH or --all DIR\n{}", USAGE)),
+                }
+            }
+            "import" => Command::Import(PathBuf::from(positional(&mut args, "PATH")?)),
+            "trash" => Command::Trash(positional(&mut args, "ID")?),
+            "restore" => Command::Restore(positional(&mut args, "ID")?),
+            _ => return Err(anyhow!("unknown command: {}\n{}", name, USAGE)),
+        };
+        if let Some(arg) = args.next() {
+            return Err(unknown(arg));
+        }
+        Ok(Self { db, json, command })
+    }
+}
+
+fn next<'a>(args: &mut impl Iterator<Item = &'a String>, option: &str) -> Result<String> {
+    args.next().cloned().ok_or_else(|| anyhow!("{} needs a value\n{}", option, USAGE))
+}
+
+fn positional<'a>(args: &mut impl Iterator<Item = &'a String>, name: &str) -> Result<String> {
+    args.next().cloned().ok_or_else(|| anyhow!("missing {}\n{}", name, USAGE))
+}
+
+fn unknown(arg: &str) -> anyhow::Error {
+    anyhow!("unknown argument: {}\n{}", arg, USAGE)
+}
+
+fn parse_format(name: &str) -> Result<ExportFormat> {
+    Ok(match name {
+        "md" | "markdown" => ExportFormat::Markdown(true),
+        "md-plain" => ExportFormat::Markdown(false),
+        "json" => ExportFormat::Json,
+        "html" => ExportFormat::Html,
+        "pdf" => ExportFormat::Pdf,
+        "docx" => ExportFormat::Docx,
+        "odt" => ExportFormat::Odt,
+        _ => return Err(anyhow!("unknown export format: {}", name)),
+    })
+}
+
+// 列表输出的笔记摘要
+#[derive(Serialize)]
+struct NoteSummary {
+    id: String,
+    title: String,
+    tags: Vec<String>,
+    pinned: bool,
+    locked: bool,
+    updated: Option<DateTime<Utc>>,
+}
+
+impl NoteSummary {
+    fn new(note: &Note) -> Self {
+        let mut tags: Vec<String> = note.tags().iter().cloned().collect();
+        tags.sort();
+        Self {
+            id: note.id().to_string(),
+            title: note.title().to_string(),
+            tags,
+            pinned: note.is_pinned(),
+            locked: note.is_locked(),
+            updated: ExportNote::from_note(note).updated,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TrashedNote<'a> {
+    id: &'a str,
+    title: &'a str,
+    deleted_at: String,
+}
+
+/// 打开数据库并执行命令
+pub fn run(args: &[String]) -> Result<()> {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let cli = Cli::parse(args)?;
+    let mut db = Database::open(&cli.db)?;
+    if db.is_locked()? {
+        let passphrase = std::env::var(PASSPHRASE_ENV)
+            .map_err(|_| anyhow!("The database is encrypted, set {} to unlock it", PASSPHRASE_ENV))?;
+        db.unlock(&passphrase)?;
+    }
+    let mut state = AppState::with_database(db)?;
+    execute(&mut state, &cli, &mut std::io::stdin().lock(), &mut std::io::stdout().lock())
+}
+
+/// 在已打开的 AppState 上执行命令, input 为标准输入
+pub fn execute(state: &mut AppState, cli: &Cli, input: &mut impl Read, output: &mut impl Write) -> Result<()> {
+    match &cli.command {
+        Command::List { trash: true, .. } => {
+            let notes = state.get_deleted_notes()?;
+            if cli.json {
+                let notes: Vec<TrashedNote> = notes.iter()
+                    .map(|note| TrashedNote { id: note.id(), title: note.title(), deleted_at: note.deleted_at() })
+                    .collect();
+                print_json(output, &notes)
+            } else {
+                for note in notes.iter() {
+                    writeln!(output, "{}\t{}\t{}", note.id(), note.deleted_at(), note.title())?;
+                }
+                Ok(())
+            }
+        }
+        Command::List { tag, .. } => {
+            let notes: Vec<Note> = state.filter_notes("")?.into_iter()
+                .filter(|note| tag.as_ref().is_none_or(|tag| note.tags().contains(tag)))
+                .collect();
+            print_notes(output, notes, cli.json)
+        }
+        Command::Search(query) => print_notes(output, state.filter_notes(query)?, cli.json),
+        Command::Show(id) => {
+            let note = unlocked_note(state, id)?;
+            if cli.json {
+                print_json(output, &ExportNote::from_note(&note))
+            } else {
+                write!(output, "{}", note.content())?;
+                Ok(())
+            }
+        }
+        Command::Create { title, tags } => {
+            let content = read_input(input)?;
+            // 没有指定标题时使用第一行
+            let title = title.clone().unwrap_or_else(|| {
+                content.lines().next().unwrap_or_default().trim_start_matches('#').trim().to_string()
+            });
+            let mut note = Note::new(if title.is_empty() { "untitled".to_string() } else { title });
+            note.update_content(content);
+            for tag in tags.iter() {
+                note.add_tag(tag.clone());
+            }
+            store_note(state, &note)?;
+            print_saved(output, &note, cli.json)
+        }
+        Command::Append(id) => {
+            let mut note = unlocked_note(state, id)?;
+            let text = read_input(input)?;
+            let mut content = note.content().to_string();
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(&text);
+            note.update_content(content);
+            store_note(state, &note)?;
+            print_saved(output, &note, cli.json)
+        }
+        Command::Tag { id, tags, remove } => {
+            let mut note = find_note(state, id)?;
+            for tag in tags.iter() {
+                if *remove { note.remove_tag(tag); } else { note.add_tag(tag.clone()); }
+            }
+            store_note(state, &note)?;
+            print_saved(output, &note, cli.json)
+        }
+        Command::Export { format, id, path } => {
+            state.export_config_mut().format = format.clone();
+            match id {
+                Some(id) => state.export_note(id, path)?,
+                None => {
+                    std::fs::create_dir_all(path)?;
+                    state.export_all_notes(path)?;
+                }
+            }
+            if cli.json {
+                print_json(output, &serde_json::json!({ "path": path }))
+            } else {
+                writeln!(output, "{}", path.display())?;
+                Ok(())
+            }
+        }
+        Command::Import(path) => {
+            let report = state.import(path)?;
+            if cli.json {
+                print_json(output, report)
+            } else {
+                writeln!(output, "{}", report.summary())?;
+                Ok(())
+            }
+        }
+        Command::Trash(id) => {
+            let note = find_note(state, id)?;
+            state.move_to_trash(note.id())?;
+            print_done(output, note.id(), "trashed", cli.json)
+        }
+        Command::Restore(id) => {
+            state.restore_from_trash(id)?;
+            print_done(output, id, "restored", cli.json)
+        }
+    }
+}
+
+// 更新内存中的笔记并立即写入数据库(不等待下一次刷新)
+fn store_note(state: &mut AppState, note: &Note) -> Result<()> {
+    state.update_note(note.clone())?;
+    state.save_note(note)?;
+    Ok(())
+}
+
+fn find_note(state: &AppState, id: &str) -> Result<Note> {
+    state.get_note(id).ok_or_else(|| anyhow!("No note with id {}", id))
+}
+
+// 加锁笔记的内容是密文，命令行中不能读取或修改
+fn unlocked_note(state: &AppState, id: &str) -> Result<Note> {
+    let note = find_note(state, id)?;
+    if note.is_sealed() {
+        return Err(anyhow!("The note is locked: {}", note.title()));
+    }
+    Ok(note)
+}
+
+fn read_input(input: &mut impl Read) -> Result<String> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+fn print_json<T: Serialize + ?Sized>(output: &mut impl Write, value: &T) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *output, value)?;
+    writeln!(output)?;
+    Ok(())
+}
+
+// 按置顶和更新时间排序输出
+fn print_notes(output: &mut impl Write, notes: Vec<Note>, json: bool) -> Result<()> {
+    let mut notes: Vec<NoteSummary> = notes.iter().map(NoteSummary::new).collect();
+    notes.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.updated.cmp(&a.updated)));
+    if json {
+        return print_json(output, &notes);
+    }
+    for note in notes.iter() {
+        let tags: Vec<String> = note.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        let updated = note.updated.map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string());
+        writeln!(output, "{}\t{}\t{}\t{}", note.id, updated.unwrap_or_default(), note.title, tags.join(" "))?;
+    }
+    Ok(())
+}
+
+fn print_saved(output: &mut impl Write, note: &Note, json: bool) -> Result<()> {
+    if json {
+        print_json(output, &NoteSummary::new(note))
+    } else {
+        writeln!(output, "{}", note.id())?;
+        Ok(())
+    }
+}
+
+fn print_done(output: &mut impl Write, id: &str, status: &str, json: bool) -> Result<()> {
+    if json {
+        print_json(output, &serde_json::json!({ "id": id, "status": status }))
+    } else {
+        writeln!(output, "{} {}", status, id)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli(state: &mut AppState, args: &[&str], input: &str) -> String {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut output = Vec::new();
+        execute(state, &Cli::parse(&args).unwrap(), &mut input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_cli_commands() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let mut state = AppState::with_database(Database::open(dir.join("notes.db")).unwrap()).unwrap();
+
+        let id = cli(&mut state, &["create", "--tag", "inbox"], "# 购物清单\n- 牛奶\n").trim().to_string();
+        // 每次命令重新打开数据库
+        let mut state = AppState::with_database(Database::open(dir.join("notes.db")).unwrap()).unwrap();
+        cli(&mut state, &["append", &id], "- 面包\n");
+        cli(&mut state, &["tag", &id, "家庭"], "");
+        let mut state = AppState::with_database(Database::open(dir.join("notes.db")).unwrap()).unwrap();
+        assert_eq!(cli(&mut state, &["show", &id], ""), "# 购物清单\n- 牛奶\n- 面包\n");
+
+        let listed: serde_json::Value = serde_json::from_str(&cli(&mut state, &["--json", "list", "--tag", "家庭"], "")).unwrap();
+        assert_eq!(listed[0]["title"], "购物清单");
+        assert_eq!(listed[0]["tags"], serde_json::json!(["inbox", "家庭"]));
+        assert!(cli(&mut state, &["search", "面包"], "").starts_with(&id));
+
+        cli(&mut state, &["trash", &id], "");
+        assert_eq!(cli(&mut state, &["list"], ""), "");
+        assert!(cli(&mut state, &["list", "--trash"], "").contains("购物清单"));
+        assert!(Cli::parse(&["export".to_string(), id.clone()]).is_err());
+    }
+}
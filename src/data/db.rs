@@ -1,14 +1,37 @@
-use rusqlite::{Connection, Result, Transaction};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use rusqlite::{Connection, OptionalExtension, Row, Transaction, types::{FromSql, ValueRef}};
 use chrono::{DateTime, Utc};
-use super::{Note, DeleteNote, NoteVersion, Notebook};
+use crate::error::{NotepadError, NotepadResult};
+use crate::utils::crypto::{self, Cipher, EncryptedBackup, VaultHeader};
+use super::{Note, DeleteNote, NoteVersion, Notebook, RecordState, SyncRecord, SyncScope, SyncState};
 
 pub struct Database {
     connection: Connection,
+    // 保险库解锁后的数据密钥(未启用加密时为None)
+    cipher: Option<Cipher>,
+    // 读取时跳过的损坏行(表, 行id, 字段), 由 quarantine_corrupt_rows 移入隔离表
+    corrupt_rows: RefCell<Vec<(&'static str, String, &'static str)>>,
 }
 
 impl Database {
-    pub fn new() -> Result<Self> {
-        let connection = Connection::open("notes.db")?;
+    pub fn new() -> NotepadResult<Self> {
+        Self::open("notes.db")
+    }
+
+    /// 打开指定路径的数据库(不存在时自动创建)
+    pub(crate) fn open<P: AsRef<Path>>(path: P) -> NotepadResult<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// 打开内存数据库(关闭后数据丢失, 用于嵌入和测试)
+    pub fn open_in_memory() -> NotepadResult<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    // 创建或升级表结构
+    fn init(connection: Connection) -> NotepadResult<Self> {
         // 笔记信息表
         connection.execute(
             "CREATE TABLE IF NOT EXISTS notes (
@@ -38,58 +61,269 @@ impl Database {
             )", 
             [],
         )?;
-        Ok( Self { connection } )
-    } 
 
-    fn load_note(&self, id: &str) -> Result<Option<Note>> {
-        let mut stmt = self.connection.prepare(
-            "SELECT id, title, content, tags, created_at, updated_at, is_pinned FROM notes
-            WHERE id = ?1"
+        // 笔记修订号(每次保存递增，用于同步)
+        Self::add_column_if_missing(&connection, "notes", "revision", "INTEGER NOT NULL DEFAULT 0")?;
+        // 笔记密码包装后的笔记密钥(JSON，未加锁时为NULL)
+        Self::add_column_if_missing(&connection, "notes", "lock", "TEXT")?;
+        // 别名和自定义属性(JSON)
+        Self::add_column_if_missing(&connection, "notes", "aliases", "TEXT")?;
+        Self::add_column_if_missing(&connection, "notes", "properties", "TEXT")?;
+
+        // 永久删除笔记的墓碑表
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS tombstones (
+                note_id TEXT PRIMARY KEY,
+                revision INTEGER NOT NULL,
+                deleted_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // 笔记同步状态表
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS sync_state (
+                note_id TEXT PRIMARY KEY,
+                local_revision INTEGER NOT NULL,
+                remote_revision INTEGER NOT NULL,
+                conflict_revision INTEGER,
+                base TEXT
+            )",
+            [],
+        )?;
+
+        // 镜像目录同步状态表(结构与 sync_state 相同)
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS mirror_state (
+                note_id TEXT PRIMARY KEY,
+                local_revision INTEGER NOT NULL,
+                remote_revision INTEGER NOT NULL,
+                conflict_revision INTEGER,
+                base TEXT
+            )",
+            [],
+        )?;
+
+        // 同步配置表
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS sync_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
         )?;
-        let mut note_iter = stmt.query_map([id], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                tags: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
-                    .unwrap().with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(5)?)
-                    .unwrap().with_timezone(&Utc),
-                is_pinned: row.get(6)?,
-            })
-        })?;
 
-        match note_iter.next() {
-            Some(Ok(note)) => Ok(Some(note)),
-            Some(Err(e)) => Err(e),
-            None => Ok(None),
+        // 保险库表(只有一行，保存口令包装后的数据密钥)
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS vault (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                header TEXT NOT NULL
+            )",
+            [],
+        )?;
+        // 无法读取的行(原始数据以JSON保存)
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS quarantine (
+                source TEXT NOT NULL,
+                row_id TEXT NOT NULL,
+                field TEXT NOT NULL,
+                data TEXT,
+                quarantined_at TEXT NOT NULL,
+                PRIMARY KEY (source, row_id)
+            )",
+            [],
+        )?;
+        Ok( Self { connection, cipher: None, corrupt_rows: RefCell::default() } )
+    } 
+
+    // 为旧版本数据库补充新增的列
+    fn add_column_if_missing(connection: &Connection, table: &str, column: &str, definition: &str) -> NotepadResult<()> {
+        let exists = connection
+            .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+            .exists([column])?;
+        if !exists {
+            connection.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
         }
+        Ok(())
+    }
 
+    // 读取时解密字段，未加密的字段原样返回; 无法解密时返回 Corrupt 错误
+    fn open_text(cipher: Option<&Cipher>, text: String, id: &str, field: &'static str) -> NotepadResult<String> {
+        if !crypto::is_encrypted(&text) {
+            return Ok(text);
+        }
+        let Some(cipher) = cipher else {
+            return Err(NotepadError::Locked("the vault".to_string()));
+        };
+        cipher.decrypt(&text).map_err(|_| corrupt(id, field))
     }
 
-    pub fn load_all_notes(&self) -> Result<Notebook> {
+    // 写入时加密字段(启用保险库时)
+    fn seal_text(cipher: Option<&Cipher>, text: &str) -> String {
+        match cipher {
+            Some(cipher) => cipher.encrypt(text),
+            None => text.to_string(),
+        }
+    }
+
+    // 从 id, title, content, tags, created_at, updated_at, is_pinned, lock, aliases, properties 列读取笔记
+    fn note_from_row(cipher: Option<&Cipher>, row: &Row) -> NotepadResult<Note> {
+        let id: String = row.get(0)?;
+        let lock = match column::<Option<String>>(row, 7, &id, "lock")? {
+            Some(lock) => Some(serde_json::from_str(&lock).map_err(|_| corrupt(&id, "lock"))?),
+            None => None,
+        };
+        Ok(Note {
+            title: Self::open_text(cipher, column(row, 1, &id, "title")?, &id, "title")?,
+            content: Self::open_text(cipher, column(row, 2, &id, "content")?, &id, "content")?,
+            tags: Self::open_json(cipher, column(row, 3, &id, "tags")?, &id, "tags")?,
+            created_at: parse_time(&column::<String>(row, 4, &id, "created_at")?, &id, "created_at")?,
+            updated_at: parse_time(&column::<String>(row, 5, &id, "updated_at")?, &id, "updated_at")?,
+            is_pinned: column(row, 6, &id, "is_pinned")?,
+            lock,
+            aliases: Self::open_json(cipher, column(row, 8, &id, "aliases")?, &id, "aliases")?,
+            properties: Self::open_json(cipher, column(row, 9, &id, "properties")?, &id, "properties")?,
+            id,
+        })
+    }
+
+    // 版本行的列依次为 id, note_id, title, content, tags, comment, saved_at
+    fn version_from_row(cipher: Option<&Cipher>, row: &Row) -> NotepadResult<NoteVersion> {
+        let id: String = row.get(0)?;
+        Ok(NoteVersion {
+            note_id: column(row, 1, &id, "note_id")?,
+            title: Self::open_text(cipher, column(row, 2, &id, "title")?, &id, "title")?,
+            content: Self::open_text(cipher, column(row, 3, &id, "content")?, &id, "content")?,
+            tags: Self::open_json(cipher, column(row, 4, &id, "tags")?, &id, "tags")?,
+            comment: column::<Option<String>>(row, 5, &id, "comment")?.unwrap_or_default(),
+            saved_at: parse_time(&column::<String>(row, 6, &id, "saved_at")?, &id, "saved_at")?,
+            id,
+        })
+    }
+
+    // 回收站中的笔记, 列依次为 id, title, updated_at
+    fn deleted_note_from_row(cipher: Option<&Cipher>, row: &Row) -> NotepadResult<DeleteNote> {
+        let id: String = row.get(0)?;
+        Ok(DeleteNote {
+            title: Self::open_text(cipher, column(row, 1, &id, "title")?, &id, "title")?,
+            deleted_at: parse_time(&column::<String>(row, 2, &id, "updated_at")?, &id, "updated_at")?,
+            id,
+        })
+    }
+
+    // 读取可选的JSON列(为空时返回默认值), 无法解析时返回 Corrupt 错误
+    fn open_json<T: serde::de::DeserializeOwned + Default>(
+        cipher: Option<&Cipher>,
+        text: Option<String>,
+        id: &str,
+        field: &'static str,
+    ) -> NotepadResult<T> {
+        match text {
+            Some(text) => serde_json::from_str(&Self::open_text(cipher, text, id, field)?).map_err(|_| corrupt(id, field)),
+            None => Ok(T::default()),
+        }
+    }
+
+    // 逐行收集查询结果: 损坏的行被跳过并记录下来(读取时不修改数据库), 其他错误直接返回
+    fn collect_rows<T>(
+        &self,
+        table: &'static str,
+        rows: impl Iterator<Item = rusqlite::Result<NotepadResult<T>>>,
+    ) -> NotepadResult<Vec<T>> {
+        let mut items = Vec::new();
+        for row in rows {
+            match row? {
+                Ok(item) => items.push(item),
+                Err(NotepadError::Corrupt { row, field }) => {
+                    let mut corrupt_rows = self.corrupt_rows.borrow_mut();
+                    if !corrupt_rows.iter().any(|(t, id, _)| *t == table && *id == row) {
+                        corrupt_rows.push((table, row, field));
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(items)
+    }
+
+    /// 将读取时跳过的损坏行连同原始数据移入隔离表, 返回被隔离的行(每行只返回一次)
+    pub fn quarantine_corrupt_rows(&mut self) -> NotepadResult<Vec<NotepadError>> {
+        let corrupt_rows = self.corrupt_rows.take();
+        if corrupt_rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let tx = self.connection.transaction()?;
+        for (table, id, field) in corrupt_rows.iter() {
+            if *table == "notes" {
+                // 版本历史引用该笔记, 一起移入隔离表
+                let versions = tx.prepare("SELECT id FROM note_versions WHERE note_id = ?1")?
+                    .query_map([id], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<String>>>()?;
+                for version_id in versions.iter() {
+                    Self::move_to_quarantine(&tx, "note_versions", version_id, "note_id")?;
+                }
+            }
+            Self::move_to_quarantine(&tx, table, id, field)?;
+        }
+        tx.commit()?;
+
+        Ok(corrupt_rows.into_iter()
+            .map(|(table, id, field)| {
+                crate::log_warn!("quarantine"; "moved corrupt row {} of {} (field {}) to quarantine", id, table, field);
+                NotepadError::Corrupt { row: id, field }
+            })
+            .collect())
+    }
+
+    fn move_to_quarantine(tx: &Transaction, table: &str, id: &str, field: &str) -> NotepadResult<()> {
+        let mut stmt = tx.prepare(&format!("SELECT * FROM {} WHERE id = ?1", table))?;
+        let names: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+        let data = stmt.query_row([id], |row| {
+            let mut columns = serde_json::Map::new();
+            for (idx, name) in names.iter().enumerate() {
+                let value = match row.get_ref(idx)? {
+                    ValueRef::Null => serde_json::Value::Null,
+                    ValueRef::Integer(i) => i.into(),
+                    ValueRef::Real(f) => f.into(),
+                    ValueRef::Text(t) | ValueRef::Blob(t) => String::from_utf8_lossy(t).into(),
+                };
+                columns.insert(name.clone(), value);
+            }
+            Ok(serde_json::Value::Object(columns).to_string())
+        }).optional()?;
+        // 已随笔记一起移入隔离表
+        let Some(data) = data else {
+            return Ok(());
+        };
+
+        tx.execute(
+            "INSERT OR REPLACE INTO quarantine (source, row_id, field, data, quarantined_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![ table, id, field, data, Utc::now().to_rfc3339() ],
+        )?;
+        tx.execute(&format!("DELETE FROM {} WHERE id = ?1", table), [id])?;
+        Ok(())
+    }
+
+    // 按id读取一篇笔记(不含回收站中的), 不存在或已损坏时返回None
+    pub fn load_note(&self, id: &str) -> NotepadResult<Option<Note>> {
         let mut stmt = self.connection.prepare(
-            "SELECT id, title, content, tags, created_at, updated_at, is_pinned FROM notes 
+            "SELECT id, title, content, tags, created_at, updated_at, is_pinned, lock, aliases, properties FROM notes
+            WHERE id = ?1 AND is_deleted = FALSE"
+        )?;
+        let note_iter = stmt.query_map([id], |row| Ok(Self::note_from_row(self.cipher.as_ref(), row)))?;
+        Ok(self.collect_rows("notes", note_iter)?.pop())
+    }
+
+    pub fn load_all_notes(&self) -> NotepadResult<Notebook> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, title, content, tags, created_at, updated_at, is_pinned, lock, aliases, properties FROM notes 
             WHERE is_deleted = FALSE"
         )?;
-        let note_iter = stmt.query_map([], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                tags: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
-                    .unwrap().with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(5)?)
-                    .unwrap().with_timezone(&Utc),
-                is_pinned: row.get(6)?,
-            })
-        })?;
+        let note_iter = stmt.query_map([], |row| Ok(Self::note_from_row(self.cipher.as_ref(), row)))?;
 
         let mut notebook = Notebook::default();
-        for note in note_iter {
-            let note = note?;
+        for note in self.collect_rows("notes", note_iter)? {
             notebook.insert_or_replace_note(note);
         }
         Ok(notebook)
@@ -97,64 +331,82 @@ impl Database {
 
     fn insert_or_replace_note(
         tx: &Transaction,
+        cipher: Option<&Cipher>,
         note: &Note,
-    ) -> Result<()> {
+    ) -> NotepadResult<()> {
         tx.execute(
             "INSERT OR REPLACE INTO notes
-            (id, title, content, tags, created_at, updated_at, is_pinned)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)", 
+            (id, title, content, tags, created_at, updated_at, is_pinned, lock, aliases, properties, revision)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
+                COALESCE((SELECT revision FROM notes WHERE id = ?1),
+                         (SELECT revision FROM tombstones WHERE note_id = ?1), 0) + 1)", 
             rusqlite::params![
                 note.id,
-                note.title,
-                note.content,
-                serde_json::to_string(&note.tags).unwrap(),
+                Self::seal_text(cipher, &note.title),
+                Self::seal_text(cipher, &note.content),
+                Self::seal_text(cipher, &to_json(&note.tags)?),
                 note.created_at.to_rfc3339(),
                 note.updated_at.to_rfc3339(),
                 note.is_pinned,
+                note.lock.as_ref().map(to_json).transpose()?,
+                Self::seal_text(cipher, &to_json(&note.aliases)?),
+                Self::seal_text(cipher, &to_json(&note.properties)?),
             ]
         )?;
+        // 重新写入已永久删除的笔记(导入、恢复备份或镜像)时, 墓碑不再有效
+        tx.execute("DELETE FROM tombstones WHERE note_id = ?1", [&note.id])?;
         Ok(())
     }
 
-    pub fn save_note(&mut self, note: &Note) -> Result<()> {
+    pub fn save_note(&mut self, note: &Note) -> NotepadResult<()> {
+        let tx = self.connection.transaction()?;
+        Self::insert_or_replace_note(&tx, self.cipher.as_ref(), note)?;
+        Ok(tx.commit()?)
+    }
+
+    // 在同一个事务中保存多个笔记(任一失败则全部回滚)
+    pub fn save_notes(&mut self, notes: &[Note]) -> NotepadResult<()> {
         let tx = self.connection.transaction()?;
-        Self::insert_or_replace_note(&tx, note)?;
-        tx.commit()
+        for note in notes.iter() {
+            Self::insert_or_replace_note(&tx, self.cipher.as_ref(), note)?;
+        }
+        Ok(tx.commit()?)
     }
 
-    pub fn save_notebook(&mut self, notebook: &Notebook) -> Result<()> {
+    pub fn save_notebook(&mut self, notebook: &Notebook) -> NotepadResult<()> {
         let tx = self.connection.transaction()?;
         for note in notebook.notes.values() {
-            Self::insert_or_replace_note(&tx, note)?;
+            Self::insert_or_replace_note(&tx, self.cipher.as_ref(), note)?;
         }
-        tx.commit()
+        Ok(tx.commit()?)
     }
 
     // 软删除笔记 （移动到回收站）
-    pub fn move_to_trash(&mut self, note_id: &str) -> Result<()> {
+    pub fn move_to_trash(&mut self, note_id: &str) -> NotepadResult<()> {
         let tx = self.connection.transaction()?;
         tx.execute(
-            "UPDATE notes SET is_deleted = TRUE, updated_at = ?1 WHERE id = ?2", 
+            "UPDATE notes SET is_deleted = TRUE, updated_at = ?1, revision = revision + 1 WHERE id = ?2", 
             rusqlite::params![ Utc::now().to_rfc3339(), note_id ],
         )?;
-        tx.commit()
+        Ok(tx.commit()?)
     }
 
     // 从回收站恢复笔记
-    pub fn restore_from_trash(&mut self, note_id: &str) -> Result<Option<Note>> {
-        {
-            let tx = self.connection.transaction()?;
-            tx.execute(
-                "UPDATE notes SET is_deleted = FALSE, updated_at = ?1 WHERE id = ?2", 
-                rusqlite::params![ Utc::now().to_rfc3339(), note_id ],
-            )?;
-            tx.commit()?;
+    // 笔记不在回收站中时返回None
+    pub fn restore_from_trash(&mut self, note_id: &str) -> NotepadResult<Option<Note>> {
+        let restored = self.connection.execute(
+            "UPDATE notes SET is_deleted = FALSE, updated_at = ?1, revision = revision + 1
+            WHERE id = ?2 AND is_deleted = TRUE",
+            rusqlite::params![ Utc::now().to_rfc3339(), note_id ],
+        )?;
+        if restored == 0 {
+            return Ok(None);
         }
         self.load_note(note_id)
     }
 
     // 永久删除笔记
-    pub fn delete_permanently(&mut self, note_id: &str) -> Result<()> {
+    pub fn delete_permanently(&mut self, note_id: &str) -> NotepadResult<()> {
         let tx = self.connection.transaction()?;
         // 先删除所有版本历史
         tx.execute(
@@ -162,39 +414,39 @@ impl Database {
             [note_id]
         )?;
 
+        // 留下墓碑，供同步时传播删除
+        tx.execute(
+            "INSERT OR REPLACE INTO tombstones (note_id, revision, deleted_at)
+            SELECT id, revision + 1, ?2 FROM notes WHERE id = ?1",
+            rusqlite::params![ note_id, Utc::now().to_rfc3339() ],
+        )?;
+
         // 再删除笔记本身
         tx.execute(
             "DELETE FROM notes WHERE id = ?1", 
             [note_id]
         )?;
-        tx.commit()
+        Ok(tx.commit()?)
     }
 
     // 获取回收站中的所有笔记
-    pub fn get_deleted_notes(&self) -> Result<Vec<DeleteNote>> {
+    pub fn get_deleted_notes(&self) -> NotepadResult<Vec<DeleteNote>> {
         let mut stmt = self.connection.prepare(
             "SELECT id, title, updated_at FROM notes
             WHERE is_deleted = TRUE ORDER BY updated_at DESC",
         )?;
 
-        let notes = stmt.query_map([], |row| {
-            Ok(DeleteNote {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                deleted_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?).unwrap().with_timezone(&Utc),
-            })
-        })?.collect::<Result<Vec<_>>>()?;
-
-        Ok(notes)
+        let notes = stmt.query_map([], |row| Ok(Self::deleted_note_from_row(self.cipher.as_ref(), row)))?;
+        self.collect_rows("notes", notes)
     }
 
     // 清空回收站
-    pub fn empty_trash(&mut self) -> Result<()> {
+    pub fn empty_trash(&mut self) -> NotepadResult<()> {
         // 获取所有已删除笔记的ID
         let deleted_ids = self.connection
             .prepare("SELECT id FROM notes WHERE is_deleted = TRUE")?
             .query_map([], |row| row.get(0))?
-            .collect::<Result<Vec<String>>>()?;
+            .collect::<rusqlite::Result<Vec<String>>>()?;
 
         // 为每个笔记执行彻底删除
         for id in deleted_ids.iter() {
@@ -205,31 +457,21 @@ impl Database {
     }
 
     // 导出版本历史
-    pub fn load_version_history(&self, note_id: &str) -> Result<Vec<NoteVersion>> {
+    pub fn load_version_history(&self, note_id: &str) -> NotepadResult<Vec<NoteVersion>> {
         let mut stmt = self.connection.prepare(
             "SELECT id, note_id, title, content, tags, comment, saved_at FROM note_versions
-            WHERE note_id = ?1",
+            WHERE note_id = ?1 ORDER BY saved_at DESC",
         )?;
 
-        let note_versions = stmt.query_map([note_id], |row| {
-            Ok(NoteVersion {
-                id: row.get(0)?,
-                note_id: row.get(1)?,
-                title: row.get(2)?,
-                content: row.get(3)?,
-                tags: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
-                comment: row.get(5)?,
-                saved_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?).unwrap().with_timezone(&Utc),
-            })
-        })?.collect::<Result<Vec<_>>>()?;
-
-        Ok(note_versions)
+        let note_versions = stmt.query_map([note_id], |row| Ok(Self::version_from_row(self.cipher.as_ref(), row)))?;
+        self.collect_rows("note_versions", note_versions)
     }
 
     fn insert_or_replace_note_version(
         tx: &Transaction,
+        cipher: Option<&Cipher>,
         note_version: &NoteVersion,
-    ) -> Result<()> {
+    ) -> NotepadResult<()> {
         tx.execute(
             "INSERT OR REPLACE INTO note_versions
             (id, note_id, title, content, tags, comment, saved_at)
@@ -237,9 +479,9 @@ impl Database {
             rusqlite::params![
                 note_version.id,
                 note_version.note_id,
-                note_version.title,
-                note_version.content,
-                serde_json::to_string(&note_version.tags).unwrap(),
+                Self::seal_text(cipher, &note_version.title),
+                Self::seal_text(cipher, &note_version.content),
+                Self::seal_text(cipher, &to_json(&note_version.tags)?),
                 note_version.comment,
                 note_version.saved_at.to_rfc3339(),
             ]
@@ -247,18 +489,557 @@ impl Database {
         Ok(())
     }
 
-    pub fn save_version(&mut self, note_version: &NoteVersion) -> Result<()> {
+    pub fn save_version(&mut self, note_version: &NoteVersion) -> NotepadResult<()> {
         let tx = self.connection.transaction()?;
-        Self::insert_or_replace_note_version(&tx, note_version)?;
-        tx.commit()
+        Self::insert_or_replace_note_version(&tx, self.cipher.as_ref(), note_version)?;
+        Ok(tx.commit()?)
     }
 
-    pub fn delete_version(&mut self, version_id: &str) -> Result<()> {
+    // 在同一个事务中写入多个版本(从git仓库迁回时使用)
+    pub fn save_versions(&mut self, versions: &[NoteVersion]) -> NotepadResult<()> {
+        let tx = self.connection.transaction()?;
+        for version in versions.iter() {
+            Self::insert_or_replace_note_version(&tx, self.cipher.as_ref(), version)?;
+        }
+        Ok(tx.commit()?)
+    }
+
+    pub fn delete_version(&mut self, version_id: &str) -> NotepadResult<()> {
         let tx = self.connection.transaction()?;
         tx.execute(
             "DELETE FROM note_versions WHERE id = ?1", 
             [version_id]
         )?;
-        tx.commit()
+        Ok(tx.commit()?)
+    }
+
+    // 删除全部版本历史(迁移到git仓库后使用)
+    pub fn clear_versions(&mut self) -> NotepadResult<()> {
+        self.connection.execute("DELETE FROM note_versions", [])?;
+        Ok(())
+    }
+
+    // 数据库文件所在的目录(内存数据库为None)
+    pub fn directory(&self) -> Option<std::path::PathBuf> {
+        let path = Path::new(self.connection.path().filter(|path| !path.is_empty())?);
+        Some(path.parent().map(Path::to_path_buf).unwrap_or_default())
+    }
+
+    // 加载所有笔记(含回收站中的, 同时返回是否已删除), 用于完整备份
+    pub fn load_notes_with_trash(&self) -> NotepadResult<Vec<(Note, bool)>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, title, content, tags, created_at, updated_at, is_pinned, lock, aliases, properties, is_deleted FROM notes"
+        )?;
+        let notes = stmt.query_map([], |row| {
+            Ok(Self::note_from_row(self.cipher.as_ref(), row).and_then(|note| {
+                let deleted = column(row, 10, &note.id, "is_deleted")?;
+                Ok((note, deleted))
+            }))
+        })?;
+        self.collect_rows("notes", notes)
+    }
+
+    // 加载所有笔记的版本历史
+    pub fn load_all_versions(&self) -> NotepadResult<Vec<NoteVersion>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, note_id, title, content, tags, comment, saved_at FROM note_versions ORDER BY saved_at",
+        )?;
+        let versions = stmt.query_map([], |row| Ok(Self::version_from_row(self.cipher.as_ref(), row)))?;
+        self.collect_rows("note_versions", versions)
+    }
+
+    // 在同一个事务中恢复备份的笔记和版本历史, 返回写入的笔记数
+    // 替换时删除备份中没有的笔记(留下墓碑); 合并时只写入新笔记和比现有笔记更新的笔记
+    pub fn restore_notes(&mut self, notes: &[(Note, bool)], versions: &[NoteVersion], replace: bool) -> NotepadResult<usize> {
+        let tx = self.connection.transaction()?;
+        let existing = tx
+            .prepare("SELECT id, updated_at FROM notes")?
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+
+        if replace {
+            let restored: std::collections::HashSet<&str> = notes.iter().map(|(note, _)| note.id.as_str()).collect();
+            for id in existing.keys().filter(|id| !restored.contains(id.as_str())) {
+                tx.execute(
+                    "INSERT OR REPLACE INTO tombstones (note_id, revision, deleted_at)
+                    SELECT id, revision + 1, ?2 FROM notes WHERE id = ?1",
+                    rusqlite::params![ id, Utc::now().to_rfc3339() ],
+                )?;
+                tx.execute("DELETE FROM notes WHERE id = ?1", [id])?;
+            }
+            tx.execute("DELETE FROM note_versions", [])?;
+        }
+
+        let mut written = 0;
+        for (note, deleted) in notes.iter() {
+            let newer = existing.get(&note.id)
+                .and_then(|updated_at| DateTime::parse_from_rfc3339(updated_at).ok())
+                .is_none_or(|updated_at| note.updated_at > updated_at);
+            if !replace && !newer {
+                continue;
+            }
+            Self::insert_or_replace_note(&tx, self.cipher.as_ref(), note)?;
+            tx.execute(
+                "UPDATE notes SET is_deleted = ?1 WHERE id = ?2",
+                rusqlite::params![ deleted, note.id ],
+            )?;
+            written += 1;
+        }
+        for version in versions.iter() {
+            Self::insert_or_replace_note_version(&tx, self.cipher.as_ref(), version)?;
+        }
+        tx.commit()?;
+        Ok(written)
+    }
+
+    // 加载所有笔记(含回收站)和墓碑的同步记录
+    pub fn load_sync_records(&self) -> NotepadResult<Vec<SyncRecord>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, title, content, tags, created_at, updated_at, is_pinned, lock, aliases, properties, is_deleted, revision FROM notes"
+        )?;
+        let records = stmt.query_map([], |row| {
+            Ok(Self::note_from_row(self.cipher.as_ref(), row).and_then(|note| {
+                let state = if column(row, 10, &note.id, "is_deleted")? { RecordState::Trashed } else { RecordState::Active };
+                let revision = column(row, 11, &note.id, "revision")?;
+                Ok(SyncRecord::new(note, state, revision))
+            }))
+        })?;
+        let mut records = self.collect_rows("notes", records)?;
+
+        let mut stmt = self.connection.prepare("SELECT note_id, revision FROM tombstones")?;
+        let tombstones = stmt.query_map([], |row| {
+            Ok(SyncRecord::tombstone(&row.get::<_, String>(0)?, row.get(1)?))
+        })?.collect::<rusqlite::Result<Vec<_>>>()?;
+        records.extend(tombstones);
+
+        Ok(records)
+    }
+
+    // 将同步记录应用到本地，返回应用后的本地修订号
+    pub fn apply_sync_record(&mut self, record: &SyncRecord) -> NotepadResult<u64> {
+        match (record.state, &record.note) {
+            // 镜像目录中的文件被删除时移入回收站
+            (RecordState::Trashed, None) => {
+                self.move_to_trash(&record.id)?;
+            }
+            (RecordState::Deleted, _) | (_, None) => {
+                self.delete_permanently(&record.id)?;
+            }
+            (state, Some(note)) => {
+                let tx = self.connection.transaction()?;
+                Self::insert_or_replace_note(&tx, self.cipher.as_ref(), note)?;
+                tx.execute(
+                    "UPDATE notes SET is_deleted = ?1 WHERE id = ?2",
+                    rusqlite::params![ state == RecordState::Trashed, note.id ],
+                )?;
+                tx.commit()?;
+            }
+        }
+
+        let revision = self.connection
+            .query_row(
+                "SELECT revision FROM notes WHERE id = ?1
+                UNION ALL SELECT revision FROM tombstones WHERE note_id = ?1",
+                [&record.id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(revision.unwrap_or_default())
+    }
+
+    // 加载所有笔记的同步状态
+    pub fn load_sync_states(&self, scope: SyncScope) -> NotepadResult<HashMap<String, SyncState>> {
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT note_id, local_revision, remote_revision, conflict_revision FROM {}", scope.table()
+        ))?;
+        let states = stmt.query_map([], |row| {
+            Ok((row.get(0)?, SyncState {
+                local_revision: row.get(1)?,
+                remote_revision: row.get(2)?,
+                conflict_revision: row.get(3)?,
+            }))
+        })?.collect::<rusqlite::Result<HashMap<_, _>>>()?;
+        Ok(states)
+    }
+
+    // 保存同步状态，base 为双方最近一次一致时的笔记(为None时保留原值)
+    pub fn save_sync_state(&mut self, scope: SyncScope, note_id: &str, state: &SyncState, base: Option<&Note>) -> NotepadResult<()> {
+        let base = base.map(to_json).transpose()?
+            .map(|json| Self::seal_text(self.cipher.as_ref(), &json));
+        self.connection.execute(
+            &format!(
+                "INSERT INTO {table}
+                (note_id, local_revision, remote_revision, conflict_revision, base)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT(note_id) DO UPDATE SET
+                    local_revision = excluded.local_revision,
+                    remote_revision = excluded.remote_revision,
+                    conflict_revision = excluded.conflict_revision,
+                    base = COALESCE(excluded.base, {table}.base)",
+                table = scope.table(),
+            ),
+            rusqlite::params![
+                note_id,
+                state.local_revision,
+                state.remote_revision,
+                state.conflict_revision,
+                base,
+            ],
+        )?;
+        Ok(())
+    }
+
+    // 获取上次同步时双方一致的笔记内容(作为三方合并的祖先)
+    pub fn sync_base(&self, scope: SyncScope, note_id: &str) -> NotepadResult<Option<Note>> {
+        let base = self.connection
+            .query_row(
+                &format!("SELECT base FROM {} WHERE note_id = ?1", scope.table()),
+                [note_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()?
+            .flatten();
+        let base = base.map(|json| Self::open_text(self.cipher.as_ref(), json, note_id, "base")).transpose()?;
+        Ok(base.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    // 冲突已在本地解决，下次同步时以本地版本覆盖远端(和镜像目录)
+    pub fn resolve_sync_conflict(&mut self, note_id: &str) -> NotepadResult<()> {
+        let tx = self.connection.transaction()?;
+        for scope in [SyncScope::Remote, SyncScope::Mirror] {
+            tx.execute(
+                &format!(
+                    "UPDATE {} SET remote_revision = conflict_revision, conflict_revision = NULL
+                    WHERE note_id = ?1 AND conflict_revision IS NOT NULL",
+                    scope.table(),
+                ),
+                [note_id],
+            )?;
+        }
+        Ok(tx.commit()?)
+    }
+
+    // 清空同步状态(更换同步目标或镜像目录时使用)
+    pub fn reset_sync_state(&mut self, scope: SyncScope) -> NotepadResult<()> {
+        let tx = self.connection.transaction()?;
+        tx.execute(&format!("DELETE FROM {}", scope.table()), [])?;
+        match scope {
+            SyncScope::Remote => tx.execute("DELETE FROM sync_meta WHERE key LIKE 'cursor:%' AND key NOT LIKE 'cursor:mirror:%'", [])?,
+            SyncScope::Mirror => tx.execute("DELETE FROM sync_meta WHERE key LIKE 'cursor:mirror:%'", [])?,
+        };
+        Ok(tx.commit()?)
+    }
+
+    // 笔记变更戳: 任何保存、删除或恢复都会使修订号增加(用于检测是否需要刷新镜像目录)
+    pub fn change_stamp(&self) -> NotepadResult<u64> {
+        Ok(self.connection.query_row(
+            "SELECT (SELECT COALESCE(SUM(revision), 0) FROM notes)
+                + (SELECT COALESCE(SUM(revision), 0) FROM tombstones)",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn sync_meta(&self, key: &str) -> NotepadResult<Option<String>> {
+        Ok(self.connection
+            .query_row("SELECT value FROM sync_meta WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?)
+    }
+
+    pub fn set_sync_meta(&mut self, key: &str, value: Option<&str>) -> NotepadResult<()> {
+        match value {
+            Some(value) => self.connection.execute(
+                "INSERT OR REPLACE INTO sync_meta (key, value) VALUES (?1, ?2)",
+                [key, value],
+            )?,
+            None => self.connection.execute("DELETE FROM sync_meta WHERE key = ?1", [key])?,
+        };
+        Ok(())
+    }
+
+    // 逐条转换某篇笔记所有版本的内容(笔记加锁或移除密码时使用)
+    pub fn map_version_contents<F>(&mut self, note_id: &str, f: F) -> NotepadResult<()>
+    where F: Fn(String) -> NotepadResult<String> {
+        let tx = self.connection.transaction()?;
+        let rows = tx
+            .prepare("SELECT id, content FROM note_versions WHERE note_id = ?1")?
+            .query_map([note_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for (id, content) in rows {
+            let content = f(Self::open_text(self.cipher.as_ref(), content, &id, "content")?)?;
+            tx.execute(
+                "UPDATE note_versions SET content = ?1 WHERE id = ?2",
+                rusqlite::params![Self::seal_text(self.cipher.as_ref(), &content), id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // 保险库头(未启用加密时为None)
+    fn vault_header(&self) -> NotepadResult<Option<VaultHeader>> {
+        let header: Option<String> = self.connection
+            .query_row("SELECT header FROM vault WHERE id = 1", [], |row| row.get(0))
+            .optional()?;
+        header.map(|h| serde_json::from_str(&h).map_err(|_| corrupt("1", "header"))).transpose()
+    }
+
+    fn save_vault_header(tx: &Transaction, header: Option<&VaultHeader>) -> NotepadResult<()> {
+        match header {
+            Some(header) => tx.execute(
+                "INSERT OR REPLACE INTO vault (id, header) VALUES (1, ?1)",
+                [to_json(header)?],
+            )?,
+            None => tx.execute("DELETE FROM vault", [])?,
+        };
+        Ok(())
+    }
+
+    /// 是否启用了保险库加密
+    pub fn is_encrypted(&self) -> NotepadResult<bool> {
+        Ok(self.vault_header()?.is_some())
+    }
+
+    /// 已启用加密但尚未解锁
+    pub fn is_locked(&self) -> NotepadResult<bool> {
+        Ok(self.cipher.is_none() && self.is_encrypted()?)
+    }
+
+    /// 用口令解锁保险库
+    pub fn unlock(&mut self, passphrase: &str) -> NotepadResult<()> {
+        let header = self.vault_header()?
+            .ok_or_else(|| NotepadError::Invalid("the vault is not enabled".to_string()))?;
+        self.cipher = Some(header.unlock(passphrase).map_err(|e| NotepadError::Locked(e.to_string()))?);
+        Ok(())
+    }
+
+    /// 启用加密：生成数据密钥并加密全部已有笔记和版本
+    pub fn enable_encryption(&mut self, passphrase: &str) -> NotepadResult<()> {
+        if self.is_encrypted()? {
+            return Err(NotepadError::Invalid("the vault is already enabled".to_string()));
+        }
+        let (header, cipher) = VaultHeader::create(passphrase).map_err(NotepadError::crypto)?;
+        let tx = self.connection.transaction()?;
+        Self::reencrypt_all(&tx, None, Some(&cipher))?;
+        Self::save_vault_header(&tx, Some(&header))?;
+        tx.commit()?;
+        self.cipher = Some(cipher);
+        Ok(())
+    }
+
+    /// 关闭加密：解密全部笔记和版本
+    pub fn disable_encryption(&mut self, passphrase: &str) -> NotepadResult<()> {
+        let header = self.vault_header()?
+            .ok_or_else(|| NotepadError::Invalid("the vault is not enabled".to_string()))?;
+        let cipher = header.unlock(passphrase).map_err(|e| NotepadError::Locked(e.to_string()))?;
+        let tx = self.connection.transaction()?;
+        Self::reencrypt_all(&tx, Some(&cipher), None)?;
+        Self::save_vault_header(&tx, None)?;
+        tx.commit()?;
+        self.cipher = None;
+        Ok(())
+    }
+
+    /// 修改口令(只重新包装数据密钥)
+    pub fn change_passphrase(&mut self, old_passphrase: &str, new_passphrase: &str) -> NotepadResult<()> {
+        let header = self.vault_header()?
+            .ok_or_else(|| NotepadError::Invalid("the vault is not enabled".to_string()))?;
+        let header = header.rewrap(old_passphrase, new_passphrase).map_err(|e| NotepadError::Locked(e.to_string()))?;
+        let tx = self.connection.transaction()?;
+        Self::save_vault_header(&tx, Some(&header))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 启用加密时用数据密钥加密备份内容，否则返回None
+    pub fn seal_backup(&self, plaintext: &str) -> NotepadResult<Option<EncryptedBackup>> {
+        match (self.vault_header()?, self.cipher.as_ref()) {
+            (Some(header), Some(cipher)) => Ok(Some(EncryptedBackup::seal(&header, cipher, plaintext))),
+            (Some(_), None) => Err(NotepadError::Locked("the vault".to_string())),
+            _ => Ok(None),
+        }
+    }
+
+    // 将加密字段从一个密钥转换到另一个密钥(None表示明文)
+    fn reencrypt_all(tx: &Transaction, from: Option<&Cipher>, to: Option<&Cipher>) -> NotepadResult<()> {
+        let convert = |text: String, id: &str, field: &'static str| -> NotepadResult<String> {
+            Ok(Self::seal_text(to, &Self::open_text(from, text, id, field)?))
+        };
+
+        for table in ["notes", "note_versions"] {
+            let rows = tx
+                .prepare(&format!("SELECT id, title, content, tags FROM {}", table))?
+                .query_map([], |row| Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                )))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for (id, title, content, tags) in rows {
+                tx.execute(
+                    &format!("UPDATE {} SET title = ?1, content = ?2, tags = ?3 WHERE id = ?4", table),
+                    rusqlite::params![
+                        convert(title, &id, "title")?,
+                        convert(content, &id, "content")?,
+                        tags.map(|tags| convert(tags, &id, "tags")).transpose()?,
+                        id,
+                    ],
+                )?;
+            }
+        }
+
+        // 别名和自定义属性只存在于笔记表
+        let rows = tx
+            .prepare("SELECT id, aliases, properties FROM notes")?
+            .query_map([], |row| Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            )))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for (id, aliases, properties) in rows {
+            tx.execute(
+                "UPDATE notes SET aliases = ?1, properties = ?2 WHERE id = ?3",
+                rusqlite::params![
+                    aliases.map(|aliases| convert(aliases, &id, "aliases")).transpose()?,
+                    properties.map(|properties| convert(properties, &id, "properties")).transpose()?,
+                    id,
+                ],
+            )?;
+        }
+
+        for scope in [SyncScope::Remote, SyncScope::Mirror] {
+            let bases = tx
+                .prepare(&format!("SELECT note_id, base FROM {} WHERE base IS NOT NULL", scope.table()))?
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for (note_id, base) in bases {
+                tx.execute(
+                    &format!("UPDATE {} SET base = ?1 WHERE note_id = ?2", scope.table()),
+                    rusqlite::params![convert(base, &note_id, "base")?, note_id],
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn corrupt(id: &str, field: &'static str) -> NotepadError {
+    NotepadError::Corrupt { row: id.to_string(), field }
+}
+
+// 读取一列, 类型不符时返回 Corrupt 错误
+fn column<T: FromSql>(row: &Row, idx: usize, id: &str, field: &'static str) -> NotepadResult<T> {
+    row.get(idx).map_err(|e| match e {
+        rusqlite::Error::FromSqlConversionFailure(..)
+        | rusqlite::Error::InvalidColumnType(..)
+        | rusqlite::Error::IntegralValueOutOfRange(..) => corrupt(id, field),
+        e => NotepadError::Database(e),
+    })
+}
+
+fn parse_time(text: &str, id: &str, field: &'static str) -> NotepadResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| corrupt(id, field))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> NotepadResult<String> {
+    serde_json::to_string(value).map_err(NotepadError::format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_encrypts_rows() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("notes.db");
+        let mut note = Note::new("bank".to_string());
+        note.update_content("pin: 1234".to_string());
+        {
+            let mut db = Database::open(&path).unwrap();
+            db.save_note(&note).unwrap();
+            db.enable_encryption("secret").unwrap();
+            db.save_version(&NoteVersion::new("saved", &note)).unwrap();
+        }
+
+        let mut db = Database::open(&path).unwrap();
+        assert!(db.is_locked().unwrap());
+        let raw: String = db.connection
+            .query_row("SELECT title || content FROM notes", [], |row| row.get(0))
+            .unwrap();
+        assert!(!raw.contains("bank") && !raw.contains("1234"));
+        assert!(db.load_all_notes().is_err());
+
+        assert!(db.unlock("wrong").is_err());
+        db.change_passphrase("secret", "new secret").unwrap();
+        db.unlock("new secret").unwrap();
+        assert_eq!(db.load_all_notes().unwrap().find_note(note.id()).unwrap().content(), "pin: 1234");
+        assert_eq!(db.load_version_history(note.id()).unwrap()[0].content(), "pin: 1234");
+
+        db.disable_encryption("new secret").unwrap();
+        let raw: String = db.connection
+            .query_row("SELECT content FROM notes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(raw, "pin: 1234");
+        drop(db);
+    }
+
+    #[test]
+    fn test_quarantine_corrupt_rows() {
+        let mut db = Database::open_in_memory().unwrap();
+        let good = Note::new("good".to_string());
+        let bad = Note::new("bad".to_string());
+        db.save_note(&good).unwrap();
+        db.save_note(&bad).unwrap();
+        db.save_version(&NoteVersion::new("v1", &bad)).unwrap();
+        db.connection.execute("UPDATE notes SET updated_at = 'yesterday' WHERE id = ?1", [bad.id()]).unwrap();
+        db.save_version(&NoteVersion::new("v1", &good)).unwrap();
+        db.connection.execute("UPDATE note_versions SET tags = '{' WHERE note_id = ?1", [good.id()]).unwrap();
+
+        // 损坏的行被跳过, 读取时不修改数据库
+        let notebook = db.load_all_notes().unwrap();
+        assert!(notebook.find_note(good.id()).is_some() && notebook.find_note(bad.id()).is_none());
+        assert!(db.load_version_history(good.id()).unwrap().is_empty());
+        let count: usize = db.connection.query_row("SELECT COUNT(*) FROM quarantine", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+
+        // 移入隔离表, 原始数据保留
+        let quarantined = db.quarantine_corrupt_rows().unwrap();
+        assert_eq!(quarantined.len(), 2);
+        assert!(matches!(&quarantined[0], NotepadError::Corrupt { row, field: "updated_at" } if row == bad.id()));
+        let data: String = db.connection
+            .query_row("SELECT data FROM quarantine WHERE source = 'notes'", [], |row| row.get(0))
+            .unwrap();
+        assert!(data.contains("yesterday"));
+        // 损坏笔记的版本随笔记一起隔离
+        let count: usize = db.connection
+            .query_row("SELECT COUNT(*) FROM quarantine WHERE source = 'note_versions'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!(db.quarantine_corrupt_rows().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_quarantine_undecryptable_rows() {
+        let mut db = Database::open_in_memory().unwrap();
+        db.enable_encryption("secret").unwrap();
+        let good = Note::new("good".to_string());
+        let tampered = Note::new("tampered".to_string());
+        let mistyped = Note::new("mistyped".to_string());
+        db.save_notes(&[good.clone(), tampered.clone(), mistyped.clone()]).unwrap();
+        db.connection.execute("UPDATE notes SET content = 'enc:v1:broken' WHERE id = ?1", [tampered.id()]).unwrap();
+        db.connection.execute("UPDATE notes SET is_pinned = 'maybe' WHERE id = ?1", [mistyped.id()]).unwrap();
+
+        // 无法解密或类型不符的行同样被跳过并隔离
+        let notebook = db.load_all_notes().unwrap();
+        assert!(notebook.find_note(good.id()).is_some());
+        assert!(notebook.find_note(tampered.id()).is_none() && notebook.find_note(mistyped.id()).is_none());
+        let quarantined = db.quarantine_corrupt_rows().unwrap();
+        assert!(quarantined.iter().any(|e| matches!(e, NotepadError::Corrupt { row, field: "content" } if row == tampered.id())));
+        assert!(quarantined.iter().any(|e| matches!(e, NotepadError::Corrupt { row, field: "is_pinned" } if row == mistyped.id())));
     }
 }
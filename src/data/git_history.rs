@@ -0,0 +1,312 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::NoteVersion;
+
+// 提交说明中记录版本id的行(迁移时用于跳过已导入的版本)
+const VERSION_TRAILER: &str = "Version-Id: ";
+const COMMITTER: &str = "Cloud Notepad";
+// git log 的输出格式: 每条提交以 \x1e 开头, 哈希值、提交时间和提交说明以 \x1f 分隔, 其后为修改的文件
+const LOG_FORMAT: &str = "--format=%x1e%H%x1f%at%x1f%B%x1f";
+
+// 版本文件的 front matter
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    title: String,
+    #[serde(default)]
+    tags: BTreeSet<String>,
+}
+
+// git log 中的一条提交
+struct Commit {
+    hash: String,
+    timestamp: i64,
+    comment: String,
+    saved_id: Option<String>,   // 提交说明中记录的版本id
+    paths: Vec<String>,
+}
+
+impl Commit {
+    fn parse(record: &str) -> Result<Self> {
+        let mut fields = record.splitn(4, '\x1f');
+        let (Some(hash), Some(timestamp), Some(message), Some(paths)) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+            anyhow::bail!("Unexpected git log output: {}", record.lines().next().unwrap_or_default());
+        };
+        let comment = message.lines()
+            .take_while(|line| !line.starts_with(VERSION_TRAILER))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let saved_id = message.lines()
+            .find_map(|line| line.strip_prefix(VERSION_TRAILER))
+            .map(|id| id.trim().to_string());
+        Ok(Self {
+            hash: hash.trim().to_string(),
+            timestamp: timestamp.trim().parse()?,
+            comment: comment.trim().to_string(),
+            saved_id,
+            paths: paths.lines().filter(|line| !line.is_empty()).map(str::to_string).collect(),
+        })
+    }
+}
+
+/// 以本地git仓库保存版本历史: 每个笔记一个文件, 每个版本一次提交(提交说明为版本备注),
+/// 版本id为提交的哈希值
+pub struct GitHistory {
+    root: PathBuf,
+}
+
+impl GitHistory {
+    // 打开仓库, 不存在时初始化
+    pub fn open(root: &Path) -> Result<Self> {
+        std::fs::create_dir_all(root.join("notes"))?;
+        let history = Self { root: root.to_path_buf() };
+        if !root.join(".git").exists() {
+            history.git(&["init", "--quiet"], None)?;
+        }
+        Ok(history)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // 笔记id一般为UUID，其他字符编码后再作为文件名
+    fn note_path(note_id: &str) -> String {
+        let stem = if note_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            note_id.to_string()
+        } else {
+            note_id.bytes().map(|b| format!("{:02x}", b)).collect()
+        };
+        format!("notes/{}.md", stem)
+    }
+
+    // 执行git命令并返回标准输出, date 为提交时间
+    fn git(&self, args: &[&str], date: Option<DateTime<Utc>>) -> Result<String> {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.root)
+            .args(["-c", "commit.gpgsign=false", "-c", "core.quotepath=false"])
+            .args(args)
+            .env("GIT_AUTHOR_NAME", COMMITTER)
+            .env("GIT_AUTHOR_EMAIL", "notepad@localhost")
+            .env("GIT_COMMITTER_NAME", COMMITTER)
+            .env("GIT_COMMITTER_EMAIL", "notepad@localhost");
+        if let Some(date) = date {
+            command.env("GIT_AUTHOR_DATE", date.to_rfc3339())
+                .env("GIT_COMMITTER_DATE", date.to_rfc3339());
+        }
+        let output = command.output()
+            .map_err(|e| anyhow::anyhow!("Failed to run git (is it installed?): {}", e))?;
+        if !output.status.success() {
+            anyhow::bail!("git {} failed: {}", args.first().unwrap_or(&""), String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    // 提交一个版本, 返回提交的哈希值
+    pub fn commit_version(&self, version: &NoteVersion) -> Result<String> {
+        let path = Self::note_path(&version.note_id);
+        let snapshot = Snapshot { title: version.title.clone(), tags: version.tags.iter().cloned().collect() };
+        let text = format!("---\n{}---\n\n{}", serde_yaml::to_string(&snapshot)?, version.content);
+        std::fs::write(self.root.join(&path), text)?;
+
+        let message = format!("{}\n\n{}{}", version.comment, VERSION_TRAILER, version.id);
+        self.git(&["add", "--", &path], None)?;
+        // 内容未变化时也保留这次版本
+        self.git(&["commit", "--quiet", "--allow-empty", "-m", &message, "--", &path], Some(version.saved_at))?;
+        Ok(self.git(&["rev-parse", "HEAD"], None)?.trim().to_string())
+    }
+
+    // 笔记的所有版本(从新到旧)
+    pub fn load_versions(&self, note_id: &str) -> Result<Vec<NoteVersion>> {
+        self.read_versions(&[note_id], false)
+    }
+
+    // 笔记的所有版本(从新到旧), 版本id为保存时的id而非提交的哈希值, 用于同步和迁回数据库
+    pub fn load_saved_versions(&self, note_id: &str) -> Result<Vec<NoteVersion>> {
+        self.read_versions(&[note_id], true)
+    }
+
+    // 多篇笔记的所有版本(从新到旧), 版本id为保存时的id
+    pub fn load_all_saved_versions(&self, note_ids: &[&str]) -> Result<Vec<NoteVersion>> {
+        self.read_versions(note_ids, true)
+    }
+
+    // 一次 git log 列出修改过这些笔记文件的提交, 再一次 git cat-file 读出各版本的文件
+    fn read_versions(&self, note_ids: &[&str], saved_ids: bool) -> Result<Vec<NoteVersion>> {
+        let paths: HashMap<String, &str> = note_ids.iter().map(|id| (Self::note_path(id), *id)).collect();
+        // 单篇笔记时按路径过滤, 否则读取全部提交后再筛选
+        let commits = match paths.keys().collect::<Vec<_>>().as_slice() {
+            [] => return Ok(Vec::new()),
+            [path] => self.log(&["--", path.as_str()])?,
+            _ => self.log(&[])?,
+        };
+        let paths = &paths;
+        let entries: Vec<(&Commit, &str, &str)> = commits.iter()
+            .flat_map(|commit| commit.paths.iter().filter_map(move |path| {
+                paths.get(path).map(|note_id| (commit, path.as_str(), *note_id))
+            }))
+            .collect();
+        let objects: Vec<String> = entries.iter().map(|(commit, path, _)| format!("{}:{}", commit.hash, path)).collect();
+        let files = self.cat_files(&objects)?;
+        entries.iter().zip(files)
+            .map(|((commit, path, note_id), text)| {
+                let mut version = Self::parse_version(commit, note_id, path, &text)?;
+                if saved_ids && let Some(id) = &commit.saved_id {
+                    version.id = id.clone();
+                }
+                Ok(version)
+            })
+            .collect()
+    }
+
+    // 提交记录(从新到旧), args 为附加的 git log 参数
+    fn log(&self, args: &[&str]) -> Result<Vec<Commit>> {
+        if !self.has_commits()? {
+            return Ok(Vec::new());
+        }
+        let mut log_args = vec!["log", LOG_FORMAT, "--name-only"];
+        log_args.extend_from_slice(args);
+        self.git(&log_args, None)?
+            .split('\x1e')
+            .filter(|record| !record.trim().is_empty())
+            .map(Commit::parse)
+            .collect()
+    }
+
+    // 通过 git cat-file --batch 按顺序读取多个 "<提交>:<路径>" 文件的内容
+    fn cat_files(&self, objects: &[String]) -> Result<Vec<String>> {
+        if objects.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut child = Command::new("git")
+            .arg("-C").arg(&self.root)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to run git (is it installed?): {}", e))?;
+        // 另起线程写入, 避免输出填满管道时互相等待
+        let mut stdin = child.stdin.take().ok_or_else(|| anyhow::anyhow!("Failed to open git stdin"))?;
+        let input = objects.join("\n") + "\n";
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child.wait_with_output()?;
+        writer.join().map_err(|_| anyhow::anyhow!("Failed to write to git"))??;
+        if !output.status.success() {
+            anyhow::bail!("git cat-file failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let mut files = Vec::with_capacity(objects.len());
+        let mut rest = output.stdout.as_slice();
+        for object in objects.iter() {
+            // 每个文件: "<哈希> blob <大小>\n<内容>\n"
+            let newline = rest.iter().position(|b| *b == b'\n')
+                .ok_or_else(|| anyhow::anyhow!("Unexpected git cat-file output for {}", object))?;
+            let header = String::from_utf8_lossy(&rest[..newline]).to_string();
+            let size: usize = match header.split(' ').collect::<Vec<_>>().as_slice() {
+                [_, "blob", size] => size.parse()?,
+                _ => anyhow::bail!("Missing version file {}: {}", object, header),
+            };
+            let body = rest.get(newline + 1..newline + 1 + size)
+                .ok_or_else(|| anyhow::anyhow!("Truncated git cat-file output for {}", object))?;
+            files.push(String::from_utf8_lossy(body).to_string());
+            rest = rest.get(newline + 2 + size..).unwrap_or_default();
+        }
+        Ok(files)
+    }
+
+    // 读取某次提交中的笔记版本
+    pub fn load_version(&self, note_id: &str, commit: &str) -> Result<NoteVersion> {
+        let path = Self::note_path(note_id);
+        let header = self.git(&["show", "--no-patch", LOG_FORMAT, commit], None)?;
+        let commit = Commit::parse(header.trim_start_matches('\x1e'))?;
+        let text = self.cat_files(&[format!("{}:{}", commit.hash, path)])?.remove(0);
+        Self::parse_version(&commit, note_id, &path, &text)
+    }
+
+    // 解析版本文件(front matter + 正文)
+    fn parse_version(commit: &Commit, note_id: &str, path: &str, text: &str) -> Result<NoteVersion> {
+        let (snapshot, content) = text.strip_prefix("---\n")
+            .and_then(|rest| rest.split_once("\n---\n\n"))
+            .ok_or_else(|| anyhow::anyhow!("Invalid version file {} in {}", path, commit.hash))?;
+        let snapshot: Snapshot = serde_yaml::from_str(snapshot)?;
+        Ok(NoteVersion {
+            id: commit.hash.clone(),
+            note_id: note_id.to_string(),
+            title: snapshot.title,
+            content: content.to_string(),
+            tags: snapshot.tags.into_iter().collect(),
+            comment: commit.comment.clone(),
+            saved_at: DateTime::from_timestamp(commit.timestamp, 0).unwrap_or_default(),
+        })
+    }
+
+    // 将数据库中的版本按保存时间依次提交, 已提交过的版本(按版本id)被跳过, 返回新提交的数量
+    pub fn migrate(&self, versions: &[NoteVersion]) -> Result<usize> {
+        let committed: HashSet<String> = if self.has_commits()? {
+            self.git(&["log", "--format=%B"], None)?
+                .lines()
+                .filter_map(|line| line.strip_prefix(VERSION_TRAILER))
+                .map(str::to_string)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        let mut versions: Vec<&NoteVersion> = versions.iter().filter(|v| !committed.contains(&v.id)).collect();
+        versions.sort_by_key(|v| v.saved_at);
+        for version in versions.iter() {
+            self.commit_version(version)?;
+        }
+        Ok(versions.len())
+    }
+
+    fn has_commits(&self) -> Result<bool> {
+        Ok(self.git(&["rev-parse", "--verify", "--quiet", "HEAD"], None).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::Note;
+    use super::*;
+
+    #[test]
+    fn test_git_history() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let history = GitHistory::open(root).unwrap();
+
+        let mut note = Note::new("计划".to_string());
+        note.update_content("first\n".to_string());
+        note.add_tag("工作".to_string());
+        let first = NoteVersion::new("初稿", &note);
+        note.update_content("second\n---\n\nmore\n".to_string());
+        let second = NoteVersion::new("", &note);
+
+        // 迁移按保存时间提交, 重复迁移时跳过已提交的版本
+        let migrated = [second, first];
+        assert_eq!(history.migrate(&migrated).unwrap(), 2);
+        assert_eq!(history.migrate(&migrated).unwrap(), 0);
+        let versions = history.load_versions(note.id()).unwrap();
+        assert_eq!(versions.len(), 2);
+        let oldest = &versions[1];
+        assert_eq!((oldest.comment(), oldest.content(), oldest.title()), ("初稿", "first\n", "计划"));
+        assert!(oldest.tags().contains("工作"));
+        assert_eq!(history.load_version(note.id(), versions[0].id()).unwrap().content(), "second\n---\n\nmore\n");
+        // 按保存时的id读取
+        let saved: Vec<String> = history.load_saved_versions(note.id()).unwrap().iter().map(|v| v.id().to_string()).collect();
+        assert_eq!(saved, [migrated[0].id(), migrated[1].id()]);
+
+        // 一次读取多篇笔记的版本
+        let other = Note::new("其他".to_string());
+        history.commit_version(&NoteVersion::new("", &other)).unwrap();
+        let all = history.load_all_saved_versions(&[note.id(), other.id()]).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!((all[0].note_id(), all[0].title()), (other.id(), "其他"));
+        assert_eq!(all[2].content(), "first\n");
+    }
+}
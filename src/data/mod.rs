@@ -5,7 +5,14 @@ pub use models::{
     delete_note::DeleteNote,
     note_version::NoteVersion,
     export_note::ExportNote,
+    note_merge::{NoteMerge, HunkResolution},
+    sync_record::{SyncRecord, SyncState, SyncScope, RecordState},
 };
 
 pub mod db;
-pub use db::Database;
\ No newline at end of file
+pub use db::Database;
+
+pub mod git_history;
+pub use git_history::GitHistory;
+pub mod version_store;
+pub use version_store::VersionStore;
@@ -1,10 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc, Local};
 use super::note::Note;
 
 // 导出笔记数据结构
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportNote {
     pub(crate) id: Option<String>,
     pub(crate) title: String,
@@ -12,6 +12,12 @@ pub struct ExportNote {
     content: String,
     pub(crate) created: Option<DateTime<Utc>>,
     pub(crate) updated: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) pinned: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) properties: BTreeMap<String, serde_json::Value>,
 }
 
 impl ExportNote {
@@ -30,6 +36,9 @@ impl ExportNote {
             content,
             created,
             updated,
+            pinned: false,
+            aliases: Vec::new(),
+            properties: BTreeMap::new(),
         }
     }
 
@@ -41,6 +50,14 @@ impl ExportNote {
         &self.tags
     }
 
+    pub(crate) fn set_content(&mut self, content: String) {
+        self.content = content;
+    }
+
+    pub fn add_tag(&mut self, tag: String) {
+        self.tags.insert(tag);
+    }
+
     pub fn content(&self) -> &str {
         &self.content
     }
@@ -59,7 +76,10 @@ impl ExportNote {
             tags: self.tags.clone(),
             created_at: self.created.unwrap_or(Utc::now()),
             updated_at: self.updated.unwrap_or(Utc::now()),
-            is_pinned: false,
+            is_pinned: self.pinned,
+            lock: None,
+            aliases: self.aliases.clone(),
+            properties: self.properties.clone(),
         }
     }
 
@@ -70,7 +90,10 @@ impl ExportNote {
             content: note.content.clone(),
             tags: note.tags.clone(),
             created: Some(note.created_at),
-            updated: Some(note.updated_at)
+            updated: Some(note.updated_at),
+            pinned: note.is_pinned,
+            aliases: note.aliases.clone(),
+            properties: note.properties.clone(),
         }
     }
 }
\ No newline at end of file
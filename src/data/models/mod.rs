@@ -2,4 +2,6 @@ pub mod note;
 pub mod notebook;
 pub mod delete_note;
 pub mod note_version;
-pub mod export_note;
\ No newline at end of file
+pub mod export_note;
+pub mod note_merge;
+pub mod sync_record;
\ No newline at end of file
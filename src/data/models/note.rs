@@ -1,6 +1,7 @@
 use chrono::{DateTime, Local, Utc};
 use serde::{Serialize, Deserialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use crate::utils::crypto::{self, VaultHeader};
 use super::note_version::NoteVersion;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +13,15 @@ pub struct Note {
     pub(in crate::data) created_at: DateTime<Utc>,
     pub(in crate::data) updated_at: DateTime<Utc>,
     pub(in crate::data) is_pinned: bool,
+    // 笔记密码包装后的笔记密钥(未加锁时为None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(in crate::data) lock: Option<VaultHeader>,
+    // 别名(其他名称, 可作为链接目标)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(in crate::data) aliases: Vec<String>,
+    // 自定义属性(导入时 front matter 中的其他键)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(in crate::data) properties: BTreeMap<String, serde_json::Value>,
 }
 
 impl Note {
@@ -25,6 +35,9 @@ impl Note {
             created_at: now,
             updated_at: now,
             is_pinned: false,
+            lock: None,
+            aliases: Vec::new(),
+            properties: BTreeMap::new(),
         }
     }
 
@@ -55,6 +68,41 @@ impl Note {
         &self.tags
     }
 
+    pub fn is_pinned(&self) -> bool {
+        self.is_pinned
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn properties(&self) -> &BTreeMap<String, serde_json::Value> {
+        &self.properties
+    }
+
+    /// 是否设置了笔记密码
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    /// 已加锁且内容尚未在本次会话中解锁
+    pub fn is_sealed(&self) -> bool {
+        self.is_locked() && crypto::is_locked_text(&self.content)
+    }
+
+    pub fn lock(&self) -> Option<&VaultHeader> {
+        self.lock.as_ref()
+    }
+
+    pub(crate) fn set_lock(&mut self, lock: Option<VaultHeader>) {
+        self.lock = lock;
+    }
+
+    // 加锁/解锁时替换内容(不刷新更新时间)
+    pub(crate) fn set_content(&mut self, content: String) {
+        self.content = content;
+    }
+
     pub fn updated_at(&self) -> String {
         format!("{}", self.updated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"))
     }
@@ -81,10 +129,16 @@ impl Note {
         }
     }
 
-    /// 判断笔记中(标题和内容)是否含有关键字符串
+    /// 判断笔记中(标题和内容)是否含有关键字符串，加锁笔记只匹配标题
     pub fn contains(&self, key: &str, case_sensitive: Option<bool>) -> bool {
         let case_sensitive = case_sensitive.unwrap_or(false);
-        if case_sensitive {
+        if self.is_locked() {
+            if case_sensitive {
+                self.title.contains(key)
+            } else {
+                self.title.to_lowercase().contains(&key.to_lowercase())
+            }
+        } else if case_sensitive {
             self.title.contains(key) || self.content.contains(key)
         } else {
             self.title.to_lowercase().contains(&key.to_lowercase())
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+use chrono::Utc;
+use crate::utils::diff::{merge3, MergeHunk};
+use super::{note::Note, note_version::NoteVersion};
+
+/// 冲突片段的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkResolution {
+    Ours,       // 保留本地修改
+    Theirs,     // 采用对方修改
+    Both,       // 两者都保留(本地在前)
+}
+
+/// 同一笔记两个分叉副本的三方合并结果
+#[derive(Debug, Clone)]
+pub struct NoteMerge {
+    pub(in crate::data) ours: Note,
+    pub(in crate::data) theirs: Note,
+    pub(in crate::data) title: String,
+    pub(in crate::data) tags: HashSet<String>,
+    pub(in crate::data) hunks: Vec<MergeHunk>,
+    pub(in crate::data) resolutions: Vec<Option<HunkResolution>>,
+    // 任一副本为加锁密文时不能逐行合并，只能整体保留一方
+    pub(in crate::data) whole_note: bool,
+}
+
+impl NoteMerge {
+    /// 以最近共同祖先为基准合并本地副本(ours)与对方副本(theirs)
+    pub fn new(base: Option<&NoteVersion>, ours: &Note, theirs: &Note) -> Self {
+        if ours.is_sealed() || theirs.is_sealed() {
+            return Self::whole(ours, theirs);
+        }
+        let base_content = base.map(|v| v.content()).unwrap_or_default();
+        let hunks = merge3(base_content, ours.content(), theirs.content());
+
+        // 标题：只有一方修改时采用修改方，双方都修改时采用较新的一方
+        let title = match base.map(|v| v.title()) {
+            Some(base_title) if ours.title() == base_title => theirs.title().to_string(),
+            Some(base_title) if theirs.title() == base_title => ours.title().to_string(),
+            _ if theirs.updated_at > ours.updated_at => theirs.title().to_string(),
+            _ => ours.title().to_string(),
+        };
+
+        // 标签取并集
+        let tags = ours.tags().union(theirs.tags()).cloned().collect();
+
+        Self {
+            ours: ours.clone(),
+            theirs: theirs.clone(),
+            title,
+            tags,
+            resolutions: vec![None; hunks.len()],
+            hunks,
+            whole_note: false,
+        }
+    }
+
+    // 整篇笔记作为一个冲突片段(密文逐行合并会破坏内容)
+    fn whole(ours: &Note, theirs: &Note) -> Self {
+        Self {
+            ours: ours.clone(),
+            theirs: theirs.clone(),
+            title: ours.title().to_string(),
+            tags: ours.tags().clone(),
+            hunks: vec![MergeHunk::Conflict { base: Vec::new(), ours: Vec::new(), theirs: Vec::new() }],
+            resolutions: vec![None],
+            whole_note: true,
+        }
+    }
+
+    pub fn note_id(&self) -> &str {
+        self.ours.id()
+    }
+
+    pub fn ours(&self) -> &Note {
+        &self.ours
+    }
+
+    pub fn theirs(&self) -> &Note {
+        &self.theirs
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn tags(&self) -> &HashSet<String> {
+        &self.tags
+    }
+
+    /// 是否只能整体选择一方副本(加锁笔记)
+    pub fn is_whole_note(&self) -> bool {
+        self.whole_note
+    }
+
+    pub fn hunks(&self) -> &[MergeHunk] {
+        &self.hunks
+    }
+
+    pub fn resolution(&self, idx: usize) -> Option<HunkResolution> {
+        self.resolutions.get(idx).copied().flatten()
+    }
+
+    /// 设置冲突片段的处理方式
+    pub fn resolve(&mut self, idx: usize, resolution: HunkResolution) {
+        if self.whole_note && resolution == HunkResolution::Both {
+            return;
+        }
+        if self.hunks.get(idx).is_some_and(|h| h.is_conflict()) {
+            self.resolutions[idx] = Some(resolution);
+        }
+    }
+
+    /// 冲突片段总数
+    pub fn conflict_count(&self) -> usize {
+        self.hunks.iter().filter(|h| h.is_conflict()).count()
+    }
+
+    /// 尚未处理的冲突片段数
+    pub fn unresolved_count(&self) -> usize {
+        self.hunks.iter()
+            .zip(self.resolutions.iter())
+            .filter(|(h, r)| h.is_conflict() && r.is_none())
+            .count()
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.unresolved_count() == 0
+    }
+
+    /// 生成合并后的笔记(仍有未处理冲突时返回None)
+    pub fn to_note(&self) -> Option<Note> {
+        if self.whole_note {
+            let mut note = match self.resolutions[0]? {
+                HunkResolution::Theirs => self.theirs.clone(),
+                _ => self.ours.clone(),
+            };
+            note.updated_at = Utc::now();
+            return Some(note);
+        }
+        let mut content = String::new();
+        for (hunk, resolution) in self.hunks.iter().zip(self.resolutions.iter()) {
+            match (hunk, resolution) {
+                (MergeHunk::Resolved(lines), _) => content.extend(lines.iter().cloned()),
+                (MergeHunk::Conflict { ours, theirs, .. }, Some(resolution)) => {
+                    if matches!(resolution, HunkResolution::Ours | HunkResolution::Both) {
+                        content.extend(ours.iter().cloned());
+                    }
+                    if matches!(resolution, HunkResolution::Theirs | HunkResolution::Both) {
+                        content.extend(theirs.iter().cloned());
+                    }
+                }
+                (MergeHunk::Conflict { .. }, None) => return None,
+            }
+        }
+
+        let mut note = self.ours.clone();
+        note.title = self.title.clone();
+        note.content = content;
+        note.tags = self.tags.clone();
+        note.created_at = note.created_at.min(self.theirs.created_at);
+        note.updated_at = Utc::now();
+        note.is_pinned |= self.theirs.is_pinned;
+        Some(note)
+    }
+
+    /// 从版本历史中找出两个副本的最近共同祖先
+    pub fn find_common_ancestor<'a>(versions: &'a [NoteVersion], ours: &Note, theirs: &Note) -> Option<&'a NoteVersion> {
+        let diverged_at = ours.updated_at.min(theirs.updated_at);
+        versions.iter()
+            .filter(|v| v.saved_at <= diverged_at)
+            .max_by_key(|v| v.saved_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::crypto::VaultHeader;
+    use super::*;
+
+    #[test]
+    fn test_sealed_notes_merge_as_whole() {
+        let (header, cipher) = VaultHeader::create("secret").unwrap();
+        let mut ours = Note::new("diary".to_string());
+        ours.content = cipher.lock_text("line one\nline two\n");
+        ours.lock = Some(header);
+        let mut theirs = ours.clone();
+        theirs.content = cipher.lock_text("line one\nline three\n");
+
+        let mut merge = NoteMerge::new(None, &ours, &theirs);
+        assert!(merge.is_whole_note());
+        assert_eq!(merge.conflict_count(), 1);
+        assert!(merge.to_note().is_none());
+
+        // 不允许拼接两份密文
+        merge.resolve(0, HunkResolution::Both);
+        assert!(!merge.is_resolved());
+        merge.resolve(0, HunkResolution::Theirs);
+        let note = merge.to_note().unwrap();
+        assert_eq!(note.content(), theirs.content());
+        assert_eq!(cipher.unlock_text(note.content()).unwrap(), "line one\nline three\n");
+    }
+}
@@ -43,6 +43,10 @@ impl NoteVersion {
         &self.content
     }
 
+    pub(crate) fn set_content(&mut self, content: String) {
+        self.content = content;
+    }
+
     pub fn tags(&self) -> &HashSet<String> {
         &self.tags
     }
@@ -0,0 +1,109 @@
+use serde::{Serialize, Deserialize};
+use super::note::Note;
+
+/// 笔记在同步中的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordState {
+    Active,     // 正常笔记
+    Trashed,    // 已移入回收站
+    Deleted,    // 已永久删除(墓碑)
+}
+
+/// 单条笔记的同步记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub(in crate::data) id: String,
+    pub(in crate::data) revision: u64,
+    pub(in crate::data) state: RecordState,
+    pub(in crate::data) note: Option<Note>,   // 墓碑记录没有笔记内容
+}
+
+impl SyncRecord {
+    pub fn new(note: Note, state: RecordState, revision: u64) -> Self {
+        Self {
+            id: note.id().to_string(),
+            revision,
+            state,
+            note: Some(note),
+        }
+    }
+
+    pub fn tombstone(id: &str, revision: u64) -> Self {
+        Self {
+            id: id.to_string(),
+            revision,
+            state: RecordState::Deleted,
+            note: None,
+        }
+    }
+
+    // 内容未知的回收站记录(镜像目录中的文件被删除)
+    pub fn trashed(id: &str, revision: u64) -> Self {
+        Self {
+            id: id.to_string(),
+            revision,
+            state: RecordState::Trashed,
+            note: None,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn set_revision(&mut self, revision: u64) {
+        self.revision = revision;
+    }
+
+    pub fn state(&self) -> RecordState {
+        self.state
+    }
+
+    pub fn note(&self) -> Option<&Note> {
+        self.note.as_ref()
+    }
+
+    /// 判断两条记录的状态和内容是否一致(忽略修订号和更新时间)
+    pub fn same_content(&self, other: &SyncRecord) -> bool {
+        self.state == other.state
+            && match (&self.note, &other.note) {
+                (Some(a), Some(b)) => a.title == b.title
+                    && a.content == b.content
+                    && a.tags == b.tags
+                    && a.is_pinned == b.is_pinned
+                    && a.aliases == b.aliases
+                    && a.properties == b.properties
+                    && a.lock == b.lock,
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
+/// 笔记上次同步时的本地/远端修订号
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncState {
+    pub local_revision: u64,
+    pub remote_revision: u64,
+    pub conflict_revision: Option<u64>,    // 尚未解决冲突的远端修订号
+}
+
+/// 同步状态的作用域: 远端同步(服务器、同步目录)和镜像目录分别记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncScope {
+    Remote,
+    Mirror,
+}
+
+impl SyncScope {
+    pub(in crate::data) fn table(&self) -> &'static str {
+        match self {
+            Self::Remote => "sync_state",
+            Self::Mirror => "mirror_state",
+        }
+    }
+}
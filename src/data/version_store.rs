@@ -0,0 +1,82 @@
+use crate::error::{NotepadError, NotepadResult};
+use super::{Database, GitHistory, Note, NoteVersion};
+
+/// 版本历史的存储位置: 启用git版本历史时为仓库, 否则为数据库
+///
+/// 所有版本的读写都经过这里, 避免在启用git版本历史时读写数据库中的空表
+#[derive(Clone, Copy)]
+pub enum VersionStore<'a> {
+    Database,
+    Git(&'a GitHistory),
+}
+
+impl<'a> VersionStore<'a> {
+    pub fn new(history: Option<&'a GitHistory>) -> Self {
+        match history {
+            Some(history) => Self::Git(history),
+            None => Self::Database,
+        }
+    }
+
+    // 笔记的所有版本(从新到旧), 仓库中的版本id为提交的哈希值
+    pub fn load(&self, db: &Database, note_id: &str) -> NotepadResult<Vec<NoteVersion>> {
+        match self {
+            Self::Database => db.load_version_history(note_id),
+            Self::Git(history) => history.load_versions(note_id).map_err(NotepadError::history),
+        }
+    }
+
+    // 所有笔记(含回收站中的)的版本, 按保存时间从旧到新, 版本id在各设备间保持一致(用于同步)
+    pub fn load_all(&self, db: &Database) -> NotepadResult<Vec<NoteVersion>> {
+        let Self::Git(history) = self else {
+            return db.load_all_versions();
+        };
+        let notes = db.load_notes_with_trash()?;
+        let ids: Vec<&str> = notes.iter().map(|(note, _)| note.id()).collect();
+        let mut versions = history.load_all_saved_versions(&ids).map_err(NotepadError::history)?;
+        versions.sort_by_key(|v| v.saved_at);
+        Ok(versions)
+    }
+
+    pub fn save(&self, db: &mut Database, version: &NoteVersion) -> NotepadResult<()> {
+        match self {
+            Self::Database => db.save_version(version),
+            Self::Git(history) => {
+                history.commit_version(version).map_err(NotepadError::history)?;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn delete(&self, db: &mut Database, version_id: &str) -> NotepadResult<()> {
+        match self {
+            Self::Database => db.delete_version(version_id),
+            Self::Git(_) => Err(NotepadError::History("Versions stored in the git repository cannot be deleted".to_string())),
+        }
+    }
+
+    // 逐条转换某篇笔记所有版本的内容(笔记加锁或移除密码时使用), 仓库中的历史不能改写
+    pub fn map_contents<F>(&self, db: &mut Database, note_id: &str, f: F) -> NotepadResult<()>
+    where F: Fn(String) -> NotepadResult<String> {
+        match self {
+            Self::Database => db.map_version_contents(note_id, f),
+            Self::Git(_) => Err(NotepadError::History("Versions stored in the git repository cannot be rewritten".to_string())),
+        }
+    }
+
+    // 恢复备份的笔记和版本, 返回写入的笔记数
+    //
+    // 替换模式会清空数据库中的版本, 但仓库中的历史不能改写: 启用git版本历史时,
+    // 原有笔记的版本仍保留在仓库中, 备份中的版本按版本id追加提交
+    pub fn restore_notes(&self, db: &mut Database, notes: &[(Note, bool)], versions: &[NoteVersion], replace: bool) -> NotepadResult<usize> {
+        match self {
+            Self::Database => db.restore_notes(notes, versions, replace),
+            Self::Git(history) => {
+                // 仓库中已有的版本按版本id跳过
+                let written = db.restore_notes(notes, &[], replace)?;
+                history.migrate(versions).map_err(NotepadError::history)?;
+                Ok(written)
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+/// 数据层、导入导出和服务层的错误
+#[derive(Debug)]
+pub enum NotepadError {
+    // 笔记(或回收站中的笔记、版本)不存在
+    NotFound(String),
+    // 数据库中某一行的字段无法解析, row 为行的id
+    Corrupt { row: String, field: &'static str },
+    // 笔记已加锁或数据库已加密
+    Locked(String),
+    // 与已有数据冲突(如同步或导入时)
+    Conflict(String),
+    // 参数无效或当前状态不允许该操作(如口令为空、未选择同步目录)
+    Invalid(String),
+    // 导入导出的文件无法解析或写出, 或格式不受支持
+    Format(String),
+    // 加密或解密失败
+    Crypto(String),
+    // git版本历史仓库的错误
+    History(String),
+    // 同步远端(同步目录、服务器或镜像目录)的错误
+    Remote(String),
+    Io(std::io::Error),
+    Database(rusqlite::Error),
+}
+
+pub type NotepadResult<T> = std::result::Result<T, NotepadError>;
+
+impl fmt::Display for NotepadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "Note not found: {}", id),
+            Self::Corrupt { row, field } => write!(f, "Corrupt data in row {} (field {})", row, field),
+            Self::Locked(what) => write!(f, "Locked: {}", what),
+            Self::Conflict(message) => write!(f, "Conflict: {}", message),
+            Self::Invalid(message) => write!(f, "{}", message),
+            Self::Format(message) => write!(f, "Format error: {}", message),
+            Self::Crypto(message) => write!(f, "Encryption error: {}", message),
+            Self::History(message) => write!(f, "Version history error: {}", message),
+            Self::Remote(message) => write!(f, "Sync error: {}", message),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NotepadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// 格式解析、加密、版本仓库和同步模块内部使用 anyhow, 在调用处转换为对应的错误类型
+impl NotepadError {
+    pub(crate) fn format(e: impl fmt::Display) -> Self {
+        Self::Format(format!("{:#}", e))
+    }
+
+    pub(crate) fn crypto(e: impl fmt::Display) -> Self {
+        Self::Crypto(format!("{:#}", e))
+    }
+
+    pub(crate) fn history(e: impl fmt::Display) -> Self {
+        Self::History(format!("{:#}", e))
+    }
+
+    pub(crate) fn remote(e: impl fmt::Display) -> Self {
+        Self::Remote(format!("{:#}", e))
+    }
+}
+
+impl From<rusqlite::Error> for NotepadError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl From<std::io::Error> for NotepadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
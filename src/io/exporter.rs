@@ -1,61 +1,278 @@
-use std::path::Path;
-use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use chrono::format::{Item, StrftimeItems};
 use crate::data::{ExportNote, NoteVersion};
-use super::{ExportConfig, ExportFormat, formats::{MarkdownHandler, JsonHandler}};
+use crate::error::{NotepadError, NotepadResult};
+use crate::utils::crypto::EncryptedBackup;
+use super::{BackupData, BackupManifest, ExportConfig, ExportFormat, ImportReport, formats::{MarkdownHandler, JsonHandler, BackupHandler, PdfHandler, HtmlHandler, DocxHandler, OdtHandler}};
 
 pub struct Exporter;
 
 impl Exporter {
     // 导出单个笔记
-    pub fn export_note(note: &ExportNote, output_path: &Path, config: &ExportConfig) -> Result<()> {
+    pub fn export_note(note: &ExportNote, output_path: &Path, config: &ExportConfig) -> NotepadResult<()> {
         match config.format {
             ExportFormat::Markdown(include_metadata) => {
                 let handler = MarkdownHandler;
-                handler.export_note(note, output_path, include_metadata)
+                handler.export_note(note, output_path, include_metadata).map_err(NotepadError::format)
             }
             ExportFormat::Json => {
                 let handler = JsonHandler;
-                handler.export_note(note, output_path)
+                handler.export_note(note, output_path).map_err(NotepadError::format)
+            }
+            ExportFormat::Pdf => {
+                let handler = PdfHandler;
+                handler.export_note(note, output_path, &config.pdf).map_err(NotepadError::format)
+            }
+            ExportFormat::Html => {
+                let handler = HtmlHandler;
+                handler.export_note(note, output_path).map_err(NotepadError::format)
+            }
+            ExportFormat::Docx => {
+                let handler = DocxHandler;
+                handler.export_note(note, output_path).map_err(NotepadError::format)
+            }
+            ExportFormat::Odt => {
+                let handler = OdtHandler;
+                handler.export_note(note, output_path).map_err(NotepadError::format)
             }
-            _ => unimplemented!(),
         }
     }
 
+    // 生成带 front matter 的Markdown文本(镜像目录使用)
+    pub fn render_markdown(note: &ExportNote) -> NotepadResult<String> {
+        let handler = MarkdownHandler;
+        handler.render(note, true).map_err(NotepadError::format)
+    }
+
     // 导出单个笔记所有版本(JSON格式)
-    pub fn export_note_versions(versions: Vec<NoteVersion>, output_path: &Path) -> Result<()> {
-        let json = serde_json::to_string_pretty(&versions)?;
+    pub fn export_note_versions(versions: Vec<NoteVersion>, output_path: &Path) -> NotepadResult<()> {
+        let json = serde_json::to_string_pretty(&versions).map_err(NotepadError::format)?;
+        std::fs::write(output_path, json)?;
+        Ok(())
+    }
+
+    // 导出导入报告(JSON格式)
+    pub fn export_import_report(report: &ImportReport, output_path: &Path) -> NotepadResult<()> {
+        let json = serde_json::to_string_pretty(report).map_err(NotepadError::format)?;
         std::fs::write(output_path, json)?;
         Ok(())
     }
 
+    // 导出加密的完整备份(JSON格式)
+    pub fn export_encrypted_backup(backup: &EncryptedBackup, output_dir: &Path) -> NotepadResult<()> {
+        let path = output_dir.join("notes_backup.json");
+        let json = serde_json::to_string_pretty(backup).map_err(NotepadError::format)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    // 导出完整备份(.cnbackup), seal 在启用保险库时加密笔记数据
+    pub fn export_backup(
+        data: &BackupData,
+        settings: &serde_json::Value,
+        attachments_dir: &Path,
+        output_path: &Path,
+        seal: impl FnOnce(&str) -> NotepadResult<Option<EncryptedBackup>>,
+    ) -> NotepadResult<BackupManifest> {
+        let handler = BackupHandler;
+        handler.export(data, settings, attachments_dir, output_path, |json| Ok(seal(json)?)).map_err(NotepadError::format)
+    }
+
     // 导出所有笔记到目录
-    pub fn export_all(notes: Vec<ExportNote>, output_dir: &Path, config: &ExportConfig) -> Result<()> {
+    pub fn export_all(notes: Vec<ExportNote>, output_dir: &Path, config: &ExportConfig) -> NotepadResult<()> {
         match config.format {
             ExportFormat::Markdown(include_metadata) => {
                 let handler = MarkdownHandler;
                 for note in notes.iter() {
-                    let mut filename = sanitize_filename(&note.title) + ".md";
-                    if std::fs::exists(output_dir.join(&filename))? {
-                        let id = note.id.clone().unwrap_or(uuid::Uuid::new_v4().to_string());
-                        filename = format!("{}-{{{}}}.md", sanitize_filename(&note.title), id);
-                    }
-                    let path = output_dir.join(filename);
-                    handler.export_note(note, &path, include_metadata)?;
+                    let path = unique_path(output_dir, note, "md", &config.filename_template)?;
+                    handler.export_note(note, &path, include_metadata).map_err(NotepadError::format)?;
+                }
+                Ok(())
+            }
+            ExportFormat::Pdf => {
+                // 每个笔记一个PDF文件
+                let handler = PdfHandler;
+                for note in notes.iter() {
+                    let path = unique_path(output_dir, note, "pdf", &config.filename_template)?;
+                    handler.export_note(note, &path, &config.pdf).map_err(NotepadError::format)?;
+                }
+                Ok(())
+            }
+            ExportFormat::Docx => {
+                let handler = DocxHandler;
+                for note in notes.iter() {
+                    let path = unique_path(output_dir, note, "docx", &config.filename_template)?;
+                    handler.export_note(note, &path).map_err(NotepadError::format)?;
+                }
+                Ok(())
+            }
+            ExportFormat::Odt => {
+                let handler = OdtHandler;
+                for note in notes.iter() {
+                    let path = unique_path(output_dir, note, "odt", &config.filename_template)?;
+                    handler.export_note(note, &path).map_err(NotepadError::format)?;
                 }
                 Ok(())
             }
             ExportFormat::Json => {
                 let path = output_dir.join("notes_backup.json");
-                let json = serde_json::to_string_pretty(&notes)?;
+                let json = serde_json::to_string_pretty(&notes).map_err(NotepadError::format)?;
                 std::fs::write(path, json)?;
                 Ok(())
             }
-            _ => unimplemented!(),
+            ExportFormat::Html => {
+                // 没有笔记本时按导出的笔记统计标签
+                let mut tags = HashMap::new();
+                for tag in notes.iter().flat_map(|note| note.tags()) {
+                    *tags.entry(tag.clone()).or_default() += 1;
+                }
+                Self::export_site(&notes, &tags, output_dir)
+            }
         }
     }
+
+    // 导出静态网站(首页、标签页、笔记页和搜索索引)
+    pub fn export_site(notes: &[ExportNote], tags: &HashMap<String, usize>, output_dir: &Path) -> NotepadResult<()> {
+        let handler = HtmlHandler;
+        handler.export_site(notes, tags, output_dir).map_err(NotepadError::format)
+    }
+}
+
+// 文件名(不含扩展名)的最大字节数, 为重名序号和扩展名留出空间
+const MAX_FILENAME_BYTES: usize = 200;
+// Windows 保留的设备名
+const RESERVED_NAMES: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+
+// 按文件名模板生成路径, 重名时添加序号
+fn unique_path(output_dir: &Path, note: &ExportNote, extension: &str, template: &str) -> NotepadResult<PathBuf> {
+    let relative = render_template(template, note)?;
+    let dir = match relative.parent() {
+        Some(parent) => output_dir.join(parent),
+        None => output_dir.to_path_buf(),
+    };
+    std::fs::create_dir_all(&dir)?;
+    let stem = relative.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let mut path = dir.join(format!("{}.{}", stem, extension));
+    let mut idx = 1;
+    while std::fs::exists(&path)? {
+        idx += 1;
+        path = dir.join(format!("{}-{}.{}", stem, idx, extension));
+    }
+    Ok(path)
 }
 
-fn sanitize_filename(name: &str) -> String {
-    // 实现文件名安全处理
-    name.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
-}
\ No newline at end of file
+// 文件名模板: {title} {id} {tag} {created:%Y-%m-%d} {updated:%Y-%m-%d}, / 用于分隔子目录
+fn render_template(template: &str, note: &ExportNote) -> NotepadResult<PathBuf> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let end = rest[start..].find('}')
+            .ok_or_else(|| NotepadError::Invalid(format!("Unclosed placeholder in filename template: {}", template)))? + start;
+        let placeholder = &rest[start + 1..end];
+        let (name, format) = placeholder.split_once(':').unwrap_or((placeholder, "%Y-%m-%d"));
+        // 标题和id中的 / 不作为目录分隔符; 标签和日期中的 / 用于生成子目录
+        let value = match name.trim() {
+            "title" => note.title.replace(['/', '\\'], ""),
+            "id" => note.id.clone().unwrap_or_default().replace(['/', '\\'], ""),
+            "tag" => {
+                let mut tags: Vec<&String> = note.tags().iter().collect();
+                tags.sort();
+                tags.first().map(|tag| tag.to_string()).unwrap_or_default()
+            }
+            "created" | "updated" => {
+                let mut items = Vec::new();
+                for item in StrftimeItems::new(format) {
+                    if item == Item::Error {
+                        return Err(NotepadError::Invalid(format!("Invalid date format in filename template: {}", placeholder)));
+                    }
+                    items.push(item);
+                }
+                let time = if name.trim() == "created" { note.created } else { note.updated };
+                time.map(|t| t.with_timezone(&chrono::Local).format_with_items(items.iter()).to_string())
+                    .unwrap_or_default()
+            }
+            _ => return Err(NotepadError::Invalid(format!("Unknown placeholder {{{}}} in filename template", placeholder))),
+        };
+        rendered.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    rendered.push_str(rest);
+
+    // 空的目录层级(如没有标签时的 {tag})被忽略
+    let mut path: PathBuf = rendered.split(['/', '\\'])
+        .map(sanitize_filename)
+        .filter(|name| !name.is_empty())
+        .collect();
+    if path.file_name().is_none() || rendered.ends_with(['/', '\\']) {
+        path.push(match sanitize_filename(&note.title) {
+            title if title.is_empty() => "untitled".to_string(),
+            title => title,
+        });
+    }
+    Ok(path)
+}
+
+// 只去掉常见文件系统(Windows/macOS/Linux)中不允许的字符, 保留中文等Unicode字符
+pub fn sanitize_filename(name: &str) -> String {
+    let name: String = name.chars()
+        .filter(|c| !c.is_control() && !matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*'))
+        .collect();
+    let mut end = name.len().min(MAX_FILENAME_BYTES);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    // 以点开头的文件在类Unix系统中隐藏, Windows 不允许以空格或点结尾
+    let mut name = name[..end].trim().trim_matches('.').trim().to_string();
+    // 保留名按第一个点之前的部分判断(con.txt 同样不可用), 后缀加在这部分之后
+    let stem_len = name.find('.').unwrap_or(name.len());
+    let stem = name[..stem_len].trim_end().to_ascii_uppercase();
+    let device = stem.len() == 4
+        && (stem.starts_with("COM") || stem.starts_with("LPT"))
+        && stem.ends_with(|c: char| ('1'..='9').contains(&c));
+    if RESERVED_NAMES.contains(&stem.as_str()) || device {
+        name.insert(stem_len, '_');
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use chrono::{TimeZone, Utc};
+    use super::*;
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("会议记录: 第一季度?"), "会议记录 第一季度");
+        assert_eq!(sanitize_filename("日本語のメモ"), "日本語のメモ");
+        assert_eq!(sanitize_filename(" .hidden. "), "hidden");
+        assert_eq!(sanitize_filename("con.txt"), "con_.txt");
+        assert_eq!(sanitize_filename("LPT1"), "LPT1_");
+        assert_eq!(sanitize_filename("Com3.tar.gz"), "Com3_.tar.gz");
+        assert_eq!(sanitize_filename("console.txt"), "console.txt");
+        assert_eq!(sanitize_filename("a<b>|c*"), "abc");
+        assert!(sanitize_filename(&"字".repeat(100)).len() <= MAX_FILENAME_BYTES);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let created = Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap();
+        let note = ExportNote::new(
+            Some("n1".to_string()),
+            "计划 A/B".to_string(),
+            HashSet::from(["工作/项目".to_string(), "项目".to_string()]),
+            String::new(),
+            Some(created),
+            None,
+        );
+        let date = created.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string();
+        assert_eq!(render_template("{created:%Y-%m-%d}-{title}", &note).unwrap(), PathBuf::from(format!("{}-计划 AB", date)));
+        assert_eq!(render_template("{tag}/{title}", &note).unwrap(), PathBuf::from("工作/项目/计划 AB"));
+        assert_eq!(render_template("{updated}/{title}", &note).unwrap(), PathBuf::from("计划 AB"));
+        assert_eq!(render_template("../{id}", &note).unwrap(), PathBuf::from("n1"));
+        assert!(render_template("{author}", &note).is_err());
+        assert!(render_template("{created:%Q}", &note).is_err());
+    }
+}
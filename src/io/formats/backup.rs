@@ -0,0 +1,187 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
+use crate::data::{Note, NoteVersion};
+use crate::utils::crypto::EncryptedBackup;
+
+// 备份格式版本, 读取更高版本的备份时报错
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+pub const BACKUP_EXTENSION: &str = "cnbackup";
+
+const MANIFEST: &str = "manifest.json";
+const DATA: &str = "data.json";
+const SETTINGS: &str = "settings.json";
+const ATTACHMENTS: &str = "attachments/";
+
+/// 备份清单(不加密, 恢复前用于确认)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub notes: usize,
+    pub trashed: usize,
+    pub versions: usize,
+    pub attachments: usize,
+    pub encrypted: bool,    // 笔记数据使用保险库密钥加密
+}
+
+/// 备份中的笔记(保留回收站状态)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupNote {
+    #[serde(flatten)]
+    pub note: Note,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trashed: bool,
+}
+
+/// 完整备份的内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BackupData {
+    pub notes: Vec<BackupNote>,
+    pub versions: Vec<NoteVersion>,
+}
+
+/// 恢复方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreMode {
+    Replace,    // 用备份替换当前保险库(包括设置), git版本历史仓库中已有的提交保留
+    Merge,      // 只添加新笔记和更新较旧的笔记
+}
+
+/// .cnbackup 完整备份: 包含清单、笔记(含回收站)、版本历史、设置和附件的zip包
+pub(in crate::io) struct BackupHandler;
+
+impl BackupHandler {
+    // seal 在启用保险库时加密笔记数据
+    pub fn export(
+        &self,
+        data: &BackupData,
+        settings: &serde_json::Value,
+        attachments_dir: &Path,
+        path: &Path,
+        seal: impl FnOnce(&str) -> anyhow::Result<Option<EncryptedBackup>>,
+    ) -> anyhow::Result<BackupManifest> {
+        let attachments = list_files(attachments_dir);
+        let json = serde_json::to_string(data)?;
+        let sealed = seal(&json)?;
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now(),
+            notes: data.notes.iter().filter(|n| !n.trashed).count(),
+            trashed: data.notes.iter().filter(|n| n.trashed).count(),
+            versions: data.versions.len(),
+            attachments: attachments.len(),
+            encrypted: sealed.is_some(),
+        };
+
+        // 先写入临时文件, 完成后再替换, 避免中途失败留下损坏的备份
+        let temp_path = path.with_extension(format!("{}.tmp", BACKUP_EXTENSION));
+        let mut zip = ZipWriter::new(File::create(&temp_path)?);
+        let options = SimpleFileOptions::default();
+        zip.start_file(MANIFEST, options)?;
+        zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+        zip.start_file(DATA, options)?;
+        match sealed {
+            Some(backup) => zip.write_all(serde_json::to_string(&backup)?.as_bytes())?,
+            None => zip.write_all(json.as_bytes())?,
+        }
+        zip.start_file(SETTINGS, options)?;
+        zip.write_all(serde_json::to_string_pretty(settings)?.as_bytes())?;
+        for relative in attachments.iter() {
+            let name = format!("{}{}", ATTACHMENTS, relative.to_string_lossy().replace('\\', "/"));
+            zip.start_file(name, options)?;
+            std::io::copy(&mut File::open(attachments_dir.join(relative))?, &mut zip)?;
+        }
+        zip.finish()?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(manifest)
+    }
+
+    pub fn read_manifest(&self, path: &Path) -> anyhow::Result<BackupManifest> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let manifest: BackupManifest = serde_json::from_str(&read_entry(&mut archive, MANIFEST)?)?;
+        if manifest.format_version > BACKUP_FORMAT_VERSION {
+            anyhow::bail!("The backup format v{} was created by a newer version ({})", manifest.format_version, manifest.app_version);
+        }
+        Ok(manifest)
+    }
+
+    // 读取笔记数据和设置(加密的备份需要口令)
+    pub fn import(&self, path: &Path, passphrase: &str) -> anyhow::Result<(BackupData, serde_json::Value)> {
+        self.read_manifest(path)?;
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let mut content: serde_json::Value = serde_json::from_str(&read_entry(&mut archive, DATA)?)?;
+        if content.get("format").and_then(|f| f.as_str()) == Some(EncryptedBackup::FORMAT) {
+            let backup: EncryptedBackup = serde_json::from_value(content)?;
+            content = serde_json::from_str(&backup.open(passphrase)?)?;
+        }
+        let settings = match read_entry(&mut archive, SETTINGS) {
+            Ok(settings) => serde_json::from_str(&settings)?,
+            Err(_) => serde_json::Value::Null,
+        };
+        Ok((serde_json::from_value(content)?, settings))
+    }
+
+    // 解压附件到附件目录, overwrite 为 false 时跳过已存在的文件, 返回写入的文件数
+    pub fn extract_attachments(&self, path: &Path, attachments_dir: &Path, overwrite: bool) -> anyhow::Result<usize> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let mut count = 0;
+        for idx in 0..archive.len() {
+            let mut entry = archive.by_index(idx)?;
+            // enclosed_name 拒绝绝对路径和 .. 路径
+            let Some(relative) = entry.enclosed_name()
+                .and_then(|name| name.strip_prefix(ATTACHMENTS).ok().map(Path::to_path_buf)) else {
+                continue;
+            };
+            if entry.is_dir() || relative.as_os_str().is_empty() {
+                continue;
+            }
+            let target = attachments_dir.join(relative);
+            if !overwrite && target.exists() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::io::copy(&mut entry, &mut File::create(&target)?)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>, name: &str) -> anyhow::Result<String> {
+    let mut text = String::new();
+    archive.by_name(name)
+        .map_err(|e| anyhow::anyhow!("Invalid backup, missing {}: {}", name, e))?
+        .read_to_string(&mut text)?;
+    Ok(text)
+}
+
+// 附件目录中所有文件的相对路径
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    fn walk(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_symlink() {
+                continue;
+            }
+            if path.is_dir() {
+                walk(root, &path, files);
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(dir, dir, &mut files);
+    files.sort();
+    files
+}
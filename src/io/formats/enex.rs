@@ -0,0 +1,489 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use base64::Engine;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::Reader;
+use crate::data::ExportNote;
+use super::{Attachment, ImportConfig, ImportResult};
+
+/// Evernote 导出文件(.enex)导入
+pub(in crate::io) struct EnexHandler;
+
+// 正在解析的笔记
+#[derive(Default)]
+struct EnexNote {
+    title: String,
+    content: String,
+    created: String,
+    updated: String,
+    tags: HashSet<String>,
+    resources: Vec<EnexResource>,
+}
+
+// 笔记中的资源(图片、附件)
+#[derive(Default)]
+struct EnexResource {
+    data: String,       // base64 编码的内容
+    mime: String,
+    file_name: String,
+}
+
+// 已解码到临时文件的资源
+struct Resource {
+    file_name: String,
+    mime: String,
+    path: PathBuf,
+}
+
+impl EnexHandler {
+    // 逐个笔记流式解析, 不一次性读入整个文件
+    pub fn import_notes(&self, path: &Path, config: &ImportConfig) -> anyhow::Result<Vec<ImportResult>> {
+        let mut reader = Reader::from_reader(BufReader::new(File::open(path)?));
+        let temp_dir = std::env::temp_dir().join(format!("cloud-notepad-enex-{}", uuid::Uuid::new_v4()));
+        let mut results = Vec::new();
+        let mut buf = Vec::new();
+        // 当前所在的元素路径
+        let mut stack: Vec<String> = Vec::new();
+        let mut note: Option<EnexNote> = None;
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) => {
+                    let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                    match (name.as_str(), note.as_mut()) {
+                        ("note", _) => note = Some(EnexNote::default()),
+                        ("resource", Some(note)) => note.resources.push(EnexResource::default()),
+                        _ => (),
+                    }
+                    stack.push(name);
+                }
+                Event::End(_) => {
+                    if stack.pop().as_deref() == Some("note")
+                        && let Some(finished) = note.take() {
+                        results.push(self.import_note(finished, path, &temp_dir, config)?);
+                    }
+                }
+                Event::Text(e) => {
+                    if let Some(note) = note.as_mut() {
+                        note.push_text(&stack, &e.unescape()?);
+                    }
+                }
+                Event::CData(e) => {
+                    if let Some(note) = note.as_mut() {
+                        note.push_text(&stack, &String::from_utf8_lossy(&e));
+                    }
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        Ok(results)
+    }
+
+    fn import_note(&self, note: EnexNote, path: &Path, temp_dir: &Path, config: &ImportConfig) -> anyhow::Result<ImportResult> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut warnings = Vec::new();
+        let mut attachments = Vec::new();
+
+        // 资源按内容的MD5索引(en-media 通过 hash 引用)
+        let mut resources = HashMap::new();
+        let mut used_names = HashSet::new();
+        for (idx, resource) in note.resources.into_iter().enumerate() {
+            let data = match base64::engine::general_purpose::STANDARD.decode(resource.data.split_whitespace().collect::<String>()) {
+                Ok(data) => data,
+                Err(e) => {
+                    warnings.push(format!("invalid resource data: {}", e));
+                    continue;
+                }
+            };
+            let hash = format!("{:x}", md5::compute(&data));
+            // 内容相同的资源只保存一次
+            if resources.contains_key(&hash) {
+                continue;
+            }
+            let file_name = match resource.file_name.trim() {
+                "" => format!("resource-{}{}", idx + 1, mime_extension(&resource.mime)),
+                name => name.replace(['/', '\\'], "_"),
+            };
+            let file_name = unique_file_name(file_name, &mut used_names);
+            std::fs::create_dir_all(temp_dir)?;
+            let temp_path = temp_dir.join(format!("{}-{}", hash, file_name));
+            std::fs::write(&temp_path, data)?;
+            let target = Path::new(&id).join(&file_name);
+            attachments.push(Attachment { source: temp_path.clone(), target: target.clone(), temporary: true });
+            let url = super::attachment_url(config, &target);
+            resources.insert(hash, Resource { file_name, mime: resource.mime, path: PathBuf::from(url) });
+        }
+
+        let content = match enml_to_markdown(&note.content, &resources) {
+            Ok(content) => content,
+            Err(e) => {
+                warnings.push(format!("failed to convert note content: {}", e));
+                note.content.clone()
+            }
+        };
+        let (created, updated) = if config.preserve_timestamps {
+            (parse_enex_time(&note.created, &mut warnings), parse_enex_time(&note.updated, &mut warnings))
+        } else {
+            (None, None)
+        };
+        let title = match note.title.trim() {
+            "" => "Untitled".to_string(),
+            title => title.to_string(),
+        };
+
+        Ok(ImportResult {
+            note: ExportNote::new(Some(id), title, note.tags, content, created, updated),
+            source: path.to_path_buf(),
+            warnings,
+            attachments,
+        })
+    }
+}
+
+impl EnexNote {
+    // 按所在元素保存文本
+    fn push_text(&mut self, stack: &[String], text: &str) {
+        let parent = stack.get(stack.len().wrapping_sub(2)).map(String::as_str);
+        match (stack.last().map(String::as_str), parent) {
+            (Some("title"), Some("note")) => self.title.push_str(text),
+            (Some("content"), _) => self.content.push_str(text),
+            (Some("created"), Some("note")) => self.created.push_str(text),
+            (Some("updated"), Some("note")) => self.updated.push_str(text),
+            (Some("tag"), _) => { self.tags.insert(text.trim().to_string()); }
+            (Some("data"), _) => self.resource().data.push_str(text),
+            (Some("mime"), _) => self.resource().mime.push_str(text.trim()),
+            (Some("file-name"), _) => self.resource().file_name.push_str(text),
+            _ => (),
+        }
+    }
+
+    fn resource(&mut self) -> &mut EnexResource {
+        if self.resources.is_empty() {
+            self.resources.push(EnexResource::default());
+        }
+        self.resources.last_mut().unwrap()
+    }
+}
+
+// ENEX 时间格式: 20240102T030405Z
+fn parse_enex_time(s: &str, warnings: &mut Vec<String>) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    match NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%SZ") {
+        Ok(dt) => Some(dt.and_utc()),
+        Err(e) => {
+            warnings.push(format!("parse time {} failed: {}", s, e));
+            None
+        }
+    }
+}
+
+fn mime_extension(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => ".png",
+        "image/jpeg" => ".jpg",
+        "image/gif" => ".gif",
+        "image/svg+xml" => ".svg",
+        "application/pdf" => ".pdf",
+        _ => "",
+    }
+}
+
+// ENML 中常见的 HTML 实体
+fn resolve_entity(entity: &str) -> Option<&'static str> {
+    match entity {
+        "nbsp" => Some(" "),
+        "ndash" => Some("–"),
+        "mdash" => Some("—"),
+        "hellip" => Some("…"),
+        "copy" => Some("©"),
+        "lsquo" => Some("‘"),
+        "rsquo" => Some("’"),
+        "ldquo" => Some("“"),
+        "rdquo" => Some("”"),
+        _ => None,
+    }
+}
+
+// 将 ENML(XHTML子集)转换为 Markdown
+fn enml_to_markdown(enml: &str, resources: &HashMap<String, Resource>) -> anyhow::Result<String> {
+    let mut reader = Reader::from_str(enml);
+    let mut writer = MarkdownWriter::default();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => writer.start(&e, resources)?,
+            Event::Empty(e) => {
+                writer.start(&e, resources)?;
+                writer.end(e.local_name().as_ref());
+            }
+            Event::End(e) => writer.end(e.local_name().as_ref()),
+            Event::Text(e) => writer.text(&e),
+            Event::CData(e) => writer.push_text(&String::from_utf8_lossy(&e)),
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(writer.finish())
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    output: String,
+    lists: Vec<Option<usize>>,      // 嵌套列表(有序列表记录序号)
+    links: Vec<Option<String>>,     // 未闭合的 <a> 的链接地址
+    in_pre: bool,
+}
+
+impl MarkdownWriter {
+    fn ensure_newline(&mut self) {
+        if !self.output.is_empty() && !self.output.ends_with('\n') {
+            self.output.push('\n');
+        }
+    }
+
+    fn start(&mut self, e: &BytesStart, resources: &HashMap<String, Resource>) -> anyhow::Result<()> {
+        let attr = |name: &str| -> anyhow::Result<Option<String>> {
+            Ok(e.try_get_attribute(name)?.map(|a| a.unescape_value()).transpose()?.map(|v| v.to_string()))
+        };
+        match e.local_name().as_ref() {
+            b"div" | b"p" | b"tr" => self.ensure_newline(),
+            b"br" => self.output.push('\n'),
+            b"hr" => {
+                self.ensure_newline();
+                self.output.push_str("---\n");
+            }
+            tag @ (b"h1" | b"h2" | b"h3" | b"h4" | b"h5" | b"h6") => {
+                self.ensure_newline();
+                self.output.push_str(&"#".repeat((tag[1] - b'0') as usize));
+                self.output.push(' ');
+            }
+            b"b" | b"strong" => self.output.push_str("**"),
+            b"i" | b"em" => self.output.push('*'),
+            b"s" | b"strike" | b"del" => self.output.push_str("~~"),
+            b"code" if !self.in_pre => self.output.push('`'),
+            b"pre" => {
+                self.ensure_newline();
+                self.output.push_str("```\n");
+                self.in_pre = true;
+            }
+            b"blockquote" => {
+                self.ensure_newline();
+                self.output.push_str("> ");
+            }
+            b"ul" => self.lists.push(None),
+            b"ol" => self.lists.push(Some(0)),
+            b"li" => {
+                self.ensure_newline();
+                let depth = self.lists.len().saturating_sub(1);
+                self.output.push_str(&"  ".repeat(depth));
+                match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        self.output.push_str(&format!("{}. ", n));
+                    }
+                    _ => self.output.push_str("- "),
+                }
+            }
+            b"a" => {
+                self.output.push('[');
+                self.links.push(attr("href")?);
+            }
+            b"img" => {
+                let src = attr("src")?.unwrap_or_default();
+                self.output.push_str(&format!("![{}]({})", attr("alt")?.unwrap_or_default(), src));
+            }
+            b"en-todo" => {
+                let checked = attr("checked")?.is_some_and(|v| v == "true");
+                if self.lists.is_empty() {
+                    self.output.push_str("- ");
+                }
+                self.output.push_str(if checked { "[x] " } else { "[ ] " });
+            }
+            b"en-media" => {
+                let hash = attr("hash")?.unwrap_or_default();
+                match resources.get(&hash) {
+                    Some(resource) if resource.mime.starts_with("image/") => {
+                        self.output.push_str(&format!("![{}]({})", resource.file_name, resource.path.display()));
+                    }
+                    Some(resource) => {
+                        self.output.push_str(&format!("[{}]({})", resource.file_name, resource.path.display()));
+                    }
+                    None => self.output.push_str(&format!("<!-- missing resource {} -->", hash)),
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn end(&mut self, name: &[u8]) {
+        match name {
+            b"div" | b"p" | b"li" | b"tr" | b"blockquote"
+            | b"h1" | b"h2" | b"h3" | b"h4" | b"h5" | b"h6" => self.ensure_newline(),
+            b"td" | b"th" => self.output.push_str(" | "),
+            b"b" | b"strong" => self.output.push_str("**"),
+            b"i" | b"em" => self.output.push('*'),
+            b"s" | b"strike" | b"del" => self.output.push_str("~~"),
+            b"code" if !self.in_pre => self.output.push('`'),
+            b"pre" => {
+                self.ensure_newline();
+                self.output.push_str("```\n");
+                self.in_pre = false;
+            }
+            b"ul" | b"ol" => {
+                self.lists.pop();
+                self.ensure_newline();
+            }
+            b"a" => {
+                let href = self.links.pop().flatten().unwrap_or_default();
+                self.output.push_str(&format!("]({})", href));
+            }
+            _ => (),
+        }
+    }
+
+    fn text(&mut self, e: &BytesText) {
+        let text = e.unescape_with(resolve_entity)
+            .map(|t| t.to_string())
+            .unwrap_or_else(|_| String::from_utf8_lossy(e).to_string());
+        self.push_text(&text);
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.in_pre {
+            self.output.push_str(text);
+            return;
+        }
+        // 普通文本中的换行和连续空白折叠为一个空格
+        let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if collapsed.is_empty() {
+            if !text.is_empty() && !self.output.is_empty() && !self.output.ends_with([' ', '\n']) {
+                self.output.push(' ');
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) && !self.output.is_empty() && !self.output.ends_with([' ', '\n']) {
+            self.output.push(' ');
+        }
+        self.output.push_str(&collapsed);
+        if text.ends_with(char::is_whitespace) {
+            self.output.push(' ');
+        }
+    }
+
+    fn finish(self) -> String {
+        let lines: Vec<&str> = self.output.lines().map(str::trim_end).collect();
+        let mut result = String::new();
+        let mut blank = 0;
+        for line in lines {
+            if line.is_empty() {
+                blank += 1;
+                if blank > 1 { continue; }
+            } else {
+                blank = 0;
+            }
+            result.push_str(line);
+            result.push('\n');
+        }
+        result.trim_matches('\n').to_string() + "\n"
+    }
+}
+
+// 同一笔记中重名的附件在扩展名前加序号(map.png -> map-2.png)
+fn unique_file_name(file_name: String, used: &mut HashSet<String>) -> String {
+    let (stem, extension) = match file_name.rfind('.') {
+        Some(pos) if pos > 0 => file_name.split_at(pos),
+        _ => (file_name.as_str(), ""),
+    };
+    let mut unique = file_name.clone();
+    let mut index = 1;
+    while used.contains(&unique.to_lowercase()) {
+        index += 1;
+        unique = format!("{}-{}{}", stem, index, extension);
+    }
+    used.insert(unique.to_lowercase());
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_enex() {
+        let image = [137u8, 80, 78, 71];
+        let hash = format!("{:x}", md5::compute(image));
+        let data = base64::engine::general_purpose::STANDARD.encode(image);
+        let enex = format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export4.dtd">
+<en-export>
+  <note>
+    <title>Trip &amp; plans</title>
+    <created>20240102T030405Z</created>
+    <updated>20240103T000000Z</updated>
+    <tag>travel</tag>
+    <tag>to do</tag>
+    <content><![CDATA[<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd">
+<en-note><h1>Packing</h1><div><en-todo checked="true"/>Passport&nbsp;and <b>tickets</b></div>
+<ul><li>Sun <a href="https://example.com">cream</a></li></ul><div><en-media type="image/png" hash="{hash}"/></div></en-note>]]></content>
+    <resource>
+      <data encoding="base64">{data}</data>
+      <mime>image/png</mime>
+      <resource-attributes><file-name>map.png</file-name></resource-attributes>
+    </resource>
+  </note>
+  <note><title>Second</title><content><![CDATA[<en-note>hi</en-note>]]></content></note>
+</en-export>"#);
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("export.enex");
+        std::fs::write(&path, enex).unwrap();
+
+        let results = EnexHandler.import_notes(&path, &ImportConfig::default()).unwrap();
+        assert_eq!(results.len(), 2);
+        let note = results[0].note();
+        assert_eq!(note.title(), "Trip & plans");
+        assert!(note.tags().contains("to do"));
+        assert_eq!(note.created.unwrap().to_rfc3339(), "2024-01-02T03:04:05+00:00");
+        assert!(note.content().starts_with("# Packing\n- [x] Passport and **tickets**\n- Sun [cream](https://example.com)\n"));
+        assert!(note.content().contains("![map.png](attachments/"));
+        let attachment = &results[0].attachments()[0];
+        assert_eq!(std::fs::read(&attachment.source).unwrap(), image);
+        assert_eq!(results[1].note().content(), "hi\n");
+        let _ = std::fs::remove_file(&attachment.source);
+    }
+
+    #[test]
+    fn test_import_enex_duplicate_file_names() {
+        let resource = |data: &[u8]| format!(r#"<resource>
+      <data encoding="base64">{}</data>
+      <mime>image/png</mime>
+      <resource-attributes><file-name>scan.png</file-name></resource-attributes>
+    </resource>"#, base64::engine::general_purpose::STANDARD.encode(data));
+        let enex = format!(r#"<en-export><note><title>Scans</title><content><![CDATA[<en-note>scans</en-note>]]></content>
+    {}{}{}
+  </note></en-export>"#, resource(b"first"), resource(b"second"), resource(b"first"));
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("export.enex");
+        std::fs::write(&path, enex).unwrap();
+
+        // 重名的附件使用不同的文件名, 内容相同的只保存一次
+        let results = EnexHandler.import_notes(&path, &ImportConfig::default()).unwrap();
+        let attachments = results[0].attachments();
+        let names: Vec<_> = attachments.iter().map(|a| a.target.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, ["scan.png", "scan-2.png"]);
+        assert_eq!(std::fs::read(&attachments[1].source).unwrap(), b"second");
+        for attachment in attachments.iter() {
+            let _ = std::fs::remove_file(&attachment.source);
+        }
+    }
+}
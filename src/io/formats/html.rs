@@ -0,0 +1,508 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd, html};
+use serde::Serialize;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator, css_for_theme_with_class_style};
+use syntect::util::LinesWithEndings;
+use crate::data::ExportNote;
+use crate::markdown::syntax;
+use super::{NOTE_LINK_SCHEME, heading_slug};
+
+const STYLESHEET: &str = "style.css";
+const SEARCH_SCRIPT: &str = "search.js";
+const SEARCH_INDEX: &str = "search-index.json";
+// file:// 下浏览器不允许读取JSON, 同时以脚本形式提供搜索索引
+const SEARCH_INDEX_SCRIPT: &str = "search-index.js";
+const NOTES_DIR: &str = "notes";
+const TAGS_DIR: &str = "tags";
+
+const HIGHLIGHT_CLASS: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+// 链接中需要编码的字符(保留常见的文件名字符)
+const PATH_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.');
+
+const BASE_CSS: &str = r#":root {
+  --text: #222; --muted: #6a737d; --bg: #fff; --panel: #f6f8fa; --border: #d0d7de; --link: #0969da;
+}
+@media (prefers-color-scheme: dark) {
+  :root { --text: #d8dee4; --muted: #8b949e; --bg: #0d1117; --panel: #161b22; --border: #30363d; --link: #58a6ff; }
+}
+* { box-sizing: border-box; }
+body { margin: 0; color: var(--text); background: var(--bg); font: 16px/1.6 -apple-system, "Segoe UI", "Source Han Sans", "Noto Sans CJK SC", sans-serif; }
+a { color: var(--link); text-decoration: none; }
+a:hover { text-decoration: underline; }
+header { display: flex; align-items: center; gap: 1em; padding: .6em 1.5em; border-bottom: 1px solid var(--border); background: var(--panel); }
+header .home { font-weight: bold; color: var(--text); }
+header .search { margin-left: auto; }
+header input { padding: .3em .6em; border: 1px solid var(--border); border-radius: 6px; background: var(--bg); color: var(--text); }
+main { max-width: 860px; margin: 0 auto; padding: 1.5em; }
+.meta, time, .count { color: var(--muted); font-size: .9em; }
+.tag { display: inline-block; margin: 0 .4em .3em 0; padding: 0 .5em; border-radius: 1em; background: var(--panel); border: 1px solid var(--border); font-size: .85em; }
+ul.notes { list-style: none; padding: 0; }
+ul.notes li { padding: .5em 0; border-bottom: 1px solid var(--border); }
+ul.notes li time { margin-left: .6em; }
+ul.notes .snippet { display: block; color: var(--muted); font-size: .9em; }
+article h1, article h2 { border-bottom: 1px solid var(--border); padding-bottom: .2em; }
+article img { max-width: 100%; }
+code { font-family: "Hack", "SFMono-Regular", Consolas, monospace; font-size: .9em; background: var(--panel); padding: .1em .3em; border-radius: 4px; }
+pre { padding: .8em 1em; overflow: auto; border-radius: 6px; border: 1px solid var(--border); }
+pre code { background: none; padding: 0; }
+blockquote { margin: 0; padding: 0 1em; color: var(--muted); border-left: .25em solid var(--border); }
+table { border-collapse: collapse; }
+th, td { border: 1px solid var(--border); padding: .3em .7em; }
+th { background: var(--panel); }
+"#;
+
+const SEARCH_JS: &str = r#"(function () {
+  var input = document.querySelector('header .search input');
+  var results = document.getElementById('results');
+  var notes = document.getElementById('notes');
+  var query = new URLSearchParams(location.search).get('q') || '';
+  if (input) input.value = query;
+  // 只有首页显示搜索结果, 其它页面提交表单后跳转到首页
+  if (!input || !results || !window.SEARCH_INDEX) return;
+  function render(text) {
+    var terms = text.trim().toLowerCase().split(/\s+/).filter(Boolean);
+    results.innerHTML = '';
+    results.hidden = terms.length === 0;
+    notes.hidden = terms.length > 0;
+    if (!terms.length) return;
+    window.SEARCH_INDEX.filter(function (note) {
+      var haystack = (note.title + ' ' + note.tags.join(' ') + ' ' + note.text).toLowerCase();
+      return terms.every(function (term) { return haystack.indexOf(term) >= 0; });
+    }).forEach(function (note) {
+      var item = document.createElement('li');
+      var link = document.createElement('a');
+      link.href = note.url;
+      link.textContent = note.title;
+      var snippet = document.createElement('span');
+      snippet.className = 'snippet';
+      var at = Math.max(0, note.text.toLowerCase().indexOf(terms[0]) - 40);
+      snippet.textContent = note.text.substr(at, 160);
+      item.appendChild(link);
+      item.appendChild(snippet);
+      results.appendChild(item);
+    });
+  }
+  input.addEventListener('input', function () { render(input.value); });
+  render(query);
+})();
+"#;
+
+// 搜索索引中的一个笔记
+#[derive(Serialize)]
+struct SearchEntry<'a> {
+    id: &'a str,
+    title: &'a str,
+    url: String,
+    tags: Vec<&'a str>,
+    updated: Option<String>,
+    text: String,
+}
+
+/// HTML导出: 单个HTML文件或可浏览的静态网站
+pub(in crate::io) struct HtmlHandler;
+
+impl HtmlHandler {
+    // 导出单个HTML文件(内嵌样式)
+    pub fn export_note(&self, note: &ExportNote, output_path: &Path) -> anyhow::Result<()> {
+        let mut renderer = Renderer { raw_html: true, ..Default::default() };
+        let (content, _) = renderer.render(note.content());
+        let body = format!(
+            "<article class=\"note\">\n<h1>{}</h1>\n{}{}</article>\n",
+            escape(note.title()), meta(note, None), content,
+        );
+        let head = format!("<style>\n{}</style>", stylesheet()?);
+        std::fs::write(output_path, document(note.title(), &head, None, &body))?;
+        Ok(())
+    }
+
+    // 导出静态网站: 首页、标签页、笔记页、搜索索引和样式表, 可直接通过 file:// 浏览
+    pub fn export_site(&self, notes: &[ExportNote], tags: &HashMap<String, usize>, output_dir: &Path) -> anyhow::Result<()> {
+        std::fs::create_dir_all(output_dir.join(NOTES_DIR))?;
+        std::fs::create_dir_all(output_dir.join(TAGS_DIR))?;
+
+        // 笔记页以id命名, 笔记链接 note://<id> 指向对应的页面
+        let mut notes: Vec<(String, &ExportNote)> = notes.iter()
+            .map(|note| {
+                let id = note.id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+                (id, note)
+            })
+            .collect();
+        notes.sort_by(|(_, a), (_, b)| b.pinned.cmp(&a.pinned).then(b.updated.cmp(&a.updated)));
+        let pages: HashMap<String, String> = notes.iter()
+            .map(|(id, _)| (id.clone(), format!("{}.html", id)))
+            .collect();
+        let tag_pages = tag_pages(tags);
+
+        let mut renderer = Renderer {
+            pages: Some(&pages),
+            output_dir: Some(output_dir),
+            copied: HashSet::new(),
+            // 网站可能公开发布, 笔记中的HTML(脚本、事件属性等)不原样输出
+            raw_html: false,
+        };
+        let mut index = Vec::new();
+        for (id, note) in notes.iter() {
+            let (content, text) = renderer.render(note.content());
+            let body = format!(
+                "<article class=\"note\">\n<h1>{}</h1>\n{}{}</article>\n",
+                escape(note.title()), meta(note, Some(&tag_pages)), content,
+            );
+            let path = output_dir.join(NOTES_DIR).join(&pages[id]);
+            std::fs::write(path, document(note.title(), &site_head("../"), Some("../"), &body))?;
+            index.push(SearchEntry {
+                id,
+                title: note.title(),
+                url: format!("{}/{}", NOTES_DIR, encode(&pages[id])),
+                tags: sorted_tags(note),
+                updated: note.updated.map(|t| t.to_rfc3339()),
+                text,
+            });
+        }
+
+        // 标签页(只列出导出的笔记)
+        let mut tag_counts = BTreeMap::new();
+        for (tag, file) in tag_pages.iter() {
+            let tagged: Vec<_> = notes.iter().filter(|(_, note)| note.tags().contains(tag)).collect();
+            if tagged.is_empty() {
+                continue;
+            }
+            tag_counts.insert(tag.as_str(), tagged.len());
+            let items: String = tagged.iter()
+                .map(|(id, note)| note_item(note, &format!("../{}/{}", NOTES_DIR, encode(&pages[id]))))
+                .collect();
+            let body = format!("<h1>#{}</h1>\n<ul class=\"notes\">\n{}</ul>\n", escape(tag), items);
+            let title = format!("#{}", tag);
+            std::fs::write(output_dir.join(TAGS_DIR).join(file), document(&title, &site_head("../"), Some("../"), &body))?;
+        }
+
+        let cloud: String = tag_counts.iter()
+            .map(|(tag, count)| format!(
+                "<a class=\"tag\" href=\"{}/{}\">#{} <span class=\"count\">{}</span></a>\n",
+                TAGS_DIR, encode(&tag_pages[*tag]), escape(tag), count,
+            ))
+            .collect();
+        let items: String = notes.iter()
+            .map(|(id, note)| note_item(note, &format!("{}/{}", NOTES_DIR, encode(&pages[id]))))
+            .collect();
+        let body = format!(
+            "<h1>Notes <span class=\"count\">{}</span></h1>\n<p class=\"tags\">\n{}</p>\n\
+             <ul id=\"results\" class=\"notes\" hidden></ul>\n<ul id=\"notes\" class=\"notes\">\n{}</ul>\n",
+            notes.len(), cloud, items,
+        );
+        std::fs::write(output_dir.join("index.html"), document("Notes", &site_head(""), Some(""), &body))?;
+
+        let json = serde_json::to_string(&index)?;
+        std::fs::write(output_dir.join(SEARCH_INDEX), &json)?;
+        std::fs::write(
+            output_dir.join(SEARCH_INDEX_SCRIPT),
+            format!("window.SEARCH_INDEX = {};\n", json.replace("</", "<\\/")),
+        )?;
+        std::fs::write(output_dir.join(SEARCH_SCRIPT), SEARCH_JS)?;
+        std::fs::write(output_dir.join(STYLESHEET), stylesheet()?)?;
+        Ok(())
+    }
+}
+
+// Markdown渲染为HTML, 改写笔记链接并复制引用的本地附件
+#[derive(Default)]
+struct Renderer<'a> {
+    pages: Option<&'a HashMap<String, String>>,    // 笔记id对应的页面(仅静态网站)
+    output_dir: Option<&'a Path>,
+    copied: HashSet<PathBuf>,
+    raw_html: bool,     // 原样输出笔记中的HTML, 否则按文本转义
+}
+
+impl Renderer<'_> {
+    // 返回HTML和用于搜索的纯文本
+    fn render(&mut self, content: &str) -> (String, String) {
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_FOOTNOTES;
+        let events: Vec<Event> = Parser::new_ext(content, options).collect();
+
+        // 标题锚点与笔记链接中的 #heading 一致
+        let mut slugs = Vec::new();
+        let mut heading: Option<String> = None;
+        for event in events.iter() {
+            match event {
+                Event::Start(Tag::Heading { .. }) => heading = Some(String::new()),
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some(heading) = heading.as_mut() {
+                        heading.push_str(text);
+                    }
+                }
+                Event::End(TagEnd::Heading(_)) => slugs.push(heading_slug(&heading.take().unwrap_or_default())),
+                _ => {}
+            }
+        }
+        let mut used: HashMap<String, usize> = HashMap::new();
+        let mut slugs = slugs.into_iter().map(|slug| {
+            let count = used.entry(slug.clone()).or_default();
+            *count += 1;
+            if *count == 1 { slug } else { format!("{}-{}", slug, *count - 1) }
+        });
+
+        let mut text = String::new();
+        let mut code: Option<(String, String)> = None;
+        let mut output = Vec::with_capacity(events.len());
+        for event in events {
+            match event {
+                Event::Start(Tag::Heading { level, id, classes, attrs }) => {
+                    let id = id.or_else(|| slugs.next().map(CowStr::from));
+                    output.push(Event::Start(Tag::Heading { level, id, classes, attrs }));
+                }
+                Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+                    let dest_url = CowStr::from(self.resolve(&dest_url));
+                    output.push(Event::Start(Tag::Link { link_type, dest_url, title, id }));
+                }
+                Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                    let dest_url = CowStr::from(self.resolve(&dest_url));
+                    output.push(Event::Start(Tag::Image { link_type, dest_url, title, id }));
+                }
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let lang = match kind {
+                        CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    code = Some((lang, String::new()));
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some((lang, code)) = code.take() {
+                        text.push_str(&code);
+                        output.push(Event::Html(highlight(&lang, &code).into()));
+                    }
+                }
+                Event::Text(content) if code.is_some() => {
+                    if let Some((_, code)) = code.as_mut() {
+                        code.push_str(&content);
+                    }
+                }
+                Event::Html(content) | Event::InlineHtml(content) if !self.raw_html => {
+                    output.push(Event::Text(content));
+                }
+                event => {
+                    match &event {
+                        Event::Text(content) | Event::Code(content) => text.push_str(content),
+                        Event::SoftBreak | Event::HardBreak | Event::End(_) if !text.ends_with(' ') => text.push(' '),
+                        _ => {}
+                    }
+                    output.push(event);
+                }
+            }
+        }
+
+        let mut html_output = String::new();
+        html::push_html(&mut html_output, output.into_iter());
+        (html_output, text.trim().to_string())
+    }
+
+    fn resolve(&mut self, url: &str) -> String {
+        if let Some(target) = url.strip_prefix(NOTE_LINK_SCHEME) {
+            let Some(pages) = self.pages else { return url.to_string() };
+            let (id, anchor) = match target.split_once('#') {
+                Some((id, anchor)) => (id, format!("#{}", anchor)),
+                None => (target, String::new()),
+            };
+            // 未导出的笔记(如加锁笔记)链接失效
+            return match pages.get(id) {
+                Some(page) => format!("{}{}", encode(page), anchor),
+                None => "#".to_string(),
+            };
+        }
+        match self.output_dir {
+            Some(output_dir) if is_local(url) => {
+                self.copy_attachment(output_dir, url);
+                format!("../{}", url)
+            }
+            // 与HTML一样, 不输出可执行脚本的链接
+            _ if !self.raw_html && is_script(url) => "#".to_string(),
+            _ => url.to_string(),
+        }
+    }
+
+    // 复制相对路径引用的附件, 保持相同的相对路径
+    fn copy_attachment(&mut self, output_dir: &Path, url: &str) {
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let path = PathBuf::from(percent_encoding::percent_decode_str(path).decode_utf8_lossy().to_string());
+        if !path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+            || !path.is_file()
+            || !self.copied.insert(path.clone()) {
+            return;
+        }
+        let target = output_dir.join(&path);
+        let copied = target.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::copy(&path, &target));
+        if let Err(e) = copied {
+            crate::log_warn!("export_site"; "failed to copy attachment {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn is_script(url: &str) -> bool {
+    let scheme = url.split(':').next().unwrap_or_default().trim().to_ascii_lowercase();
+    url.contains(':') && matches!(scheme.as_str(), "javascript" | "vbscript" | "data")
+}
+
+fn is_local(url: &str) -> bool {
+    !url.is_empty() && !url.contains(':') && !url.starts_with(['#', '/', '\\'])
+}
+
+fn highlight(lang: &str, code: &str) -> String {
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax::find_syntax(lang), &syntax::SYNTAXES, HIGHLIGHT_CLASS);
+    let highlighted = LinesWithEndings::from(code)
+        .try_for_each(|line| generator.parse_html_for_line_which_includes_newline(line));
+    let code = match highlighted {
+        Ok(()) => generator.finalize(),
+        Err(_) => escape(code),
+    };
+    format!("<pre class=\"hl-code\"><code class=\"language-{}\">{}</code></pre>\n", escape(lang), code)
+}
+
+// 样式表: 页面样式和浅色/深色代码高亮主题
+fn stylesheet() -> anyhow::Result<String> {
+    let light = css_for_theme_with_class_style(&syntax::theme(syntax::LIGHT_THEME), HIGHLIGHT_CLASS)?;
+    let dark = css_for_theme_with_class_style(&syntax::theme(syntax::DARK_THEME), HIGHLIGHT_CLASS)?;
+    Ok(format!("{}\n{}\n@media (prefers-color-scheme: dark) {{\n{}}}\n", BASE_CSS, light, dark))
+}
+
+fn site_head(root: &str) -> String {
+    format!("<link rel=\"stylesheet\" href=\"{}{}\">", root, STYLESHEET)
+}
+
+// root 为页面到网站根目录的相对路径, 单个HTML文件没有首页和搜索
+fn document(title: &str, head: &str, root: Option<&str>, body: &str) -> String {
+    let (header, scripts) = match root {
+        Some(root) => (
+            format!(
+                "<header><a class=\"home\" href=\"{root}index.html\">Notes</a>\
+                 <form class=\"search\" action=\"{root}index.html\"><input type=\"search\" name=\"q\" placeholder=\"Search\"></form></header>\n",
+            ),
+            format!("<script src=\"{root}{SEARCH_INDEX_SCRIPT}\"></script>\n<script src=\"{root}{SEARCH_SCRIPT}\"></script>\n"),
+        ),
+        None => (String::new(), String::new()),
+    };
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n{}\n</head>\n<body>\n{}<main>\n{}</main>\n{}</body>\n</html>\n",
+        escape(title), head, header, body, scripts,
+    )
+}
+
+// 更新时间和标签
+fn meta(note: &ExportNote, tag_pages: Option<&BTreeMap<String, String>>) -> String {
+    let mut meta = String::new();
+    if let Some(updated) = note.updated {
+        meta.push_str(&format!(
+            "<time datetime=\"{}\">{}</time> ",
+            updated.to_rfc3339(),
+            updated.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+        ));
+    }
+    for tag in sorted_tags(note) {
+        match tag_pages.and_then(|pages| pages.get(tag)) {
+            Some(page) => meta.push_str(&format!("<a class=\"tag\" href=\"../{}/{}\">#{}</a>", TAGS_DIR, encode(page), escape(tag))),
+            None => meta.push_str(&format!("<span class=\"tag\">#{}</span>", escape(tag))),
+        }
+    }
+    if meta.is_empty() { meta } else { format!("<p class=\"meta\">{}</p>\n", meta) }
+}
+
+fn note_item(note: &ExportNote, href: &str) -> String {
+    let updated = note.updated
+        .map(|t| format!("<time>{}</time>", t.with_timezone(&chrono::Local).format("%Y-%m-%d")))
+        .unwrap_or_default();
+    format!("<li><a href=\"{}\">{}</a>{}</li>\n", href, escape(note.title()), updated)
+}
+
+fn sorted_tags(note: &ExportNote) -> Vec<&str> {
+    let mut tags: Vec<&str> = note.tags().iter().map(String::as_str).collect();
+    tags.sort();
+    tags
+}
+
+// 标签页文件名: 保留文字和数字, 其它字符替换为 -, 重名时添加序号
+fn tag_pages(tags: &HashMap<String, usize>) -> BTreeMap<String, String> {
+    let mut names = HashSet::new();
+    let mut tags: Vec<&String> = tags.keys().collect();
+    tags.sort();
+    tags.into_iter()
+        .map(|tag| {
+            let slug: String = tag.chars().map(|c| if c.is_alphanumeric() { c } else { '-' }).collect();
+            let slug = slug.trim_matches('-');
+            let slug = if slug.is_empty() { "tag" } else { slug };
+            let mut name = format!("{}.html", slug);
+            let mut idx = 1;
+            while !names.insert(name.clone()) {
+                idx += 1;
+                name = format!("{}-{}.html", slug, idx);
+            }
+            (tag.clone(), name)
+        })
+        .collect()
+}
+
+fn encode(path: &str) -> String {
+    utf8_percent_encode(path, PATH_SET).to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+
+    #[test]
+    fn test_export_site() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let first = ExportNote::new(
+            Some("n1".to_string()),
+            "First".to_string(),
+            HashSet::from(["工作/rust".to_string()]),
+            "# Intro\n\nSee [second](note://n2#part-two) and [locked](note://n3).\n\n```rust\nfn main() {}\n```\n".to_string(),
+            None,
+            None,
+        );
+        let second = ExportNote::new(
+            Some("n2".to_string()),
+            "Second </script>".to_string(),
+            HashSet::new(),
+            "## Part Two\n\ntext <img src=x onerror=alert(1)>\n\n<script>alert(2)</script>\n\n[click](javascript:alert(3))\n".to_string(),
+            None,
+            None,
+        );
+        let tags = HashMap::from([("工作/rust".to_string(), 1), ("empty".to_string(), 0)]);
+        HtmlHandler.export_site(&[first, second], &tags, dir).unwrap();
+
+        let first = std::fs::read_to_string(dir.join("notes/n1.html")).unwrap();
+        assert!(first.contains("<h1 id=\"intro\">Intro</h1>"));
+        assert!(first.contains("href=\"n2.html#part-two\"") && first.contains("href=\"#\""));
+        assert!(first.contains("class=\"hl-code\"") && first.contains("href=\"../style.css\""));
+        let second = std::fs::read_to_string(dir.join("notes/n2.html")).unwrap();
+        assert!(second.contains("id=\"part-two\""));
+        // 笔记中的HTML被转义
+        assert!(!second.contains("<img src=x") && second.contains("&lt;img src=x onerror=alert(1)&gt;"));
+        assert!(!second.contains("<script>alert") && second.contains("&lt;script&gt;alert(2)"));
+        assert!(!second.contains("javascript:"));
+        // 只生成有笔记的标签页
+        assert!(dir.join("tags/工作-rust.html").exists() && !dir.join("tags/empty.html").exists());
+        let index = std::fs::read_to_string(dir.join("index.html")).unwrap();
+        assert!(index.contains("href=\"tags/%E5%B7%A5%E4%BD%9C-rust.html\""));
+        let script = std::fs::read_to_string(dir.join("search-index.js")).unwrap();
+        assert!(script.starts_with("window.SEARCH_INDEX = [") && !script.contains("</script>"));
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("search-index.json")).unwrap()).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert!(std::fs::read_to_string(dir.join("style.css")).unwrap().contains(".hl-code"));
+    }
+}
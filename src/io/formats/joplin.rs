@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use crate::data::ExportNote;
+use crate::io::ImportReport;
+use super::{Attachment, ImportConfig, ImportResult, NOTE_LINK_SCHEME};
+
+// 正文中对其他条目的引用 [文本](:/<id>)
+static ITEM_LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(!?)\[([^\]]*)\]\(:/([0-9a-fA-F]{32})(#[^)\s]*)?\)").unwrap()
+});
+
+// 条目类型(元数据中的 type_)
+const TYPE_NOTE: u32 = 1;
+const TYPE_FOLDER: u32 = 2;
+const TYPE_RESOURCE: u32 = 4;
+const TYPE_TAG: u32 = 5;
+const TYPE_NOTE_TAG: u32 = 6;
+
+/// Joplin 导出导入：RAW 目录(每个条目一个.md文件, 末尾为元数据)或 JEX 压缩包
+pub(in crate::io) struct JoplinHandler;
+
+// 一个条目: 首行为标题, 之后为正文, 末尾空行后为 key: value 元数据
+struct Item {
+    path: PathBuf,
+    title: String,
+    body: String,
+    metadata: HashMap<String, String>,
+}
+
+impl Item {
+    fn parse(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let lines: Vec<&str> = text.lines().collect();
+        let mut start = lines.len();
+        while start > 0 && metadata_line(lines[start - 1]).is_some() {
+            start -= 1;
+        }
+        let metadata: HashMap<String, String> = lines[start..].iter()
+            .filter_map(|line| metadata_line(line))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        if !metadata.contains_key("type_") {
+            anyhow::bail!("Not a Joplin item: missing type_");
+        }
+
+        let text_lines = &lines[..start];
+        let title = text_lines.first().map(|s| s.trim().to_string()).unwrap_or_default();
+        let body = text_lines.iter().skip(1).copied().collect::<Vec<_>>().join("\n");
+        Ok(Self {
+            path: path.to_path_buf(),
+            title,
+            body: body.trim_matches('\n').to_string(),
+            metadata,
+        })
+    }
+
+    fn get(&self, key: &str) -> &str {
+        self.metadata.get(key).map(String::as_str).unwrap_or_default()
+    }
+
+    fn id(&self) -> &str {
+        self.get("id")
+    }
+
+    fn item_type(&self) -> u32 {
+        self.get("type_").parse().unwrap_or_default()
+    }
+
+    fn time(&self, key: &str, warnings: &mut Vec<String>) -> Option<DateTime<Utc>> {
+        let value = self.get(key);
+        if value.is_empty() {
+            return None;
+        }
+        match DateTime::parse_from_rfc3339(value) {
+            Ok(time) => Some(time.with_timezone(&Utc)),
+            Err(e) => {
+                warnings.push(format!("parse {} {} failed: {}", key, value, e));
+                None
+            }
+        }
+    }
+}
+
+fn metadata_line(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let valid = !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    (valid && (value.is_empty() || value.starts_with(' '))).then(|| (key, value.trim()))
+}
+
+impl JoplinHandler {
+    // RAW 导出目录: 包含以32位十六进制id命名、带 type_ 元数据的.md文件
+    pub fn is_export(path: &Path) -> bool {
+        let Ok(entries) = std::fs::read_dir(path) else { return false };
+        entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .filter(|path| path.file_stem().and_then(|s| s.to_str()).is_some_and(|s| super::hex_to_uuid(s).is_some()))
+            .take(5)
+            .any(|path| Item::parse(&path).is_ok())
+    }
+
+    // JEX 是 RAW 目录的tar包, 解包到临时目录后导入
+    pub fn import_jex(&self, path: &Path, config: &ImportConfig, report: &mut ImportReport) -> anyhow::Result<Vec<ImportResult>> {
+        let temp_dir = std::env::temp_dir().join(format!("cloud-notepad-jex-{}", uuid::Uuid::new_v4()));
+        tar::Archive::new(File::open(path)?).unpack(&temp_dir)?;
+        let mut results = self.import_directory(&temp_dir, config, report);
+        // 附件在提交导入时复制, 其余解包的文件立即删除
+        for result in results.iter_mut() {
+            result.source = path.join(result.source.strip_prefix(&temp_dir).unwrap_or(&result.source));
+            for attachment in result.attachments.iter_mut() {
+                attachment.temporary = true;
+            }
+        }
+        let keep: HashSet<&Path> = results.iter()
+            .flat_map(|result| result.attachments.iter().map(|a| a.source.as_path()))
+            .collect();
+        for dir in [temp_dir.clone(), temp_dir.join("resources")] {
+            for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+                if entry.path().is_file() && !keep.contains(entry.path().as_path()) {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+        for entry in report.entries.iter_mut() {
+            if let Ok(relative) = entry.source.strip_prefix(&temp_dir) {
+                entry.source = path.join(relative);
+            }
+        }
+        Ok(results)
+    }
+
+    // 导入 RAW 目录: 笔记本层级转换为标签, 资源作为附件, :/id 链接替换为笔记链接
+    pub fn import_directory(&self, dir: &Path, config: &ImportConfig, report: &mut ImportReport) -> Vec<ImportResult> {
+        let mut entries: Vec<PathBuf> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries.flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+                .collect(),
+            Err(e) => {
+                report.push_failure(dir, e.to_string());
+                return Vec::new();
+            }
+        };
+        entries.sort();
+
+        let mut items = Vec::new();
+        for path in entries {
+            match Item::parse(&path) {
+                Ok(item) if item.get("encryption_applied") == "1" => {
+                    report.push_failure(&path, "Item is encrypted, decrypt it in Joplin before exporting".to_string());
+                }
+                Ok(item) => items.push(item),
+                Err(e) => report.push_failure(&path, e.to_string()),
+            }
+        }
+
+        let by_id: HashMap<&str, &Item> = items.iter().map(|item| (item.id(), item)).collect();
+        let tag_names: HashMap<&str, &str> = items.iter()
+            .filter(|item| item.item_type() == TYPE_TAG)
+            .map(|item| (item.id(), item.title.as_str()))
+            .collect();
+        let mut note_tags: HashMap<&str, HashSet<String>> = HashMap::new();
+        for item in items.iter().filter(|item| item.item_type() == TYPE_NOTE_TAG) {
+            if let Some(tag) = tag_names.get(item.get("tag_id")) {
+                note_tags.entry(item.get("note_id")).or_default().insert(tag.split_whitespace().collect::<Vec<_>>().join("_"));
+            }
+        }
+
+        let mut results = Vec::new();
+        for item in items.iter().filter(|item| item.item_type() == TYPE_NOTE) {
+            let Some(note_id) = super::hex_to_uuid(item.id()) else {
+                report.push_failure(&item.path, format!("Invalid item id: {}", item.id()));
+                continue;
+            };
+            let mut warnings = Vec::new();
+            let mut attachments = Vec::new();
+
+            let content = ITEM_LINK.replace_all(&item.body, |caps: &Captures| {
+                let text = &caps[2];
+                match by_id.get(&caps[3]) {
+                    Some(target) if target.item_type() == TYPE_NOTE => {
+                        let id = super::hex_to_uuid(target.id()).unwrap_or_default();
+                        format!("[{}]({}{}{})", text, NOTE_LINK_SCHEME, id, caps.get(4).map_or("", |m| m.as_str()))
+                    }
+                    Some(target) if target.item_type() == TYPE_RESOURCE => {
+                        match self.resource_attachment(dir, target, &note_id) {
+                            Some(attachment) => {
+                                let url = super::attachment_url(config, &attachment.target);
+                                attachments.push(attachment);
+                                format!("{}[{}]({})", &caps[1], text, url)
+                            }
+                            None => {
+                                warnings.push(format!("missing resource file: {}", target.id()));
+                                caps[0].to_string()
+                            }
+                        }
+                    }
+                    _ => {
+                        warnings.push(format!("unresolved link: {}", &caps[0]));
+                        caps[0].to_string()
+                    }
+                }
+            }).to_string();
+
+            let mut tags = note_tags.remove(item.id()).unwrap_or_default();
+            if config.folder_tags
+                && let Some(tag) = folder_path(&by_id, item.get("parent_id")) {
+                tags.insert(tag);
+            }
+            let (created, updated) = if config.preserve_timestamps {
+                (item.time("created_time", &mut warnings), item.time("updated_time", &mut warnings))
+            } else {
+                (None, None)
+            };
+            let title = if item.title.is_empty() { "Untitled".to_string() } else { item.title.clone() };
+
+            let mut note = ExportNote::new(Some(note_id), title, tags, content, created, updated);
+            for key in ["source_url", "author"] {
+                if !item.get(key).is_empty() {
+                    note.properties.insert(key.to_string(), serde_json::Value::String(item.get(key).to_string()));
+                }
+            }
+            if item.get("is_todo") == "1" {
+                note.properties.insert("todo_completed".to_string(), serde_json::Value::Bool(item.get("todo_completed") != "0"));
+            }
+            results.push(ImportResult { note, source: item.path.clone(), warnings, attachments });
+        }
+        results
+    }
+
+    // 资源文件保存在 resources/<id>.<扩展名>
+    fn resource_attachment(&self, dir: &Path, resource: &Item, note_id: &str) -> Option<Attachment> {
+        let extension = resource.get("file_extension");
+        let file_name = if extension.is_empty() { resource.id().to_string() } else { format!("{}.{}", resource.id(), extension) };
+        let source = dir.join("resources").join(&file_name);
+        if !source.is_file() {
+            return None;
+        }
+        let name = match resource.title.trim() {
+            "" => file_name,
+            title => title.replace(['/', '\\'], "_"),
+        };
+        Some(Attachment { source, target: Path::new(note_id).join(name), temporary: false })
+    }
+}
+
+// 笔记本层级(如 Work/Rust), 作为标签时空白替换为 _
+fn folder_path(items: &HashMap<&str, &Item>, parent_id: &str) -> Option<String> {
+    let mut folders = Vec::new();
+    let mut current = parent_id;
+    while let Some(folder) = items.get(current).filter(|item| item.item_type() == TYPE_FOLDER) {
+        // 防止损坏的导出中出现循环
+        if folders.len() > 64 {
+            break;
+        }
+        folders.push(folder.title.split_whitespace().collect::<Vec<_>>().join("_"));
+        current = folder.get("parent_id");
+    }
+    folders.reverse();
+    (!folders.is_empty()).then(|| folders.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_joplin_raw() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("resources")).unwrap();
+        let (folder, child, note, other, resource, tag) = (
+            "a".repeat(32), "b".repeat(32), "c".repeat(32), "d".repeat(32), "e".repeat(32), "f".repeat(32),
+        );
+        std::fs::write(dir.join(format!("{}.md", folder)), format!("Work\n\nid: {}\nparent_id: \ntype_: 2", folder)).unwrap();
+        std::fs::write(dir.join(format!("{}.md", child)), format!("Rust lang\n\nid: {}\nparent_id: {}\ntype_: 2", child, folder)).unwrap();
+        std::fs::write(dir.join(format!("{}.md", note)), format!(
+            "Ownership\n\nSee [other](:/{}) and ![diagram](:/{})\n\nid: {}\nparent_id: {}\n\
+            created_time: 2024-01-02T03:04:05.000Z\nupdated_time: 2024-01-03T03:04:05.000Z\nis_todo: 0\ntype_: 1",
+            other, resource, note, child,
+        )).unwrap();
+        std::fs::write(dir.join(format!("{}.md", other)), format!("Other\n\nid: {}\nparent_id: \ntype_: 1", other)).unwrap();
+        std::fs::write(dir.join(format!("{}.md", resource)), format!("diagram.png\n\nid: {}\nmime: image/png\nfile_extension: png\ntype_: 4", resource)).unwrap();
+        std::fs::write(dir.join("resources").join(format!("{}.png", resource)), [0u8; 4]).unwrap();
+        std::fs::write(dir.join(format!("{}.md", tag)), format!("to read\n\nid: {}\ntype_: 5", tag)).unwrap();
+        std::fs::write(dir.join(format!("{}.md", "1".repeat(32))), format!("id: {}\nnote_id: {}\ntag_id: {}\ntype_: 6", "1".repeat(32), note, tag)).unwrap();
+        assert!(JoplinHandler::is_export(dir));
+
+        let mut report = ImportReport::new(dir);
+        let results = JoplinHandler.import_directory(dir, &ImportConfig::default(), &mut report);
+        assert_eq!(report.failed(), 0);
+        assert_eq!(results.len(), 2);
+        let ownership = results.iter().find(|r| r.note().title() == "Ownership").unwrap();
+        let other_id = super::super::hex_to_uuid(&other).unwrap();
+        assert!(ownership.note().content().starts_with(&format!("See [other](note://{}) and ![diagram](attachments/", other_id)));
+        assert!(ownership.note().tags().contains("Work/Rust_lang"));
+        assert!(ownership.note().tags().contains("to_read"));
+        assert_eq!(ownership.note().created.unwrap().to_rfc3339(), "2024-01-02T03:04:05+00:00");
+        assert_eq!(ownership.attachments().len(), 1);
+    }
+}
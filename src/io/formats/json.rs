@@ -1,5 +1,6 @@
 use std::path::Path;
 use crate::data::ExportNote;
+use crate::utils::crypto::EncryptedBackup;
 use super::{ImportConfig, ImportResult};
 
 pub(in crate::io) struct JsonHandler;
@@ -12,10 +13,24 @@ impl JsonHandler {
         Ok(())
     }
 
-    // 导入JSON文件
-    pub fn import_note(&self, path: &Path, config: &ImportConfig) -> anyhow::Result<ImportResult> {
-        let content = std::fs::read_to_string(path)?;
-        let mut note: ExportNote = serde_json::from_str(&content)?;
+    // 导入JSON文件：单个笔记、笔记数组或加密备份
+    pub fn import_notes(&self, path: &Path, config: &ImportConfig) -> anyhow::Result<Vec<ImportResult>> {
+        let mut content: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if content.get("format").and_then(|f| f.as_str()) == Some(EncryptedBackup::FORMAT) {
+            let backup: EncryptedBackup = serde_json::from_value(content)?;
+            content = serde_json::from_str(&backup.open(&config.backup_passphrase)?)?;
+        }
+
+        let notes: Vec<ExportNote> = match content {
+            serde_json::Value::Array(_) => serde_json::from_value(content)?,
+            _ => vec![serde_json::from_value(content)?],
+        };
+        Ok(notes.into_iter()
+            .map(|note| self.import_note(note, path, config))
+            .collect())
+    }
+
+    fn import_note(&self, mut note: ExportNote, path: &Path, config: &ImportConfig) -> ImportResult {
         let mut warnings = Vec::new();
         if config.preserve_timestamps {
             if note.updated.is_none() {
@@ -25,9 +40,11 @@ impl JsonHandler {
             note.created = None;
             note.updated = None;
         }
-        Ok(ImportResult {
+        ImportResult {
             note,
+            source: path.to_path_buf(),
             warnings,
-        })
+            attachments: Vec::new(),
+        }
     }
 }
\ No newline at end of file
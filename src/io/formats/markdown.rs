@@ -1,4 +1,7 @@
-use std::{collections::HashSet, path::Path};
+use std::collections::BTreeMap;
+use std::path::Path;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 use crate::data::ExportNote;
 use super::{ImportConfig, ImportResult};
 
@@ -8,139 +11,271 @@ pub(in crate::io) struct MarkdownHandler;
 impl MarkdownHandler {
     // 导出单个笔记到Markdown文件
     pub fn export_note(&self, note: &ExportNote, path: &Path, include_metadata: bool) -> anyhow::Result<()> {
+        std::fs::write(path, self.render(note, include_metadata)?)?;
+        Ok(())
+    }
+
+    // 生成Markdown文本
+    pub fn render(&self, note: &ExportNote, include_metadata: bool) -> anyhow::Result<String> {
         let mut content = String::new();
 
-        // 添加元数据作为Front Matter(如果配置需要)
+        // 添加元数据作为YAML Front Matter(如果配置需要)
         if include_metadata {
-            content.push_str(&format!("---\n"));
-            content.push_str(&format!("title: {}\n", note.title()));
-            if let Some(created_at) = note.created() {
-                content.push_str(&format!("created: {}\n", created_at));
-            }
-            if !note.tags().is_empty() {
-                content.push_str(&format!("tags: [{}]\n", 
-                    note.tags().iter().cloned().collect::<Vec<_>>().join(" ")
-                ));
-            }
-            content.push_str(&format!("---\n\n"));
+            content.push_str("---\n");
+            content.push_str(&serde_yaml::to_string(&FrontMatter::from_note(note)?)?);
+            content.push_str("---\n\n");
         }
 
         // 添加内容
         content.push_str(note.content());
-
-        //写入文件
-        std::fs::write(path, content)?;
-        Ok(())
+        Ok(content)
     }
 
     // 从Markdown文件中导入笔记
     pub fn import_note(&self, path: &Path, config: &ImportConfig) -> anyhow::Result<ImportResult> {
-        let content = std::fs::read_to_string(path)?;
-        let (front_matter, content) = self.parse_front_matter(&content);
-        
+        let text = std::fs::read_to_string(path)?;
         let mut warnings = Vec::new();
 
-        let title = front_matter
-            .as_ref()
-            .and_then(|fm| Some(fm.title.clone()))
-            .take_if(|s| !s.is_empty())
+        // front matter 无法解析时保留原文作为内容
+        let (front_matter, content) = match split_front_matter(&text) {
+            Some((yaml, body)) => match FrontMatter::parse(yaml) {
+                Ok(front_matter) => (front_matter, body),
+                Err(e) => {
+                    warnings.push(format!("invalid front matter: {}", e));
+                    (FrontMatter::default(), text.as_str())
+                }
+            },
+            None => (FrontMatter::default(), text.as_str()),
+        };
+
+        let title = front_matter.title.clone()
+            .filter(|s| !s.is_empty())
             .unwrap_or_else(|| {
                 path.file_stem()
                     .and_then(|s| s.to_str())
                     .unwrap_or("Untitled")
                     .to_string()
             });
-        
-        let tags = front_matter
-            .as_ref()
-            .and_then(|fm| Some(fm.tags.clone()))
-            .unwrap_or(HashSet::new());
-
-        let created_at = front_matter
-            .and_then(|fm| fm.created)
-            .and_then(|s| match chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M") {
-                Ok(dt) => Some(dt.and_local_timezone(chrono::Local).unwrap().to_utc()),
-                Err(e) => { warnings.push(format!("parse created time failed: {}", e)); None }
-            });
-        
-        let updated_at = if config.preserve_timestamps {
-            super::get_modified(path, &mut warnings)
+
+        let (created_at, updated_at) = if config.preserve_timestamps {
+            let created_at = front_matter.created.as_deref()
+                .and_then(|s| parse_time(s, "created", &mut warnings));
+            let updated_at = match front_matter.updated.as_deref() {
+                Some(s) => parse_time(s, "updated", &mut warnings),
+                None => super::get_modified(path, &mut warnings),
+            };
+            (created_at, updated_at)
         } else {
-            None
+            (None, None)
         };
 
+        let mut note = ExportNote::new(
+            front_matter.id.clone(),
+            title,
+            front_matter.tags.iter().cloned().collect(),
+            content.to_string(),
+            created_at,
+            updated_at,
+        );
+        note.pinned = front_matter.pinned;
+        note.aliases = front_matter.aliases.clone();
+        note.properties = front_matter.properties(&mut warnings);
+
         Ok(ImportResult {
-            note: ExportNote::new(None, title, tags, content, created_at, updated_at),
+            note,
+            source: path.to_path_buf(),
             warnings,
+            attachments: Vec::new(),
         })
     }
 }
 
-impl MarkdownHandler {
-    // 解析Front Matter(YAML格式)
-    fn parse_front_matter(&self, content: &str) -> (Option<FrontMatter>, String) {
-        // 查找 front matter 块
-        let mut lines = content.lines();
-        let mut front_lines = Vec::new();
-        let mut in_front = false;
-        let mut is_valid = false;
-
-        for line in lines.by_ref() {
-            if line.trim() == "---" {
-                if !in_front {
-                    in_front = true;
-                } else {
-                    // 结束 front matter
-                    break;
-                }
-            } else if in_front {
-                if line.trim().starts_with("title:") { is_valid = true; }
-                front_lines.push(line);
-            }
+// 拆分文件开头以 --- 包围的 front matter 和正文
+fn split_front_matter(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let body = &rest[offset + line.len()..];
+            // 导出时 front matter 后有一个空行
+            let body = body.strip_prefix('\n').or_else(|| body.strip_prefix("\r\n")).unwrap_or(body);
+            return Some((&rest[..offset], body));
         }
+        offset += line.len();
+    }
+    None
+}
 
-        if is_valid {
-            let content_rest = lines.collect::<Vec<_>>().join("\n");
-
-            let mut title = String::new();
-            let mut created = None;
-            let mut tags = HashSet::new();
-
-            for line in front_lines {
-                let line = line.trim();
-                if let Some(rest) = line.strip_prefix("title:") {
-                    title = rest.trim().to_string();
-                } else if let Some(rest) = line.strip_prefix("created:") {
-                    created = Some(rest.trim().to_string());
-                } else if let Some(rest) = line.strip_prefix("tags:") {
-                    // 解析 tags: [tag1 tag2 ...]
-                    let rest = rest.trim();
-                    if rest.starts_with('[') && rest.ends_with(']') {
-                        let tag_str = &rest[1..rest.len()-1];
-                        for tag in tag_str.split_whitespace() {
-                            if !tag.is_empty() {
-                                tags.insert(tag.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-            
-            let fm = FrontMatter {
-                title,
-                created,
-                tags,
-            };
-            (Some(fm), content_rest)
-        } else {
-            (None, content.to_string())
+// 解析时间: RFC 3339 或本地时间(YYYY-MM-DD[ HH:MM[:SS]])
+fn parse_time(s: &str, key: &str, warnings: &mut Vec<String>) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.to_utc());
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"].iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .or_else(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)));
+    match naive.and_then(|dt| dt.and_local_timezone(Local).earliest()) {
+        Some(dt) => Some(dt.to_utc()),
+        None => {
+            warnings.push(format!("parse {} time failed: {}", key, s));
+            None
         }
     }
 }
 
-#[derive(Debug)]
+// YAML Front Matter, 未知的键保留为自定义属性
+#[derive(Debug, Default, Serialize, Deserialize)]
 struct FrontMatter {
-    title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "scalar")]
+    id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "scalar")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "scalar")]
     created: Option<String>,
-    tags: HashSet<String>,
-}
\ No newline at end of file
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "scalar")]
+    updated: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pinned: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "string_list")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "string_list")]
+    aliases: Vec<String>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl FrontMatter {
+    fn from_note(note: &ExportNote) -> anyhow::Result<Self> {
+        let mut tags: Vec<String> = note.tags().iter().cloned().collect();
+        tags.sort();
+        Ok(Self {
+            id: note.id.clone(),
+            title: Some(note.title().to_string()),
+            created: note.created.map(|t| t.to_rfc3339()),
+            updated: note.updated.map(|t| t.to_rfc3339()),
+            pinned: note.pinned,
+            tags,
+            aliases: note.aliases.clone(),
+            extra: note.properties.iter()
+                .map(|(key, value)| Ok((key.clone(), serde_yaml::to_value(value)?)))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+
+    fn parse(yaml: &str) -> anyhow::Result<Self> {
+        // 兼容旧版导出的 `tags: [a b]`(以空格分隔)
+        let yaml = yaml.lines()
+            .map(|line| match line.strip_prefix("tags:").map(str::trim) {
+                Some(rest) if rest.starts_with('[') && rest.ends_with(']') && !rest.contains(',') => {
+                    format!("tags: [{}]", rest[1..rest.len() - 1].split_whitespace().collect::<Vec<_>>().join(", "))
+                }
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if yaml.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&yaml)?)
+    }
+
+    // 自定义属性转换为JSON(无法表示的值记录警告)
+    fn properties(&self, warnings: &mut Vec<String>) -> BTreeMap<String, serde_json::Value> {
+        self.extra.iter()
+            .filter_map(|(key, value)| match serde_json::to_value(value) {
+                Ok(value) => Some((key.clone(), value)),
+                Err(e) => {
+                    warnings.push(format!("unsupported property {}: {}", key, e));
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+fn scalar_to_string(value: serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+// 标量值(字符串、数字或布尔)读取为字符串
+fn scalar<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(scalar_to_string(serde_yaml::Value::deserialize(deserializer)?))
+}
+
+// 列表或以逗号分隔的字符串, 单个字符串(可含空格)为一项
+// (旧版以空格分隔的 `tags: [a b]` 在 FrontMatter::parse 中处理)
+fn string_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let items: Vec<String> = match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::Sequence(items) => items.into_iter().filter_map(scalar_to_string).collect(),
+        value => scalar_to_string(value)
+            .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
+            .unwrap_or_default(),
+    };
+    Ok(items.into_iter().filter(|s| !s.is_empty()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_front_matter_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("note.md");
+        std::fs::write(&path, "---\n\
+            id: n1\n\
+            title: 'Design: v2'\n\
+            created: 2024-03-01 09:30\n\
+            updated: 2024-03-02T10:00:00Z\n\
+            pinned: true\n\
+            tags: [work, big ideas]\n\
+            aliases: v2 spec\n\
+            status: draft\n\
+            rating: 4\n\
+            ---\n\nbody\n").unwrap();
+
+        let handler = MarkdownHandler;
+        let config = ImportConfig::default();
+        let imported = handler.import_note(&path, &config).unwrap();
+        let note = imported.note();
+        assert!(imported.warnings().is_empty());
+        assert_eq!(note.id.as_deref(), Some("n1"));
+        assert_eq!(note.title(), "Design: v2");
+        assert!(note.tags().contains("big ideas"));
+        assert_eq!(note.aliases, vec!["v2 spec"]);
+        assert!(note.pinned);
+        assert_eq!(note.properties["status"], "draft");
+        assert_eq!(note.content(), "body\n");
+
+        // 导出后再导入保持一致
+        let exported = dir.join("exported.md");
+        handler.export_note(note, &exported, true).unwrap();
+        let again = handler.import_note(&exported, &config).unwrap();
+        let again = again.note();
+        assert_eq!(again.id, note.id);
+        assert_eq!(again.title(), note.title());
+        assert_eq!(again.tags(), note.tags());
+        assert_eq!(again.aliases, note.aliases);
+        assert_eq!(again.properties, note.properties);
+        assert_eq!(again.created, note.created);
+        assert_eq!(again.updated, note.updated);
+        assert_eq!(again.content(), note.content());
+    }
+
+    #[test]
+    fn test_legacy_front_matter() {
+        let front_matter = FrontMatter::parse("title: old\ncreated: 2023-01-01 08:00\ntags: [a b]").unwrap();
+        assert_eq!(front_matter.tags, vec!["a", "b"]);
+        assert_eq!(front_matter.created.as_deref(), Some("2023-01-01 08:00"));
+        // 字符串形式: 逗号分隔, 否则整体为一项
+        let front_matter = FrontMatter::parse("tags: work, big ideas\naliases: Design spec").unwrap();
+        assert_eq!(front_matter.tags, vec!["work", "big ideas"]);
+        assert_eq!(front_matter.aliases, vec!["Design spec"]);
+    }
+}
@@ -1,12 +1,28 @@
 pub mod markdown;
 pub mod json;
+pub mod obsidian;
+pub mod enex;
+pub mod joplin;
+pub mod notion;
+pub mod backup;
+pub mod pdf;
+pub mod html;
+pub mod office;
 
 pub(super) use markdown::MarkdownHandler;
 pub(super) use json::JsonHandler;
+pub(super) use obsidian::ObsidianHandler;
+pub(super) use enex::EnexHandler;
+pub(super) use joplin::JoplinHandler;
+pub(super) use notion::NotionHandler;
+pub(super) use backup::BackupHandler;
+pub(super) use pdf::PdfHandler;
+pub(super) use html::HtmlHandler;
+pub(super) use office::{DocxHandler, OdtHandler};
 
 use std::path::Path;
 use chrono::{DateTime, Utc};
-use super::{ImportConfig, ImportResult};
+use super::{Attachment, ImportConfig, ImportResult, NOTE_LINK_SCHEME};
 
 fn get_modified(path: &Path, warnings: &mut Vec<String>) -> Option<DateTime<Utc>> {
     match std::fs::metadata(path)
@@ -20,4 +36,28 @@ fn get_modified(path: &Path, warnings: &mut Vec<String>) -> Option<DateTime<Utc>
             },
             Err(e) => { warnings.push(format!("access modified time failed: {}", e)); None }
         }
-}
\ No newline at end of file
+}
+
+// 将32位十六进制id(Joplin、Notion)转换为uuid格式, 重复导入时id保持不变
+fn hex_to_uuid(hex: &str) -> Option<String> {
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    uuid::Uuid::parse_str(hex).ok().map(|id| id.to_string())
+}
+
+// 附件在笔记内容中的相对链接
+fn attachment_url(config: &ImportConfig, target: &Path) -> String {
+    config.attachments_dir.join(target).to_string_lossy().replace('\\', "/").replace(' ', "%20")
+}
+
+// 标题锚点: 小写, 空白替换为 -
+fn heading_slug(heading: &str) -> String {
+    heading.trim().to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
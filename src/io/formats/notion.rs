@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use zip::ZipArchive;
+use crate::data::ExportNote;
+use super::{Attachment, ImportConfig, ImportResult, NOTE_LINK_SCHEME};
+
+// [文本](相对路径) 或 ![图片](相对路径), Notion 导出的路径经过URL编码
+static MARKDOWN_LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(!?)\[([^\]]*)\]\(([^)\s]+)\)").unwrap()
+});
+// Notion 文件名: "页面标题 <32位十六进制id>"
+static NOTION_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(.*?)\s+([0-9a-fA-F]{32})$").unwrap()
+});
+
+/// Notion 导出的压缩包导入(Markdown页面 + 数据库CSV)
+pub(in crate::io) struct NotionHandler;
+
+// 压缩包中的一个页面
+struct Page {
+    entry: String,      // 压缩包内路径
+    title: String,
+    id: String,
+    content: String,
+    tags: HashSet<String>,
+    properties: Vec<(String, String)>,
+}
+
+impl Page {
+    fn new(entry: &str, content: &str) -> Self {
+        let stem = Path::new(entry).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let (name, id) = split_notion_name(&stem);
+        // 首行的一级标题是完整的页面标题(文件名中会去掉部分字符)
+        let content = content.trim_start_matches('\u{feff}');
+        let (title, content) = match content.split_once('\n') {
+            Some((first, rest)) if first.starts_with("# ") => (first[2..].trim().to_string(), rest.trim_start_matches('\n')),
+            None if content.starts_with("# ") => (content[2..].trim().to_string(), ""),
+            _ => (name.to_string(), content),
+        };
+        Self {
+            entry: entry.to_string(),
+            title: if title.is_empty() { "Untitled".to_string() } else { title },
+            id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            content: content.to_string(),
+            tags: HashSet::new(),
+            properties: Vec::new(),
+        }
+    }
+
+    fn dir(&self) -> &str {
+        self.entry.rsplit_once('/').map_or("", |(dir, _)| dir)
+    }
+}
+
+// 去掉名称末尾的id, 返回(名称, uuid格式的id)
+fn split_notion_name(name: &str) -> (&str, Option<String>) {
+    match NOTION_NAME.captures(name) {
+        Some(caps) => (caps.get(1).map_or(name, |m| m.as_str()), super::hex_to_uuid(&caps[2])),
+        None => (name, None),
+    }
+}
+
+impl NotionHandler {
+    pub fn import_zip(&self, path: &Path, config: &ImportConfig) -> anyhow::Result<Vec<ImportResult>> {
+        let temp_dir = std::env::temp_dir().join(format!("cloud-notepad-notion-{}", uuid::Uuid::new_v4()));
+        let mut results = Vec::new();
+        self.import_archive(ZipArchive::new(File::open(path)?)?, path, config, &temp_dir, &mut results)?;
+        Ok(results)
+    }
+
+    // 大型工作区会导出为包含多个分卷压缩包的压缩包
+    fn import_archive<R: Read + Seek>(
+        &self,
+        mut archive: ZipArchive<R>,
+        source: &Path,
+        config: &ImportConfig,
+        temp_dir: &Path,
+        results: &mut Vec<ImportResult>,
+    ) -> anyhow::Result<()> {
+        let mut pages = Vec::new();
+        let mut tables = HashMap::new();
+        let mut files = HashSet::new();
+        let mut nested = Vec::new();
+        for idx in 0..archive.len() {
+            let mut entry = archive.by_index(idx)?;
+            if entry.is_dir() {
+                continue;
+            }
+            let name = entry.name().to_string();
+            let extension = Path::new(&name).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+            match extension.as_str() {
+                "md" => {
+                    let mut text = String::new();
+                    entry.read_to_string(&mut text)?;
+                    pages.push(Page::new(&name, &text));
+                }
+                "csv" => {
+                    let mut text = String::new();
+                    entry.read_to_string(&mut text)?;
+                    // 同时存在 X.csv 和 X_all.csv 时, 后者包含所有行
+                    let stem = name.trim_end_matches(".csv");
+                    let (table, all) = match stem.strip_suffix("_all") {
+                        Some(table) => (table.to_string(), true),
+                        None => (stem.to_string(), false),
+                    };
+                    if all || !tables.contains_key(&table) {
+                        tables.insert(table, text);
+                    }
+                }
+                "zip" => {
+                    let mut data = Vec::new();
+                    entry.read_to_end(&mut data)?;
+                    nested.push(data);
+                }
+                _ => {
+                    files.insert(name);
+                }
+            }
+        }
+        for data in nested {
+            self.import_archive(ZipArchive::new(Cursor::new(data))?, source, config, temp_dir, results)?;
+        }
+
+        for (table, text) in tables.iter() {
+            apply_table(table, text, &mut pages)?;
+        }
+
+        let ids: HashSet<String> = pages.iter().map(|page| page.id.clone()).collect();
+        for page in pages {
+            let mut warnings = Vec::new();
+            let mut linked_files = Vec::new();
+            let content = MARKDOWN_LINK.replace_all(&page.content, |caps: &Captures| {
+                let url = &caps[3];
+                if url.contains("://") || url.starts_with('#') || url.starts_with("mailto:") {
+                    return caps[0].to_string();
+                }
+                let decoded = percent_encoding::percent_decode_str(url).decode_utf8_lossy();
+                let Some(entry) = resolve_entry(page.dir(), &decoded) else {
+                    warnings.push(format!("unresolved link: {}", &caps[0]));
+                    return caps[0].to_string();
+                };
+                if files.contains(&entry) {
+                    let name = Path::new(&entry).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                    let target = Path::new(&page.id).join(&name);
+                    let url = super::attachment_url(config, &target);
+                    linked_files.push((entry, target));
+                    return format!("{}[{}]({})", &caps[1], &caps[2], url);
+                }
+                let stem = Path::new(&entry).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                match split_notion_name(&stem).1.filter(|id| ids.contains(id)) {
+                    Some(id) => format!("[{}]({}{})", &caps[2], NOTE_LINK_SCHEME, id),
+                    None => {
+                        warnings.push(format!("unresolved link: {}", &caps[0]));
+                        caps[0].to_string()
+                    }
+                }
+            }).to_string();
+
+            // 每个笔记的附件单独解包, 提交导入后删除
+            let mut attachments = Vec::new();
+            for (entry, target) in linked_files {
+                match extract_entry(&mut archive, &entry, temp_dir) {
+                    Ok(path) => attachments.push(Attachment { source: path, target, temporary: true }),
+                    Err(e) => warnings.push(format!("failed to extract {}: {}", entry, e)),
+                }
+            }
+
+            let folder = if config.folder_tags { folder_tag(page.dir()) } else { None };
+            let mut tags = page.tags;
+            tags.extend(folder);
+            let mut note = ExportNote::new(Some(page.id), page.title, tags, content, None, None);
+            for (key, value) in page.properties {
+                note.properties.insert(key, serde_json::Value::String(value));
+            }
+            results.push(ImportResult { note, source: source.join(&page.entry), warnings, attachments });
+        }
+        Ok(())
+    }
+}
+
+// 数据库CSV: 首列为页面标题, 其余列作为属性(Tags列作为标签), 没有对应页面的行导入为空笔记
+fn apply_table(table: &str, text: &str, pages: &mut Vec<Page>) -> anyhow::Result<()> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(text.trim_start_matches('\u{feff}').as_bytes());
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.trim().to_string()).collect();
+    let mut used = HashSet::new();
+    for row in reader.records() {
+        let row = row?;
+        let title = row.get(0).unwrap_or_default().trim();
+        let idx = match pages.iter().enumerate()
+            .position(|(idx, page)| page.dir() == table && page.title == title && !used.contains(&idx)) {
+            Some(idx) => idx,
+            None => {
+                let (name, _) = split_notion_name(table);
+                pages.push(Page::new(&format!("{}/{}.md", table, title), ""));
+                let idx = pages.len() - 1;
+                pages[idx].title = if title.is_empty() { format!("{} row", name) } else { title.to_string() };
+                idx
+            }
+        };
+        used.insert(idx);
+
+        let page = &mut pages[idx];
+        for (header, value) in headers.iter().zip(row.iter()).skip(1) {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            if header.eq_ignore_ascii_case("tags") {
+                page.tags.extend(value.split(',').map(|t| t.split_whitespace().collect::<Vec<_>>().join("_")).filter(|t| !t.is_empty()));
+            } else {
+                page.properties.push((header.clone(), value.to_string()));
+            }
+        }
+        // 页面开头重复列出的属性行(Key: value)已保存为属性
+        let lines: Vec<&str> = page.content.lines().collect();
+        let skip = lines.iter()
+            .take_while(|line| line.split_once(": ").is_some_and(|(key, _)| headers.iter().any(|h| h == key)))
+            .count();
+        if skip > 0 {
+            page.content = lines[skip..].join("\n").trim_start_matches('\n').to_string();
+        }
+    }
+    Ok(())
+}
+
+// 将相对链接解析为压缩包内的路径
+fn resolve_entry(dir: &str, link: &str) -> Option<String> {
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in link.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+fn extract_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, entry: &str, temp_dir: &Path) -> anyhow::Result<PathBuf> {
+    let mut file = archive.by_name(entry)?;
+    let name = Path::new(entry).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    std::fs::create_dir_all(temp_dir)?;
+    let path = temp_dir.join(format!("{}-{}", uuid::Uuid::new_v4(), name));
+    std::io::copy(&mut file, &mut File::create(&path)?)?;
+    Ok(path)
+}
+
+// 上级页面的标题(去掉id)作为层级标签
+fn folder_tag(dir: &str) -> Option<String> {
+    let folders: Vec<String> = dir.split('/')
+        .filter(|p| !p.is_empty())
+        .map(|p| split_notion_name(p).0.split_whitespace().collect::<Vec<_>>().join("_"))
+        .filter(|p| !p.is_empty())
+        .collect();
+    (!folders.is_empty()).then(|| folders.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use super::*;
+
+    #[test]
+    fn test_import_notion_zip() {
+        let (home, task, db) = ("1".repeat(32), "2".repeat(32), "3".repeat(32));
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("export.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        let mut add = |name: String, data: &[u8]| {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(data).unwrap();
+        };
+        add(format!("Home {}.md", home), format!(
+            "# Home\n\nSee [Write docs](Home%20{home}/Tasks%20{db}/Write%20docs%20{task}.md) \
+            ![chart](Home%20{home}/chart.png) [gone](Gone%20{db}.md)\n"
+        ).as_bytes());
+        add(format!("Home {}/chart.png", home), &[0u8; 4]);
+        add(format!("Home {}/Tasks {}.csv", home, db), "\u{feff}Name,Status,Tags\nWrite docs,Done,\"docs, q1\"\nEmpty row,Todo,\n".as_bytes());
+        add(format!("Home {}/Tasks {}/Write docs {}.md", home, db, task), b"# Write docs\n\nStatus: Done\nTags: docs, q1\n\nBody");
+        zip.finish().unwrap();
+
+        let results = NotionHandler.import_zip(&path, &ImportConfig::default()).unwrap();
+        assert_eq!(results.len(), 3);
+        let home = results.iter().find(|r| r.note().title() == "Home").unwrap();
+        let task = results.iter().find(|r| r.note().title() == "Write docs").unwrap();
+        let task_id = task.note().id.clone().unwrap();
+        assert_eq!(task_id, "22222222-2222-2222-2222-222222222222");
+        assert!(home.note().content().starts_with(&format!("See [Write docs](note://{}) ![chart](attachments/", task_id)));
+        assert_eq!(home.warnings().len(), 1);
+        assert_eq!(home.attachments().len(), 1);
+        assert_eq!(task.note().content(), "Body");
+        assert!(task.note().tags().contains("q1") && task.note().tags().contains("Home/Tasks"));
+        assert_eq!(task.note().properties.get("Status"), Some(&serde_json::Value::String("Done".to_string())));
+        assert!(results.iter().any(|r| r.note().title() == "Empty row"));
+        for attachment in home.attachments() {
+            let _ = std::fs::remove_file(&attachment.source);
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use super::{Attachment, ImportConfig, ImportResult, NOTE_LINK_SCHEME, heading_slug};
+
+// [[目标#标题|别名]] 或 ![[附件]]
+static WIKILINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(!?)\[\[([^\[\]|#]*)(#[^\[\]|]*)?(?:\|([^\[\]]*))?\]\]").unwrap()
+});
+// 行内标签 #tag 或 #parent/child(不能是纯数字), 链接目标 [文字](#标题) 不是标签
+static INLINE_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:^\(?|\s|[^\]]\()#([\p{L}\p{N}_/-]*[\p{L}_/-][\p{L}\p{N}_/-]*)").unwrap()
+});
+
+/// Obsidian 仓库导入：在Markdown导入结果上解析行内标签、双链和嵌入的附件
+pub(in crate::io) struct ObsidianHandler;
+
+impl ObsidianHandler {
+    // 含有 .obsidian 配置目录的目录视为 Obsidian 仓库
+    pub fn is_vault(path: &Path) -> bool {
+        path.join(".obsidian").is_dir()
+    }
+
+    // 为笔记分配id并将链接替换为 note://<id>, 无法解析的链接记录到警告中
+    pub fn link_notes(&self, root: &Path, results: &mut [ImportResult], config: &ImportConfig) {
+        for result in results.iter_mut() {
+            if result.note.id.is_none() {
+                result.note.id = Some(uuid::Uuid::new_v4().to_string());
+            }
+        }
+        let notes = NoteIndex::new(root, results);
+        let files = attachment_index(root);
+
+        for result in results.iter_mut() {
+            let note_id = result.note.id.clone().unwrap_or_default();
+            let note_dir = result.source.parent().unwrap_or(root).to_path_buf();
+            let mut tags = HashSet::new();
+            let mut unresolved = Vec::new();
+            let mut attachments = Vec::new();
+
+            let mut in_fence = false;
+            let content = result.note.content().split_inclusive('\n')
+                .map(|line| {
+                    let trimmed = line.trim_start();
+                    if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                        in_fence = !in_fence;
+                        return line.to_string();
+                    }
+                    if in_fence {
+                        return line.to_string();
+                    }
+                    // 行内代码(反引号之间)保持原样
+                    line.split('`').enumerate()
+                        .map(|(idx, segment)| {
+                            if idx % 2 == 1 {
+                                return segment.to_string();
+                            }
+                            tags.extend(INLINE_TAG.captures_iter(segment).map(|c| c[1].trim_end_matches('/').to_string()));
+                            WIKILINK.replace_all(segment, |caps: &Captures| {
+                                let link = Link::from_captures(caps);
+                                match link.resolve(&notes, &files, root, &note_dir) {
+                                    Some(Target::Note(id)) => link.to_note_link(&id),
+                                    Some(Target::Heading) => link.to_heading_link(),
+                                    Some(Target::File(path)) => {
+                                        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                                        let target = Path::new(&note_id).join(&name);
+                                        let url = config.attachments_dir.join(&target).to_string_lossy().replace('\\', "/");
+                                        attachments.push(Attachment { source: path, target, temporary: false });
+                                        link.to_file_link(&name, &url)
+                                    }
+                                    None => {
+                                        unresolved.push(caps[0].to_string());
+                                        caps[0].to_string()
+                                    }
+                                }
+                            }).to_string()
+                        })
+                        .collect::<Vec<_>>()
+                        .join("`")
+                })
+                .collect::<String>();
+
+            result.note.set_content(content);
+            for tag in tags.into_iter().filter(|t| !t.is_empty()) {
+                result.note.add_tag(tag);
+            }
+            result.warnings.extend(unresolved.into_iter().map(|link| format!("unresolved link: {}", link)));
+            result.attachments.extend(attachments);
+        }
+    }
+}
+
+// 链接解析结果
+enum Target {
+    Note(String),   // 其他笔记的id
+    Heading,        // 本笔记内的标题
+    File(PathBuf),  // 附件
+}
+
+struct Link<'a> {
+    embed: bool,
+    target: &'a str,
+    heading: Option<&'a str>,
+    alias: Option<&'a str>,
+}
+
+impl<'a> Link<'a> {
+    fn from_captures(caps: &'a Captures) -> Self {
+        Self {
+            embed: !caps[1].is_empty(),
+            target: caps.get(2).map_or("", |m| m.as_str()).trim(),
+            heading: caps.get(3).map(|m| m.as_str()[1..].trim()).filter(|h| !h.is_empty()),
+            alias: caps.get(4).map(|m| m.as_str().trim()).filter(|a| !a.is_empty()),
+        }
+    }
+
+    fn resolve(&self, notes: &NoteIndex, files: &HashMap<String, PathBuf>, root: &Path, note_dir: &Path) -> Option<Target> {
+        if self.target.is_empty() {
+            return self.heading.map(|_| Target::Heading);
+        }
+        let name = self.target.strip_suffix(".md").unwrap_or(self.target);
+        if let Some(id) = notes.find(name) {
+            return Some(Target::Note(id.to_string()));
+        }
+        // 附件: 相对当前笔记、相对仓库根目录, 或按文件名查找
+        [note_dir.join(self.target), root.join(self.target)].into_iter()
+            .find(|path| path.is_file())
+            .or_else(|| {
+                let file_name = Path::new(self.target).file_name()?.to_string_lossy().to_lowercase();
+                files.get(&file_name).cloned()
+            })
+            .map(Target::File)
+    }
+
+    fn text(&self) -> String {
+        match (self.alias, self.heading) {
+            (Some(alias), _) => alias.to_string(),
+            (None, Some(heading)) if self.target.is_empty() => heading.to_string(),
+            (None, Some(heading)) => format!("{} > {}", self.target, heading),
+            (None, None) => self.target.to_string(),
+        }
+    }
+
+    fn anchor(&self) -> String {
+        self.heading.map(|h| format!("#{}", heading_slug(h))).unwrap_or_default()
+    }
+
+    fn to_note_link(&self, id: &str) -> String {
+        format!("[{}]({}{}{})", self.text(), NOTE_LINK_SCHEME, id, self.anchor())
+    }
+
+    fn to_heading_link(&self) -> String {
+        format!("[{}]({})", self.text(), self.anchor())
+    }
+
+    fn to_file_link(&self, name: &str, url: &str) -> String {
+        let text = self.alias.unwrap_or(name);
+        let url = url.replace(' ', "%20");
+        if self.embed {
+            format!("![{}]({})", text, url)
+        } else {
+            format!("[{}]({})", text, url)
+        }
+    }
+}
+
+// 按相对路径、文件名、标题和别名查找笔记id(不区分大小写)
+struct NoteIndex {
+    names: HashMap<String, String>,
+}
+
+impl NoteIndex {
+    fn new(root: &Path, results: &[ImportResult]) -> Self {
+        let mut names = HashMap::new();
+        // 后插入的键不覆盖先插入的: 路径优先于文件名, 文件名优先于标题和别名
+        for result in results.iter() {
+            let id = result.note.id.clone().unwrap_or_default();
+            if let Ok(relative) = result.source.strip_prefix(root) {
+                let path = relative.with_extension("").to_string_lossy().replace('\\', "/");
+                names.entry(path.to_lowercase()).or_insert(id.clone());
+            }
+        }
+        for result in results.iter() {
+            let id = result.note.id.clone().unwrap_or_default();
+            if let Some(stem) = result.source.file_stem() {
+                names.entry(stem.to_string_lossy().to_lowercase()).or_insert(id.clone());
+            }
+        }
+        for result in results.iter() {
+            let id = result.note.id.clone().unwrap_or_default();
+            for name in std::iter::once(result.note.title()).chain(result.note.aliases.iter().map(String::as_str)) {
+                names.entry(name.to_lowercase()).or_insert(id.clone());
+            }
+        }
+        Self { names }
+    }
+
+    fn find(&self, name: &str) -> Option<&str> {
+        self.names.get(&name.replace('\\', "/").to_lowercase()).map(String::as_str)
+    }
+}
+
+// 仓库中的非Markdown文件(按小写文件名索引, 跳过隐藏目录和符号链接循环)
+fn attachment_index(root: &Path) -> HashMap<String, PathBuf> {
+    fn walk(dir: &Path, visited: &mut HashSet<PathBuf>, files: &mut HashMap<String, PathBuf>) {
+        if !dir.canonicalize().is_ok_and(|dir| visited.insert(dir)) {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                walk(&path, visited, files);
+            } else if path.extension().is_none_or(|ext| ext != "md") {
+                files.entry(entry.file_name().to_string_lossy().to_lowercase()).or_insert(path);
+            }
+        }
+    }
+
+    let mut files = HashMap::new();
+    walk(root, &mut HashSet::new(), &mut files);
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{ImportReport, Importer};
+    use super::*;
+
+    #[test]
+    fn test_import_obsidian_vault() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join(".obsidian")).unwrap();
+        std::fs::create_dir_all(dir.join("projects")).unwrap();
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join(".obsidian/app.json"), "{}").unwrap();
+        std::fs::write(dir.join("assets/diagram.png"), [0u8; 4]).unwrap();
+        std::fs::write(dir.join("projects/Roadmap.md"), "---\naliases: [plan]\n---\n# Goals\n").unwrap();
+        std::fs::write(dir.join("Home.md"), "See [[Roadmap#Goals|the plan]] and [[plan]].\n\
+            ![[diagram.png]] #status/active `#not-a-tag` [[Missing]]\n\
+            [see](#setup) (#review)\n\
+            ```\n[[Roadmap]] #code\n```\n").unwrap();
+
+        let config = ImportConfig::default();
+        let mut report = ImportReport::new(dir);
+        let results = Importer::import(dir, &config, &mut report).unwrap();
+        assert_eq!(report.failed(), 0);
+        assert_eq!(results.len(), 2);
+        let home = results.iter().find(|r| r.note().title() == "Home").unwrap();
+        let roadmap = results.iter().find(|r| r.note().title() == "Roadmap").unwrap();
+        let roadmap_id = roadmap.note().id.clone().unwrap();
+
+        let content = home.note().content();
+        assert!(content.contains(&format!("[the plan](note://{}#goals)", roadmap_id)));
+        assert!(content.contains(&format!("[plan](note://{})", roadmap_id)));
+        assert!(content.contains("![diagram.png](attachments/"));
+        assert!(content.contains("```\n[[Roadmap]] #code\n```"));
+        assert!(home.note().tags().contains("status/active"));
+        assert!(!home.note().tags().contains("not-a-tag") && !home.note().tags().contains("code"));
+        assert!(home.note().tags().contains("review") && !home.note().tags().contains("setup"));
+        assert_eq!(home.attachments().len(), 1);
+        assert_eq!(home.warnings(), &vec!["unresolved link: [[Missing]]".to_string()]);
+        assert!(roadmap.note().tags().contains("projects"));
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use pulldown_cmark::{Alignment, Event, Options, Parser, Tag, TagEnd};
+use crate::io::NOTE_LINK_SCHEME;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub strike: bool,
+    pub code: bool,
+}
+
+impl RunStyle {
+    // 样式组合的编号(用于生成样式名)
+    pub fn bits(&self) -> u8 {
+        self.bold as u8 | (self.italic as u8) << 1 | (self.strike as u8) << 2 | (self.code as u8) << 3
+    }
+}
+
+pub enum Inline {
+    Text { text: String, style: RunStyle, link: Option<String> },
+    Break,
+    Image { index: usize, alt: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParagraphStyle {
+    Body,
+    Heading(u8),
+    Quote,
+}
+
+pub enum Block {
+    Paragraph(ParagraphStyle, Vec<Inline>),
+    Code(Vec<String>),
+    List { start: Option<u64>, items: Vec<Vec<Block>> },    // start 为 None 时是无序列表
+    Table { aligns: Vec<Alignment>, head: usize, rows: Vec<Vec<Vec<Inline>>> },
+    Rule,
+}
+
+// 嵌入的图片(PNG/JPEG)
+pub struct Picture {
+    pub data: Vec<u8>,
+    pub extension: &'static str,
+    pub width: u32,     // 像素
+    pub height: u32,
+}
+
+impl Picture {
+    fn load(data: Vec<u8>) -> anyhow::Result<Self> {
+        if data.starts_with(&[0xFF, 0xD8]) {
+            let mut decoder = jpeg_decoder::Decoder::new(std::io::Cursor::new(&data));
+            decoder.read_info()?;
+            let info = decoder.info().ok_or_else(|| anyhow::anyhow!("Invalid JPEG header"))?;
+            let (width, height) = (info.width as u32, info.height as u32);
+            return Ok(Self { data, extension: "jpeg", width, height });
+        }
+        let reader = png::Decoder::new(std::io::Cursor::new(&data)).read_info()?;
+        let (width, height) = (reader.info().width, reader.info().height);
+        Ok(Self { data, extension: "png", width, height })
+    }
+
+    pub fn media_type(&self) -> &'static str {
+        match self.extension {
+            "jpeg" => "image/jpeg",
+            _ => "image/png",
+        }
+    }
+
+    // 按96dpi换算为英寸, 缩小到不超过 max_width
+    pub fn size_inches(&self, max_width: f32) -> (f32, f32) {
+        let (width, height) = (self.width as f32 / 96.0, self.height as f32 / 96.0);
+        let scale = if width > max_width { max_width / width } else { 1.0 };
+        (width * scale, height * scale)
+    }
+}
+
+enum Frame {
+    List(Option<u64>, Vec<Vec<Block>>),
+    Item(Vec<Block>),
+}
+
+struct Table {
+    aligns: Vec<Alignment>,
+    head: usize,
+    rows: Vec<Vec<Vec<Inline>>>,
+}
+
+/// 文档结构: Markdown解析后的段落、列表、代码块、表格和图片, 由DOCX/ODT分别输出
+#[derive(Default)]
+pub struct Document {
+    pub blocks: Vec<Block>,
+    pub pictures: Vec<Picture>,
+    frames: Vec<Frame>,
+    paragraph: Option<ParagraphStyle>,
+    inlines: Vec<Inline>,
+    code: Option<String>,
+    table: Option<Table>,
+    image: Option<(String, String)>,
+    link: Option<String>,
+    quote: usize,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    loaded: HashMap<PathBuf, Option<usize>>,
+}
+
+impl Document {
+    pub fn parse(markdown: &str) -> Self {
+        let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+        let mut document = Self::default();
+        for event in Parser::new_ext(markdown, options) {
+            document.event(event);
+        }
+        document.flush();
+        document
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if let Some(code) = self.code.as_mut() {
+                    code.push_str(&text);
+                } else if let Some((_, alt)) = self.image.as_mut() {
+                    alt.push_str(&text);
+                } else {
+                    self.text(&text, false);
+                }
+            }
+            Event::Code(text) => self.text(&text, true),
+            Event::SoftBreak => self.text(" ", false),
+            Event::HardBreak => self.push_inline(Inline::Break),
+            Event::TaskListMarker(checked) => self.text(if checked { "☑ " } else { "☐ " }, false),
+            Event::Rule => {
+                self.flush();
+                self.push_block(Block::Rule);
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.paragraph = Some(self.body_style()),
+            Tag::Heading { level, .. } => self.paragraph = Some(ParagraphStyle::Heading(level as u8)),
+            Tag::BlockQuote(_) => {
+                self.flush();
+                self.quote += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.flush();
+                self.code = Some(String::new());
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.frames.push(Frame::List(start, Vec::new()));
+            }
+            Tag::Item => self.frames.push(Frame::Item(Vec::new())),
+            Tag::Table(aligns) => {
+                self.flush();
+                self.table = Some(Table { aligns, head: 0, rows: Vec::new() });
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strike += 1,
+            // 只保留外部链接, 笔记链接和页内锚点在文档中无法打开
+            Tag::Link { dest_url, .. } => {
+                let external = dest_url.contains("://") && !dest_url.starts_with(NOTE_LINK_SCHEME);
+                self.link = (external || dest_url.starts_with("mailto:")).then(|| dest_url.to_string());
+            }
+            Tag::Image { dest_url, .. } => self.image = Some((dest_url.to_string(), String::new())),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::Heading(_) => self.flush(),
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.quote = self.quote.saturating_sub(1);
+            }
+            TagEnd::CodeBlock => {
+                if let Some(code) = self.code.take() {
+                    let lines = code.trim_end_matches('\n').split('\n').map(str::to_string).collect();
+                    self.push_block(Block::Code(lines));
+                }
+            }
+            TagEnd::Item => {
+                self.flush();
+                if let Some(Frame::Item(blocks)) = self.frames.pop()
+                    && let Some(Frame::List(_, items)) = self.frames.last_mut() {
+                    items.push(blocks);
+                }
+            }
+            TagEnd::List(_) => {
+                if let Some(Frame::List(start, items)) = self.frames.pop() {
+                    self.push_block(Block::List { start, items });
+                }
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    table.head = table.rows.len();
+                }
+            }
+            TagEnd::TableCell => {
+                let inlines = std::mem::take(&mut self.inlines);
+                if let Some(row) = self.table.as_mut().and_then(|t| t.rows.last_mut()) {
+                    row.push(inlines);
+                }
+            }
+            TagEnd::Table => {
+                if let Some(Table { aligns, head, rows }) = self.table.take() {
+                    self.push_block(Block::Table { aligns, head, rows });
+                }
+            }
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Strikethrough => self.strike = self.strike.saturating_sub(1),
+            TagEnd::Link => self.link = None,
+            TagEnd::Image => {
+                if let Some((url, alt)) = self.image.take() {
+                    match self.load_picture(&url) {
+                        Some(index) => self.push_inline(Inline::Image { index, alt }),
+                        // 远程图片和无法读取的图片显示替代文本
+                        None => self.text(&format!("[{}]", if alt.is_empty() { &url } else { &alt }), false),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn body_style(&self) -> ParagraphStyle {
+        if self.quote > 0 { ParagraphStyle::Quote } else { ParagraphStyle::Body }
+    }
+
+    fn text(&mut self, text: &str, code: bool) {
+        let style = RunStyle { bold: self.bold > 0, italic: self.italic > 0, strike: self.strike > 0, code };
+        // 合并样式相同的相邻文本
+        if let Some(Inline::Text { text: last, style: last_style, link }) = self.inlines.last_mut()
+            && *last_style == style
+            && *link == self.link {
+            last.push_str(text);
+            return;
+        }
+        self.push_inline(Inline::Text { text: text.to_string(), style, link: self.link.clone() });
+    }
+
+    fn push_inline(&mut self, inline: Inline) {
+        // 紧凑列表项中的文本没有段落标签
+        if self.paragraph.is_none() && self.table.is_none() {
+            self.paragraph = Some(self.body_style());
+        }
+        self.inlines.push(inline);
+    }
+
+    fn push_block(&mut self, block: Block) {
+        match self.frames.last_mut() {
+            Some(Frame::Item(blocks)) => blocks.push(block),
+            _ => self.blocks.push(block),
+        }
+    }
+
+    // 结束当前段落
+    fn flush(&mut self) {
+        if let Some(style) = self.paragraph.take() {
+            let inlines = std::mem::take(&mut self.inlines);
+            self.push_block(Block::Paragraph(style, inlines));
+        }
+    }
+
+    fn load_picture(&mut self, url: &str) -> Option<usize> {
+        if url.contains("://") && !url.starts_with("file://") {
+            return None;
+        }
+        let path = PathBuf::from(
+            percent_encoding::percent_decode_str(url.trim_start_matches("file://")).decode_utf8_lossy().to_string()
+        );
+        if let Some(index) = self.loaded.get(&path) {
+            return *index;
+        }
+        let index = match std::fs::read(&path).map_err(anyhow::Error::from).and_then(Picture::load) {
+            Ok(picture) => {
+                self.pictures.push(picture);
+                Some(self.pictures.len() - 1)
+            }
+            Err(e) => {
+                crate::log_warn!("export_office"; "failed to load image {}: {}", path.display(), e);
+                None
+            }
+        };
+        self.loaded.insert(path, index);
+        index
+    }
+}
@@ -0,0 +1,386 @@
+use std::io::Write;
+use pulldown_cmark::Alignment;
+use zip::{ZipWriter, write::SimpleFileOptions};
+use crate::data::ExportNote;
+use super::{APP_NAME, Properties, escape, document::{Block, Document, Inline, ParagraphStyle, RunStyle}};
+
+const MAIN_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const REL_TYPE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const EMU_PER_INCH: f32 = 914400.0;
+// A4 纸, 2.54cm 页边距的版心宽度
+const TEXT_WIDTH_TWIPS: u32 = 9026;
+const LIST_INDENT_TWIPS: u32 = 720;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:docDefaults>
+<w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:eastAsia="Microsoft YaHei" w:cs="Calibri"/><w:sz w:val="22"/><w:szCs w:val="22"/><w:lang w:val="en-US" w:eastAsia="zh-CN"/></w:rPr></w:rPrDefault>
+<w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="288" w:lineRule="auto"/></w:pPr></w:pPrDefault>
+</w:docDefaults>
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>
+<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="60"/></w:pPr><w:rPr><w:b/><w:sz w:val="48"/><w:szCs w:val="48"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Subtitle"><w:name w:val="Subtitle"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="240"/></w:pPr><w:rPr><w:color w:val="737373"/><w:sz w:val="20"/><w:szCs w:val="20"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="40"/><w:szCs w:val="40"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="320" w:after="120"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="34"/><w:szCs w:val="34"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="280" w:after="80"/><w:outlineLvl w:val="2"/></w:pPr><w:rPr><w:b/><w:sz w:val="30"/><w:szCs w:val="30"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading4"><w:name w:val="heading 4"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="80"/><w:outlineLvl w:val="3"/></w:pPr><w:rPr><w:b/><w:sz w:val="26"/><w:szCs w:val="26"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading5"><w:name w:val="heading 5"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="60"/><w:outlineLvl w:val="4"/></w:pPr><w:rPr><w:b/><w:sz w:val="24"/><w:szCs w:val="24"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading6"><w:name w:val="heading 6"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="60"/><w:outlineLvl w:val="5"/></w:pPr><w:rPr><w:b/><w:color w:val="595959"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Quote"><w:name w:val="Quote"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:pBdr><w:left w:val="single" w:sz="18" w:space="8" w:color="D0D7DE"/></w:pBdr><w:ind w:left="360"/></w:pPr><w:rPr><w:color w:val="595959"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="ListParagraph"><w:name w:val="List Paragraph"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="60"/><w:contextualSpacing/></w:pPr></w:style>
+<w:style w:type="paragraph" w:customStyle="1" w:styleId="Code"><w:name w:val="Code"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:shd w:val="clear" w:color="auto" w:fill="F6F8FA"/><w:spacing w:after="120" w:line="240" w:lineRule="auto"/></w:pPr><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/><w:sz w:val="19"/><w:szCs w:val="19"/></w:rPr></w:style>
+<w:style w:type="character" w:customStyle="1" w:styleId="CodeChar"><w:name w:val="Code Char"/><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/><w:shd w:val="clear" w:color="auto" w:fill="F0F0F0"/></w:rPr></w:style>
+<w:style w:type="character" w:styleId="Hyperlink"><w:name w:val="Hyperlink"/><w:rPr><w:color w:val="0563C1"/><w:u w:val="single"/></w:rPr></w:style>
+<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:left w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:bottom w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:right w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:insideH w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:insideV w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/></w:tblBorders><w:tblCellMar><w:left w:w="108" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>
+</w:styles>
+"#;
+
+// 列表编号定义: (是否有序, 起始值, 层级)
+struct Numbering(Vec<(bool, u64, usize)>);
+
+impl Numbering {
+    fn xml(&self) -> String {
+        let levels = |ordered: bool| -> String {
+            (0..9).map(|level| {
+                let (format, text) = if ordered {
+                    ("decimal", format!("%{}.", level + 1))
+                } else {
+                    ("bullet", ["•", "◦", "▪"][level % 3].to_string())
+                };
+                format!(
+                    "<w:lvl w:ilvl=\"{level}\"><w:start w:val=\"1\"/><w:numFmt w:val=\"{format}\"/><w:lvlText w:val=\"{text}\"/>\
+                     <w:lvlJc w:val=\"left\"/><w:pPr><w:ind w:left=\"{}\" w:hanging=\"360\"/></w:pPr></w:lvl>",
+                    LIST_INDENT_TWIPS * (level as u32 + 1),
+                )
+            }).collect()
+        };
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:numbering xmlns:w=\"{MAIN_NS}\">\
+             <w:abstractNum w:abstractNumId=\"0\"><w:multiLevelType w:val=\"hybridMultilevel\"/>{}</w:abstractNum>\
+             <w:abstractNum w:abstractNumId=\"1\"><w:multiLevelType w:val=\"hybridMultilevel\"/>{}</w:abstractNum>",
+            levels(false), levels(true),
+        );
+        // 每个有序列表单独编号, 从列表的起始值开始
+        for (idx, (ordered, start, level)) in self.0.iter().enumerate() {
+            xml.push_str(&format!("<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"{}\"/>", idx + 1, *ordered as u8));
+            if *ordered {
+                xml.push_str(&format!("<w:lvlOverride w:ilvl=\"{}\"><w:startOverride w:val=\"{}\"/></w:lvlOverride>", level, start));
+            }
+            xml.push_str("</w:num>");
+        }
+        xml.push_str("</w:numbering>");
+        xml
+    }
+}
+
+struct Writer<'a> {
+    document: &'a Document,
+    body: String,
+    links: Vec<String>,     // 外部链接的关系
+    numbering: Numbering,
+    drawings: usize,
+}
+
+impl Writer<'_> {
+    // 关系编号: rId1 样式, rId2 编号, 之后依次为图片和链接
+    fn picture_rel(index: usize) -> String {
+        format!("rId{}", index + 3)
+    }
+
+    fn link_rel(&mut self, url: &str) -> String {
+        let index = self.links.iter().position(|l| l == url).unwrap_or_else(|| {
+            self.links.push(url.to_string());
+            self.links.len() - 1
+        });
+        format!("rId{}", self.document.pictures.len() + index + 3)
+    }
+
+    fn blocks(&mut self, blocks: &[Block], level: Option<usize>) {
+        for block in blocks {
+            self.block(block, level);
+        }
+    }
+
+    // level 为所在列表项的层级, 列表项中的后续段落缩进对齐
+    fn block(&mut self, block: &Block, level: Option<usize>) {
+        let indent = level.map(|level| format!("<w:ind w:left=\"{}\"/>", LIST_INDENT_TWIPS * (level as u32 + 1)))
+            .unwrap_or_default();
+        match block {
+            Block::Paragraph(style, inlines) => {
+                let style = match style {
+                    ParagraphStyle::Body => String::new(),
+                    ParagraphStyle::Heading(level) => format!("<w:pStyle w:val=\"Heading{}\"/>", level),
+                    ParagraphStyle::Quote => "<w:pStyle w:val=\"Quote\"/>".to_string(),
+                };
+                self.paragraph(&format!("{}{}", style, indent), inlines);
+            }
+            Block::Code(lines) => {
+                self.body.push_str(&format!("<w:p><w:pPr><w:pStyle w:val=\"Code\"/>{}</w:pPr>", indent));
+                for (idx, line) in lines.iter().enumerate() {
+                    if idx > 0 {
+                        self.body.push_str("<w:r><w:br/></w:r>");
+                    }
+                    self.text_run(line, RunStyle::default(), false);
+                }
+                self.body.push_str("</w:p>");
+            }
+            Block::List { start, items } => {
+                let level = level.map_or(0, |level| level + 1);
+                self.numbering.0.push((start.is_some(), start.unwrap_or(1), level));
+                let num = self.numbering.0.len();
+                let numbered = format!("<w:pStyle w:val=\"ListParagraph\"/><w:numPr><w:ilvl w:val=\"{level}\"/><w:numId w:val=\"{num}\"/></w:numPr>");
+                for item in items {
+                    match item.split_first() {
+                        Some((Block::Paragraph(_, inlines), rest)) => {
+                            self.paragraph(&numbered, inlines);
+                            self.blocks(rest, Some(level));
+                        }
+                        // 列表项不以段落开头时单独输出编号
+                        _ => {
+                            self.paragraph(&numbered, &[]);
+                            self.blocks(item, Some(level));
+                        }
+                    }
+                }
+            }
+            Block::Table { aligns, head, rows } => self.table(aligns, *head, rows),
+            Block::Rule => self.body.push_str(
+                "<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"BFBFBF\"/></w:pBdr></w:pPr></w:p>"
+            ),
+        }
+    }
+
+    fn paragraph(&mut self, properties: &str, inlines: &[Inline]) {
+        self.body.push_str("<w:p>");
+        if !properties.is_empty() {
+            self.body.push_str(&format!("<w:pPr>{}</w:pPr>", properties));
+        }
+        self.inlines(inlines, false);
+        self.body.push_str("</w:p>");
+    }
+
+    fn inlines(&mut self, inlines: &[Inline], bold: bool) {
+        for inline in inlines {
+            match inline {
+                Inline::Text { text, style, link } => {
+                    let style = RunStyle { bold: style.bold || bold, ..*style };
+                    match link {
+                        Some(url) => {
+                            let rel = self.link_rel(url);
+                            self.body.push_str(&format!("<w:hyperlink r:id=\"{}\" w:history=\"1\">", rel));
+                            self.text_run(text, style, true);
+                            self.body.push_str("</w:hyperlink>");
+                        }
+                        None => self.text_run(text, style, false),
+                    }
+                }
+                Inline::Break => self.body.push_str("<w:r><w:br/></w:r>"),
+                Inline::Image { index, alt } => self.drawing(*index, alt),
+            }
+        }
+    }
+
+    fn text_run(&mut self, text: &str, style: RunStyle, link: bool) {
+        let mut properties = String::new();
+        if link {
+            properties.push_str("<w:rStyle w:val=\"Hyperlink\"/>");
+        } else if style.code {
+            properties.push_str("<w:rStyle w:val=\"CodeChar\"/>");
+        }
+        if style.bold {
+            properties.push_str("<w:b/>");
+        }
+        if style.italic {
+            properties.push_str("<w:i/>");
+        }
+        if style.strike {
+            properties.push_str("<w:strike/>");
+        }
+        self.body.push_str("<w:r>");
+        if !properties.is_empty() {
+            self.body.push_str(&format!("<w:rPr>{}</w:rPr>", properties));
+        }
+        for (idx, part) in text.split('\t').enumerate() {
+            if idx > 0 {
+                self.body.push_str("<w:tab/>");
+            }
+            if !part.is_empty() {
+                self.body.push_str(&format!("<w:t xml:space=\"preserve\">{}</w:t>", escape(part)));
+            }
+        }
+        self.body.push_str("</w:r>");
+    }
+
+    fn drawing(&mut self, index: usize, alt: &str) {
+        let picture = &self.document.pictures[index];
+        let (width, height) = picture.size_inches(TEXT_WIDTH_TWIPS as f32 / 1440.0);
+        let (cx, cy) = ((width * EMU_PER_INCH) as u64, (height * EMU_PER_INCH) as u64);
+        self.drawings += 1;
+        let id = self.drawings;
+        self.body.push_str(&format!(
+            "<w:r><w:drawing><wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\"><wp:extent cx=\"{cx}\" cy=\"{cy}\"/>\
+             <wp:docPr id=\"{id}\" name=\"Picture {id}\" descr=\"{alt}\"/>\
+             <a:graphic><a:graphicData uri=\"http://schemas.openxmlformats.org/drawingml/2006/picture\"><pic:pic>\
+             <pic:nvPicPr><pic:cNvPr id=\"{id}\" name=\"image{index}.{ext}\"/><pic:cNvPicPr/></pic:nvPicPr>\
+             <pic:blipFill><a:blip r:embed=\"{rel}\"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>\
+             <pic:spPr><a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm><a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr>\
+             </pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>",
+            alt = escape(alt), ext = picture.extension, rel = Self::picture_rel(index),
+        ));
+    }
+
+    fn table(&mut self, aligns: &[Alignment], head: usize, rows: &[Vec<Vec<Inline>>]) {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let width = TEXT_WIDTH_TWIPS / columns as u32;
+        self.body.push_str("<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/><w:tblW w:w=\"0\" w:type=\"auto\"/></w:tblPr><w:tblGrid>");
+        self.body.push_str(&format!("<w:gridCol w:w=\"{}\"/>", width).repeat(columns));
+        self.body.push_str("</w:tblGrid>");
+        for (idx, row) in rows.iter().enumerate() {
+            // 表头在每页重复
+            self.body.push_str(if idx < head { "<w:tr><w:trPr><w:tblHeader/></w:trPr>" } else { "<w:tr>" });
+            for column in 0..columns {
+                let align = match aligns.get(column) {
+                    Some(Alignment::Center) => "<w:jc w:val=\"center\"/>",
+                    Some(Alignment::Right) => "<w:jc w:val=\"right\"/>",
+                    _ => "",
+                };
+                let shading = if idx < head { "<w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"EEF0F4\"/>" } else { "" };
+                self.body.push_str(&format!(
+                    "<w:tc><w:tcPr><w:tcW w:w=\"{}\" w:type=\"dxa\"/>{}</w:tcPr><w:p><w:pPr><w:spacing w:after=\"0\"/>{}</w:pPr>",
+                    width, shading, align,
+                ));
+                if let Some(cell) = row.get(column) {
+                    self.inlines(cell, idx < head);
+                }
+                self.body.push_str("</w:p></w:tc>");
+            }
+            self.body.push_str("</w:tr>");
+        }
+        self.body.push_str("</w:tbl>");
+    }
+}
+
+// 生成 .docx (Office Open XML)
+pub fn write(note: &ExportNote, properties: &Properties, document: &Document, output: impl Write + std::io::Seek) -> anyhow::Result<()> {
+    let mut writer = Writer {
+        document,
+        body: String::new(),
+        links: Vec::new(),
+        numbering: Numbering(Vec::new()),
+        drawings: 0,
+    };
+    writer.paragraph("<w:pStyle w:val=\"Title\"/>", &[Inline::Text { text: note.title().to_string(), style: RunStyle::default(), link: None }]);
+    if !properties.subtitle.is_empty() {
+        writer.paragraph("<w:pStyle w:val=\"Subtitle\"/>", &[Inline::Text { text: properties.subtitle.clone(), style: RunStyle::default(), link: None }]);
+    }
+    writer.blocks(&document.blocks, None);
+
+    let mut zip = ZipWriter::new(output);
+    let options = SimpleFileOptions::default();
+    let mut content_types = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+         <Default Extension=\"png\" ContentType=\"image/png\"/>\
+         <Default Extension=\"jpeg\" ContentType=\"image/jpeg\"/>",
+    );
+    for (part, kind) in [
+        ("/word/document.xml", "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"),
+        ("/word/styles.xml", "application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"),
+        ("/word/numbering.xml", "application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml"),
+        ("/docProps/core.xml", "application/vnd.openxmlformats-package.core-properties+xml"),
+        ("/docProps/app.xml", "application/vnd.openxmlformats-officedocument.extended-properties+xml"),
+    ] {
+        content_types.push_str(&format!("<Override PartName=\"{}\" ContentType=\"{}\"/>", part, kind));
+    }
+    content_types.push_str("</Types>");
+    zip.start_file("[Content_Types].xml", options)?;
+    zip.write_all(content_types.as_bytes())?;
+
+    zip.start_file("_rels/.rels", options)?;
+    zip.write_all(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+         <Relationship Id=\"rId1\" Type=\"{REL_TYPE}/officeDocument\" Target=\"word/document.xml\"/>\
+         <Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" Target=\"docProps/core.xml\"/>\
+         <Relationship Id=\"rId3\" Type=\"{REL_TYPE}/extended-properties\" Target=\"docProps/app.xml\"/>\
+         </Relationships>",
+    ).as_bytes())?;
+
+    let mut rels = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+         <Relationship Id=\"rId1\" Type=\"{REL_TYPE}/styles\" Target=\"styles.xml\"/>\
+         <Relationship Id=\"rId2\" Type=\"{REL_TYPE}/numbering\" Target=\"numbering.xml\"/>",
+    );
+    for (index, picture) in document.pictures.iter().enumerate() {
+        rels.push_str(&format!(
+            "<Relationship Id=\"{}\" Type=\"{REL_TYPE}/image\" Target=\"media/image{}.{}\"/>",
+            Writer::picture_rel(index), index + 1, picture.extension,
+        ));
+    }
+    for (index, url) in writer.links.iter().enumerate() {
+        rels.push_str(&format!(
+            "<Relationship Id=\"rId{}\" Type=\"{REL_TYPE}/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>",
+            document.pictures.len() + index + 3, escape(url),
+        ));
+    }
+    rels.push_str("</Relationships>");
+    zip.start_file("word/_rels/document.xml.rels", options)?;
+    zip.write_all(rels.as_bytes())?;
+
+    zip.start_file("word/document.xml", options)?;
+    zip.write_all(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <w:document xmlns:w=\"{MAIN_NS}\" xmlns:r=\"{REL_NS}\" \
+         xmlns:wp=\"http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing\" \
+         xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" \
+         xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\"><w:body>{}\
+         <w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/>\
+         <w:pgMar w:top=\"1440\" w:right=\"1440\" w:bottom=\"1440\" w:left=\"1440\" w:header=\"708\" w:footer=\"708\" w:gutter=\"0\"/>\
+         </w:sectPr></w:body></w:document>",
+        writer.body,
+    ).as_bytes())?;
+    zip.start_file("word/styles.xml", options)?;
+    zip.write_all(STYLES.as_bytes())?;
+    zip.start_file("word/numbering.xml", options)?;
+    zip.write_all(writer.numbering.xml().as_bytes())?;
+    for (index, picture) in document.pictures.iter().enumerate() {
+        zip.start_file(format!("word/media/image{}.{}", index + 1, picture.extension), options)?;
+        zip.write_all(&picture.data)?;
+    }
+
+    // 文档属性: 标题、标签(关键词)、创建和修改时间
+    let mut core = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:dcterms=\"http://purl.org/dc/terms/\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\"><dc:title>{}</dc:title><dc:creator>{}</dc:creator>",
+        escape(note.title()), APP_NAME,
+    );
+    if !properties.tags.is_empty() {
+        core.push_str(&format!("<cp:keywords>{}</cp:keywords>", escape(&properties.tags.join(", "))));
+    }
+    if let Some(created) = &properties.created {
+        core.push_str(&format!("<dcterms:created xsi:type=\"dcterms:W3CDTF\">{}</dcterms:created>", created));
+    }
+    if let Some(modified) = &properties.modified {
+        core.push_str(&format!("<dcterms:modified xsi:type=\"dcterms:W3CDTF\">{}</dcterms:modified>", modified));
+    }
+    core.push_str("</cp:coreProperties>");
+    zip.start_file("docProps/core.xml", options)?;
+    zip.write_all(core.as_bytes())?;
+    zip.start_file("docProps/app.xml", options)?;
+    zip.write_all(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Properties xmlns=\"http://schemas.openxmlformats.org/officeDocument/2006/extended-properties\"><Application>{}</Application></Properties>",
+        APP_NAME,
+    ).as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+mod document;
+mod docx;
+mod odt;
+
+use std::fs::File;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use crate::data::ExportNote;
+use document::Document;
+
+const APP_NAME: &str = "Cloud Notepad";
+
+// 文档属性
+struct Properties {
+    subtitle: String,           // 标题下方显示的更新时间和标签
+    tags: Vec<String>,
+    created: Option<String>,    // W3C日期格式
+    modified: Option<String>,
+}
+
+impl Properties {
+    fn new(note: &ExportNote) -> Self {
+        let mut tags: Vec<String> = note.tags().iter().cloned().collect();
+        tags.sort();
+        let mut subtitle: Vec<String> = note.updated
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .into_iter()
+            .collect();
+        subtitle.extend(tags.iter().map(|t| format!("#{}", t)));
+        let w3c = |t: DateTime<Utc>| t.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        Self {
+            subtitle: subtitle.join("  "),
+            tags,
+            created: note.created.map(w3c),
+            modified: note.updated.map(w3c),
+        }
+    }
+}
+
+/// Word文档导出(.docx)
+pub(in crate::io) struct DocxHandler;
+
+impl DocxHandler {
+    pub fn export_note(&self, note: &ExportNote, output_path: &Path) -> anyhow::Result<()> {
+        let document = Document::parse(note.content());
+        docx::write(note, &Properties::new(note), &document, File::create(output_path)?)
+    }
+}
+
+/// OpenDocument文本导出(.odt)
+pub(in crate::io) struct OdtHandler;
+
+impl OdtHandler {
+    pub fn export_note(&self, note: &ExportNote, output_path: &Path) -> anyhow::Result<()> {
+        let document = Document::parse(note.content());
+        odt::write(note, &Properties::new(note), &document, File::create(output_path)?)
+    }
+}
+
+// XML转义, 并去掉XML中不允许的控制字符
+fn escape(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::io::Read;
+    use zip::ZipArchive;
+    use super::*;
+
+    fn read_entry(path: &Path, name: &str) -> String {
+        let mut archive = ZipArchive::new(File::open(path).unwrap()).unwrap();
+        let mut content = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn test_export_office() {
+        let note = ExportNote::new(
+            Some("n1".to_string()),
+            "Report <Q1>".to_string(),
+            HashSet::from(["work".to_string(), "报告".to_string()]),
+            "# Summary\n\n**Bold** and `code` with [link](https://example.com).\n\n\
+             3. third\n4. fourth\n   - nested\n\n```\nfn main() {\n    x\n}\n```\n\n| a | b |\n|---|--:|\n| 1 | 2 |\n".to_string(),
+            Some(Utc::now()),
+            Some(Utc::now()),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let docx = dir.path().join("report.docx");
+        DocxHandler.export_note(&note, &docx).unwrap();
+        let body = read_entry(&docx, "word/document.xml");
+        assert!(body.contains("<w:pStyle w:val=\"Heading1\"/>") && body.contains("Report &lt;Q1&gt;"));
+        assert!(body.contains("<w:ilvl w:val=\"1\"/>") && body.contains("<w:tblHeader/>"));
+        assert!(read_entry(&docx, "word/numbering.xml").contains("<w:startOverride w:val=\"3\"/>"));
+        assert!(read_entry(&docx, "word/_rels/document.xml.rels").contains("Target=\"https://example.com\" TargetMode=\"External\""));
+        assert!(read_entry(&docx, "docProps/core.xml").contains("<cp:keywords>work, 报告</cp:keywords>"));
+
+        let odt = dir.path().join("report.odt");
+        OdtHandler.export_note(&note, &odt).unwrap();
+        assert_eq!(read_entry(&odt, "mimetype"), "application/vnd.oasis.opendocument.text");
+        let content = read_entry(&odt, "content.xml");
+        assert!(content.contains("<text:h text:style-name=\"Heading_20_1\" text:outline-level=\"1\">Summary</text:h>"));
+        assert!(content.contains("<text:list-item text:start-value=\"3\">") && content.contains("<table:table-header-rows>"));
+        assert!(content.contains("<text:s text:c=\"3\"/>x"));
+        assert!(read_entry(&odt, "meta.xml").contains("<meta:keyword>报告</meta:keyword>"));
+    }
+}
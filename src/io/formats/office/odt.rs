@@ -0,0 +1,369 @@
+use std::io::Write;
+use pulldown_cmark::Alignment;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+use crate::data::ExportNote;
+use super::{APP_NAME, Properties, escape, document::{Block, Document, Inline, ParagraphStyle, RunStyle}};
+
+const MIME_TYPE: &str = "application/vnd.oasis.opendocument.text";
+const NAMESPACES: &str = "xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" \
+    xmlns:style=\"urn:oasis:names:tc:opendocument:xmlns:style:1.0\" \
+    xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\" \
+    xmlns:table=\"urn:oasis:names:tc:opendocument:xmlns:table:1.0\" \
+    xmlns:draw=\"urn:oasis:names:tc:opendocument:xmlns:drawing:1.0\" \
+    xmlns:fo=\"urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0\" \
+    xmlns:svg=\"urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0\" \
+    xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+    xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+    xmlns:meta=\"urn:oasis:names:tc:opendocument:xmlns:meta:1.0\"";
+// A4 纸, 2cm 页边距的版心宽度
+const TEXT_WIDTH_CM: f32 = 17.0;
+const LIST_INDENT_CM: f32 = 0.635;
+
+const STYLES: &str = r##"<office:styles>
+<style:default-style style:family="paragraph"><style:paragraph-properties fo:margin-bottom="0.2cm" fo:line-height="120%"/><style:text-properties fo:font-size="11pt" fo:font-family="Calibri" style:font-family-asian="'Microsoft YaHei'" fo:language="en" fo:country="US" style:language-asian="zh" style:country-asian="CN"/></style:default-style>
+<style:style style:name="Standard" style:family="paragraph" style:class="text"/>
+<style:style style:name="Text_20_body" style:display-name="Text body" style:family="paragraph" style:parent-style-name="Standard" style:class="text"/>
+<style:style style:name="Title" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Text_20_body" style:class="chapter"><style:paragraph-properties fo:margin-bottom="0.1cm"/><style:text-properties fo:font-size="24pt" fo:font-weight="bold" style:font-weight-asian="bold"/></style:style>
+<style:style style:name="Subtitle" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Text_20_body" style:class="chapter"><style:paragraph-properties fo:margin-bottom="0.4cm"/><style:text-properties fo:font-size="10pt" fo:color="#737373"/></style:style>
+<style:style style:name="Heading" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Text_20_body" style:class="text"><style:paragraph-properties fo:margin-top="0.5cm" fo:margin-bottom="0.2cm" fo:keep-with-next="always"/><style:text-properties fo:font-weight="bold" style:font-weight-asian="bold"/></style:style>
+<style:style style:name="Heading_20_1" style:display-name="Heading 1" style:family="paragraph" style:parent-style-name="Heading" style:default-outline-level="1" style:class="text"><style:text-properties fo:font-size="20pt"/></style:style>
+<style:style style:name="Heading_20_2" style:display-name="Heading 2" style:family="paragraph" style:parent-style-name="Heading" style:default-outline-level="2" style:class="text"><style:text-properties fo:font-size="17pt"/></style:style>
+<style:style style:name="Heading_20_3" style:display-name="Heading 3" style:family="paragraph" style:parent-style-name="Heading" style:default-outline-level="3" style:class="text"><style:text-properties fo:font-size="15pt"/></style:style>
+<style:style style:name="Heading_20_4" style:display-name="Heading 4" style:family="paragraph" style:parent-style-name="Heading" style:default-outline-level="4" style:class="text"><style:text-properties fo:font-size="13pt"/></style:style>
+<style:style style:name="Heading_20_5" style:display-name="Heading 5" style:family="paragraph" style:parent-style-name="Heading" style:default-outline-level="5" style:class="text"><style:text-properties fo:font-size="12pt"/></style:style>
+<style:style style:name="Heading_20_6" style:display-name="Heading 6" style:family="paragraph" style:parent-style-name="Heading" style:default-outline-level="6" style:class="text"><style:text-properties fo:color="#595959"/></style:style>
+<style:style style:name="Quotations" style:family="paragraph" style:parent-style-name="Standard" style:class="html"><style:paragraph-properties fo:margin-left="0.6cm" fo:padding-left="0.3cm" fo:border-left="2.25pt solid #d0d7de"/><style:text-properties fo:color="#595959"/></style:style>
+<style:style style:name="Preformatted_20_Text" style:display-name="Preformatted Text" style:family="paragraph" style:parent-style-name="Standard" style:class="html"><style:paragraph-properties fo:background-color="#f6f8fa" fo:padding="0.15cm" fo:line-height="100%"/><style:text-properties fo:font-size="9.5pt" fo:font-family="Consolas" style:font-family-generic="modern" style:font-pitch="fixed"/></style:style>
+<style:style style:name="Table_20_Contents" style:display-name="Table Contents" style:family="paragraph" style:parent-style-name="Standard" style:class="extra"><style:paragraph-properties fo:margin-bottom="0cm"/></style:style>
+<style:style style:name="Table_20_Heading" style:display-name="Table Heading" style:family="paragraph" style:parent-style-name="Table_20_Contents" style:class="extra"><style:text-properties fo:font-weight="bold" style:font-weight-asian="bold"/></style:style>
+<style:style style:name="Horizontal_20_Line" style:display-name="Horizontal Line" style:family="paragraph" style:parent-style-name="Standard" style:class="html"><style:paragraph-properties fo:padding-bottom="0.05cm" fo:border-bottom="0.5pt solid #bfbfbf"/><style:text-properties fo:font-size="6pt"/></style:style>
+<style:style style:name="Source_20_Text" style:display-name="Source Text" style:family="text"><style:text-properties fo:font-family="Consolas" style:font-family-generic="modern" style:font-pitch="fixed" fo:background-color="#f0f0f0"/></style:style>
+<style:style style:name="Internet_20_link" style:display-name="Internet link" style:family="text"><style:text-properties fo:color="#0563c1" style:text-underline-style="solid" style:text-underline-width="auto" style:text-underline-color="font-color"/></style:style>
+LIST_STYLES</office:styles>"##;
+
+// 编号和项目符号列表样式(10级)
+fn list_styles() -> String {
+    let levels = |ordered: bool| -> String {
+        (1..=10).map(|level| {
+            let indent = LIST_INDENT_CM * level as f32;
+            let properties = format!(
+                "<style:list-level-properties text:list-level-position-and-space-mode=\"label-alignment\">\
+                 <style:list-level-label-alignment text:label-followed-by=\"listtab\" text:list-tab-stop-position=\"{indent:.3}cm\" \
+                 fo:text-indent=\"-{LIST_INDENT_CM}cm\" fo:margin-left=\"{indent:.3}cm\"/></style:list-level-properties>",
+            );
+            if ordered {
+                format!("<text:list-level-style-number text:level=\"{level}\" style:num-suffix=\".\" style:num-format=\"1\">{properties}</text:list-level-style-number>")
+            } else {
+                let bullet = ["•", "◦", "▪"][(level - 1) % 3];
+                format!("<text:list-level-style-bullet text:level=\"{level}\" text:bullet-char=\"{bullet}\">{properties}</text:list-level-style-bullet>")
+            }
+        }).collect()
+    };
+    format!(
+        "<text:list-style style:name=\"Bullet\">{}</text:list-style><text:list-style style:name=\"Numbering\">{}</text:list-style>\n",
+        levels(false), levels(true),
+    )
+}
+
+// 自动样式: 粗体/斜体/删除线/代码的组合和表格单元格
+fn automatic_styles() -> String {
+    let mut styles = String::from("<office:automatic-styles>");
+    for bits in 1..16u8 {
+        let style = RunStyle { bold: bits & 1 != 0, italic: bits & 2 != 0, strike: bits & 4 != 0, code: bits & 8 != 0 };
+        let parent = if style.code { " style:parent-style-name=\"Source_20_Text\"" } else { "" };
+        let mut properties = String::new();
+        if style.bold {
+            properties.push_str(" fo:font-weight=\"bold\" style:font-weight-asian=\"bold\"");
+        }
+        if style.italic {
+            properties.push_str(" fo:font-style=\"italic\" style:font-style-asian=\"italic\"");
+        }
+        if style.strike {
+            properties.push_str(" style:text-line-through-style=\"solid\"");
+        }
+        styles.push_str(&format!(
+            "<style:style style:name=\"T{bits}\" style:family=\"text\"{parent}><style:text-properties{properties}/></style:style>"
+        ));
+    }
+    // 单元格段落的对齐方式
+    for (parent, prefix) in [("Table_20_Contents", "TC"), ("Table_20_Heading", "TH")] {
+        for align in ["start", "center", "end"] {
+            styles.push_str(&format!(
+                "<style:style style:name=\"{prefix}-{align}\" style:family=\"paragraph\" style:parent-style-name=\"{parent}\">\
+                 <style:paragraph-properties fo:text-align=\"{align}\"/></style:style>"
+            ));
+        }
+    }
+    styles.push_str(
+        "<style:style style:name=\"Cell\" style:family=\"table-cell\"><style:table-cell-properties fo:padding=\"0.1cm\" fo:border=\"0.5pt solid #bfbfbf\"/></style:style>\
+         <style:style style:name=\"HeadCell\" style:family=\"table-cell\"><style:table-cell-properties fo:padding=\"0.1cm\" fo:border=\"0.5pt solid #bfbfbf\" fo:background-color=\"#eef0f4\"/></style:style>\
+         <style:style style:name=\"Image\" style:family=\"graphic\"><style:graphic-properties style:vertical-pos=\"top\" style:vertical-rel=\"baseline\"/></style:style>\
+         </office:automatic-styles>"
+    );
+    styles
+}
+
+struct Writer<'a> {
+    document: &'a Document,
+    body: String,
+    tables: usize,
+    frames: usize,
+}
+
+impl Writer<'_> {
+    fn blocks(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            self.block(block);
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        match block {
+            Block::Paragraph(ParagraphStyle::Heading(level), inlines) => {
+                self.body.push_str(&format!("<text:h text:style-name=\"Heading_20_{level}\" text:outline-level=\"{level}\">"));
+                self.inlines(inlines, false);
+                self.body.push_str("</text:h>");
+            }
+            Block::Paragraph(style, inlines) => {
+                let style = if *style == ParagraphStyle::Quote { "Quotations" } else { "Text_20_body" };
+                self.paragraph(style, inlines);
+            }
+            Block::Code(lines) => {
+                self.body.push_str("<text:p text:style-name=\"Preformatted_20_Text\">");
+                for (idx, line) in lines.iter().enumerate() {
+                    if idx > 0 {
+                        self.body.push_str("<text:line-break/>");
+                    }
+                    self.text(line);
+                }
+                self.body.push_str("</text:p>");
+            }
+            Block::List { start, items } => {
+                let style = if start.is_some() { "Numbering" } else { "Bullet" };
+                self.body.push_str(&format!("<text:list text:style-name=\"{}\">", style));
+                for (idx, item) in items.iter().enumerate() {
+                    match start {
+                        Some(start) if idx == 0 && *start != 1 => {
+                            self.body.push_str(&format!("<text:list-item text:start-value=\"{}\">", start));
+                        }
+                        _ => self.body.push_str("<text:list-item>"),
+                    }
+                    if item.is_empty() {
+                        self.body.push_str("<text:p/>");
+                    }
+                    for block in item {
+                        match block {
+                            // 列表项中不能包含表格, 每行输出为一个段落
+                            Block::Table { rows, .. } => {
+                                for row in rows {
+                                    self.body.push_str("<text:p text:style-name=\"Table_20_Contents\">");
+                                    for (idx, cell) in row.iter().enumerate() {
+                                        if idx > 0 {
+                                            self.body.push_str("<text:tab/>");
+                                        }
+                                        self.inlines(cell, false);
+                                    }
+                                    self.body.push_str("</text:p>");
+                                }
+                            }
+                            _ => self.block(block),
+                        }
+                    }
+                    self.body.push_str("</text:list-item>");
+                }
+                self.body.push_str("</text:list>");
+            }
+            Block::Table { aligns, head, rows } => self.table(aligns, *head, rows),
+            Block::Rule => self.body.push_str("<text:p text:style-name=\"Horizontal_20_Line\"/>"),
+        }
+    }
+
+    fn paragraph(&mut self, style: &str, inlines: &[Inline]) {
+        self.body.push_str(&format!("<text:p text:style-name=\"{}\">", style));
+        self.inlines(inlines, false);
+        self.body.push_str("</text:p>");
+    }
+
+    fn inlines(&mut self, inlines: &[Inline], bold: bool) {
+        for inline in inlines {
+            match inline {
+                Inline::Text { text, style, link } => {
+                    if let Some(url) = link {
+                        self.body.push_str(&format!(
+                            "<text:a xlink:type=\"simple\" xlink:href=\"{}\" text:style-name=\"Internet_20_link\">",
+                            escape(url),
+                        ));
+                    }
+                    let bits = RunStyle { bold: style.bold || bold, ..*style }.bits();
+                    if bits != 0 {
+                        self.body.push_str(&format!("<text:span text:style-name=\"T{}\">", bits));
+                    }
+                    self.text(text);
+                    if bits != 0 {
+                        self.body.push_str("</text:span>");
+                    }
+                    if link.is_some() {
+                        self.body.push_str("</text:a>");
+                    }
+                }
+                Inline::Break => self.body.push_str("<text:line-break/>"),
+                Inline::Image { index, alt } => self.frame(*index, alt),
+            }
+        }
+    }
+
+    // ODF会合并连续的空白, 空格和制表符需要单独标记
+    fn text(&mut self, text: &str) {
+        let mut spaces = 0;
+        let mut chunk = String::new();
+        let flush_spaces = |body: &mut String, spaces: &mut usize| {
+            match *spaces {
+                0 => {}
+                1 => body.push(' '),
+                n => body.push_str(&format!(" <text:s text:c=\"{}\"/>", n - 1)),
+            }
+            *spaces = 0;
+        };
+        for ch in text.chars() {
+            match ch {
+                ' ' => {
+                    self.body.push_str(&escape(&std::mem::take(&mut chunk)));
+                    spaces += 1;
+                }
+                '\t' => {
+                    self.body.push_str(&escape(&std::mem::take(&mut chunk)));
+                    flush_spaces(&mut self.body, &mut spaces);
+                    self.body.push_str("<text:tab/>");
+                }
+                _ => {
+                    flush_spaces(&mut self.body, &mut spaces);
+                    chunk.push(ch);
+                }
+            }
+        }
+        self.body.push_str(&escape(&chunk));
+        flush_spaces(&mut self.body, &mut spaces);
+    }
+
+    fn frame(&mut self, index: usize, alt: &str) {
+        let picture = &self.document.pictures[index];
+        let (width, height) = picture.size_inches(TEXT_WIDTH_CM / 2.54);
+        self.frames += 1;
+        self.body.push_str(&format!(
+            "<draw:frame draw:style-name=\"Image\" draw:name=\"Image{}\" text:anchor-type=\"as-char\" svg:width=\"{:.3}cm\" svg:height=\"{:.3}cm\">\
+             <draw:image xlink:href=\"Pictures/image{}.{}\" xlink:type=\"simple\" xlink:show=\"embed\" xlink:actuate=\"onLoad\"/>\
+             <svg:desc>{}</svg:desc></draw:frame>",
+            self.frames, width * 2.54, height * 2.54, index + 1, picture.extension, escape(alt),
+        ));
+    }
+
+    fn table(&mut self, aligns: &[Alignment], head: usize, rows: &[Vec<Vec<Inline>>]) {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        self.tables += 1;
+        self.body.push_str(&format!(
+            "<table:table table:name=\"Table{}\"><table:table-column table:number-columns-repeated=\"{}\"/>",
+            self.tables, columns,
+        ));
+        for (idx, row) in rows.iter().enumerate() {
+            if idx == 0 && head > 0 {
+                self.body.push_str("<table:table-header-rows>");
+            }
+            self.body.push_str("<table:table-row>");
+            for column in 0..columns {
+                let align = match aligns.get(column) {
+                    Some(Alignment::Center) => "center",
+                    Some(Alignment::Right) => "end",
+                    _ => "start",
+                };
+                let (cell, paragraph) = if idx < head { ("HeadCell", "TH") } else { ("Cell", "TC") };
+                self.body.push_str(&format!(
+                    "<table:table-cell table:style-name=\"{cell}\" office:value-type=\"string\"><text:p text:style-name=\"{paragraph}-{align}\">",
+                ));
+                if let Some(cell) = row.get(column) {
+                    self.inlines(cell, false);
+                }
+                self.body.push_str("</text:p></table:table-cell>");
+            }
+            self.body.push_str("</table:table-row>");
+            if idx + 1 == head {
+                self.body.push_str("</table:table-header-rows>");
+            }
+        }
+        self.body.push_str("</table:table>");
+    }
+}
+
+// 生成 .odt (OpenDocument Text)
+pub fn write(note: &ExportNote, properties: &Properties, document: &Document, output: impl Write + std::io::Seek) -> anyhow::Result<()> {
+    let mut writer = Writer { document, body: String::new(), tables: 0, frames: 0 };
+    writer.body.push_str(&format!("<text:p text:style-name=\"Title\">{}</text:p>", escape(note.title())));
+    if !properties.subtitle.is_empty() {
+        writer.body.push_str(&format!("<text:p text:style-name=\"Subtitle\">{}</text:p>", escape(&properties.subtitle)));
+    }
+    writer.blocks(&document.blocks);
+
+    let mut zip = ZipWriter::new(output);
+    let options = SimpleFileOptions::default();
+    // mimetype 必须是第一个且不压缩
+    zip.start_file("mimetype", options.compression_method(CompressionMethod::Stored))?;
+    zip.write_all(MIME_TYPE.as_bytes())?;
+
+    let mut manifest = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <manifest:manifest xmlns:manifest=\"urn:oasis:names:tc:opendocument:xmlns:manifest:1.0\" manifest:version=\"1.3\">\
+         <manifest:file-entry manifest:full-path=\"/\" manifest:version=\"1.3\" manifest:media-type=\"{MIME_TYPE}\"/>\
+         <manifest:file-entry manifest:full-path=\"content.xml\" manifest:media-type=\"text/xml\"/>\
+         <manifest:file-entry manifest:full-path=\"styles.xml\" manifest:media-type=\"text/xml\"/>\
+         <manifest:file-entry manifest:full-path=\"meta.xml\" manifest:media-type=\"text/xml\"/>",
+    );
+    for (index, picture) in document.pictures.iter().enumerate() {
+        manifest.push_str(&format!(
+            "<manifest:file-entry manifest:full-path=\"Pictures/image{}.{}\" manifest:media-type=\"{}\"/>",
+            index + 1, picture.extension, picture.media_type(),
+        ));
+    }
+    manifest.push_str("</manifest:manifest>");
+    zip.start_file("META-INF/manifest.xml", options)?;
+    zip.write_all(manifest.as_bytes())?;
+
+    zip.start_file("content.xml", options)?;
+    zip.write_all(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<office:document-content {NAMESPACES} office:version=\"1.3\">{}\
+         <office:body><office:text>{}</office:text></office:body></office:document-content>",
+        automatic_styles(), writer.body,
+    ).as_bytes())?;
+    zip.start_file("styles.xml", options)?;
+    zip.write_all(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<office:document-styles {NAMESPACES} office:version=\"1.3\">{}\
+         <office:automatic-styles><style:page-layout style:name=\"Page\"><style:page-layout-properties fo:page-width=\"21cm\" fo:page-height=\"29.7cm\" \
+         fo:margin-top=\"2cm\" fo:margin-bottom=\"2cm\" fo:margin-left=\"2cm\" fo:margin-right=\"2cm\"/></style:page-layout></office:automatic-styles>\
+         <office:master-styles><style:master-page style:name=\"Standard\" style:page-layout-name=\"Page\"/></office:master-styles></office:document-styles>",
+        STYLES.replace("LIST_STYLES", &list_styles()),
+    ).as_bytes())?;
+    for (index, picture) in document.pictures.iter().enumerate() {
+        zip.start_file(format!("Pictures/image{}.{}", index + 1, picture.extension), options)?;
+        zip.write_all(&picture.data)?;
+    }
+
+    // 文档属性: 标题、标签(关键词)、创建和修改时间
+    let mut meta = format!("<meta:generator>{}</meta:generator><dc:title>{}</dc:title>", APP_NAME, escape(note.title()));
+    for tag in properties.tags.iter() {
+        meta.push_str(&format!("<meta:keyword>{}</meta:keyword>", escape(tag)));
+    }
+    if let Some(created) = &properties.created {
+        meta.push_str(&format!("<meta:creation-date>{}</meta:creation-date>", created));
+    }
+    if let Some(modified) = &properties.modified {
+        meta.push_str(&format!("<dc:date>{}</dc:date>", modified));
+    }
+    zip.start_file("meta.xml", options)?;
+    zip.write_all(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<office:document-meta {NAMESPACES} office:version=\"1.3\"><office:meta>{}</office:meta></office:document-meta>",
+        meta,
+    ).as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
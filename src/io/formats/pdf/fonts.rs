@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+use ttf_parser::{Face, GlyphId, name_id};
+use crate::utils::fonts::{HACK_REGULAR, SOURCE_HAN_SANS_BOLD, SOURCE_HAN_SANS_REGULAR, UBUNTU_LIGHT};
+
+// 字体在 FontSet 中的位置
+const REGULAR: usize = 0;
+const BOLD: usize = 1;
+const SANS: usize = 2;
+const MONO: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub mono: bool,
+}
+
+// 字符对应的字形
+pub struct Glyph {
+    pub face: usize,
+    pub id: u16,
+    pub width: f32,         // 宽度(千分之一字号)
+    pub fake_bold: bool,    // 没有粗体字形时通过描边加粗
+}
+
+pub struct FontFace {
+    pub data: &'static [u8],
+    pub index: u32,         // 字体集合(.ttc)中的位置
+    pub face: Face<'static>,
+    pub used: BTreeMap<u16, char>,  // 已使用的字形, 用于嵌入子集和复制文本
+}
+
+impl FontFace {
+    // 空的或无法解析的字体返回None
+    fn parse(data: &'static [u8]) -> Option<Self> {
+        let face = Face::parse(data, 0).ok()?;
+        Some(Self { data, index: 0, face, used: BTreeMap::new() })
+    }
+
+    // PostScript名称(只保留PDF名称中安全的字符)
+    pub fn name(&self) -> String {
+        let name: String = self.face.names()
+            .into_iter()
+            .filter(|n| n.name_id == name_id::POST_SCRIPT_NAME)
+            .find_map(|n| n.to_string())
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect();
+        if name.is_empty() { "Font".to_string() } else { name }
+    }
+
+    // CFF轮廓(思源黑体)与TrueType轮廓的嵌入方式不同
+    pub fn is_cff(&self) -> bool {
+        self.face.tables().cff.is_some()
+    }
+
+    // 字体单位转换为千分之一字号
+    pub fn scale(&self, value: i16) -> f32 {
+        value as f32 * 1000.0 / self.face.units_per_em() as f32
+    }
+
+    pub fn advance(&self, id: u16) -> f32 {
+        let advance = self.face.glyph_hor_advance(GlyphId(id)).unwrap_or(0);
+        advance as f32 * 1000.0 / self.face.units_per_em() as f32
+    }
+}
+
+/// 导出使用的字体: 思源黑体优先, 缺字时使用 egui 自带的西文字体
+pub struct FontSet {
+    pub faces: Vec<Option<FontFace>>,
+}
+
+impl FontSet {
+    pub fn load() -> anyhow::Result<Self> {
+        let faces: Vec<_> = [SOURCE_HAN_SANS_REGULAR, SOURCE_HAN_SANS_BOLD, UBUNTU_LIGHT, HACK_REGULAR]
+            .into_iter()
+            .map(FontFace::parse)
+            .collect();
+        if faces.iter().all(Option::is_none) {
+            anyhow::bail!("No usable font for PDF export");
+        }
+        Ok(Self { faces })
+    }
+
+    // 按顺序查找包含字符的字体
+    fn chain(style: TextStyle) -> &'static [usize] {
+        if style.mono {
+            &[MONO, REGULAR, SANS]
+        } else if style.bold {
+            &[BOLD, REGULAR, SANS, MONO]
+        } else {
+            &[REGULAR, SANS, MONO]
+        }
+    }
+
+    fn find(&self, style: TextStyle, ch: char) -> Option<(usize, u16)> {
+        Self::chain(style).iter().find_map(|&idx| {
+            let face = self.faces[idx].as_ref()?;
+            face.face.glyph_index(ch).map(|id| (idx, id.0))
+        })
+    }
+
+    fn lookup(&self, style: TextStyle, ch: char) -> Glyph {
+        // 所有字体都缺字时显示为 ?
+        let (face, id) = self.find(style, ch)
+            .or_else(|| self.find(style, '?'))
+            .unwrap_or_else(|| (self.faces.iter().position(Option::is_some).unwrap_or(0), 0));
+        let width = self.faces[face].as_ref().map_or(0.0, |f| f.advance(id));
+        Glyph { face, id, width, fake_bold: style.bold && face != BOLD }
+    }
+
+    // 查找字形并记录为已使用
+    pub fn glyph(&mut self, style: TextStyle, ch: char) -> Glyph {
+        let glyph = self.lookup(style, ch);
+        if let Some(face) = self.faces[glyph.face].as_mut() {
+            face.used.entry(glyph.id).or_insert(ch);
+        }
+        glyph
+    }
+
+    // 文本宽度(磅)
+    pub fn measure(&self, style: TextStyle, text: &str, size: f32) -> f32 {
+        text.chars().map(|ch| self.lookup(style, ch).width).sum::<f32>() * size / 1000.0
+    }
+}
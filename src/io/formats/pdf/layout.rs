@@ -0,0 +1,907 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme};
+use syntect::util::LinesWithEndings;
+use crate::io::PdfConfig;
+use crate::markdown::syntax::{self, SYNTAXES};
+use super::fonts::{FontSet, TextStyle};
+
+pub type Color = [f32; 3];
+
+const TEXT: Color = [0.13, 0.13, 0.13];
+const MUTED: Color = [0.45, 0.45, 0.45];
+const LINK: Color = [0.04, 0.36, 0.75];
+const RULE: Color = [0.8, 0.8, 0.8];
+const CODE_BG: Color = [0.96, 0.96, 0.97];
+const HEADER_BG: Color = [0.93, 0.94, 0.96];
+
+const MM: f32 = 72.0 / 25.4;
+const LINE_SPACING: f32 = 1.45;
+const LIST_INDENT: f32 = 18.0;
+const QUOTE_INDENT: f32 = 14.0;
+const CELL_PADDING: f32 = 4.0;
+const CODE_PADDING: f32 = 6.0;
+const HEADING_SCALE: [f32; 6] = [2.0, 1.6, 1.35, 1.2, 1.1, 1.0];
+
+static CODE_THEME: Lazy<Theme> = Lazy::new(|| syntax::theme(syntax::LIGHT_THEME));
+
+// 页面上的绘制操作(PDF坐标, 原点在左下角)
+pub enum Op {
+    Text { x: f32, y: f32, size: f32, face: usize, glyphs: Vec<u16>, color: Color, italic: bool, fake_bold: bool },
+    Rect { x: f32, y: f32, w: f32, h: f32, color: Color, stroke: Option<f32> },
+    Line { x1: f32, y1: f32, x2: f32, y2: f32, width: f32, color: Color },
+    Image { index: usize, x: f32, y: f32, w: f32, h: f32 },
+    Link { rect: [f32; 4], url: String },
+}
+
+#[derive(Default)]
+pub struct Page {
+    pub ops: Vec<Op>,
+}
+
+// 嵌入的图片: JPEG 直接嵌入, PNG 解码为像素
+pub enum Image {
+    Jpeg { data: Vec<u8>, width: u32, height: u32, components: u8 },
+    Pixels { data: Vec<u8>, alpha: Option<Vec<u8>>, width: u32, height: u32, gray: bool },
+}
+
+impl Image {
+    fn decode(bytes: Vec<u8>) -> anyhow::Result<Self> {
+        if bytes.starts_with(&[0xFF, 0xD8]) {
+            let mut decoder = jpeg_decoder::Decoder::new(std::io::Cursor::new(&bytes));
+            decoder.read_info()?;
+            let info = decoder.info().ok_or_else(|| anyhow::anyhow!("Invalid JPEG header"))?;
+            let components = match info.pixel_format {
+                jpeg_decoder::PixelFormat::L8 => 1,
+                jpeg_decoder::PixelFormat::RGB24 => 3,
+                jpeg_decoder::PixelFormat::CMYK32 => 4,
+                format => anyhow::bail!("Unsupported JPEG pixel format {:?}", format),
+            };
+            let (width, height) = (info.width as u32, info.height as u32);
+            return Ok(Image::Jpeg { data: bytes, width, height, components });
+        }
+
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        buf.truncate(info.buffer_size());
+        let (width, height) = (info.width, info.height);
+        // 透明通道拆分为单独的软遮罩
+        let split = |channels: usize| {
+            let color = buf.chunks(channels).flat_map(|px| px[..channels - 1].to_vec()).collect();
+            let alpha = buf.chunks(channels).map(|px| px[channels - 1]).collect();
+            (color, Some(alpha))
+        };
+        let (data, alpha, gray) = match info.color_type {
+            png::ColorType::Grayscale => (buf.clone(), None, true),
+            png::ColorType::GrayscaleAlpha => { let (data, alpha) = split(2); (data, alpha, true) }
+            png::ColorType::Rgb => (buf.clone(), None, false),
+            png::ColorType::Rgba => { let (data, alpha) = split(4); (data, alpha, false) }
+            png::ColorType::Indexed => anyhow::bail!("Unexpanded indexed PNG"),
+        };
+        Ok(Image::Pixels { data, alpha, width, height, gray })
+    }
+
+    fn size(&self) -> (f32, f32) {
+        match self {
+            Image::Jpeg { width, height, .. } | Image::Pixels { width, height, .. } => (*width as f32, *height as f32),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SpanStyle {
+    font: TextStyle,
+    size: f32,
+    color: Color,
+    strike: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+struct Span {
+    text: String,
+    style: SpanStyle,
+}
+
+// 折行后一行中样式相同的一段文本
+struct Piece {
+    text: String,
+    style: SpanStyle,
+    x: f32,
+    width: f32,
+}
+
+#[derive(Default)]
+struct Line {
+    pieces: Vec<Piece>,
+    width: f32,
+    size: f32,  // 行内最大字号
+}
+
+impl Line {
+    fn height(&self) -> f32 {
+        self.size * LINE_SPACING
+    }
+
+    fn append(&mut self, text: &str, style: &SpanStyle, width: f32) {
+        match self.pieces.last_mut() {
+            Some(piece) if &piece.style == style => {
+                piece.text.push_str(text);
+                piece.width += width;
+            }
+            _ => self.pieces.push(Piece { text: text.to_string(), style: style.clone(), x: self.width, width }),
+        }
+        self.width += width;
+        self.size = self.size.max(style.size);
+    }
+}
+
+enum Marker {
+    Bullet(usize),  // 嵌套层级
+    Number(u64),
+    Task(bool),
+}
+
+struct Table {
+    aligns: Vec<Alignment>,
+    rows: Vec<Vec<Vec<Span>>>,
+    head: usize,    // 表头行数
+}
+
+/// 将Markdown排版为分页的绘制操作
+pub struct Layout<'a> {
+    fonts: &'a mut FontSet,
+    size: f32,          // 正文字号
+    margin: f32,
+    left: f32,
+    right: f32,
+    top: f32,
+    bottom: f32,
+    pages: Vec<Page>,
+    current: usize,     // 正在绘制的页
+    images: Vec<Image>,
+    loaded: HashMap<PathBuf, Option<usize>>,
+    y: f32,             // 下一行的顶部位置
+    // 块和行内状态
+    spans: Vec<Span>,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    link: Option<String>,
+    heading: Option<usize>,
+    quotes: Vec<f32>,   // 引用竖线的位置
+    lists: Vec<Option<u64>>,
+    marker: Option<Marker>,
+    code: Option<(String, String)>,
+    table: Option<Table>,
+    image: Option<(String, String)>,
+}
+
+impl<'a> Layout<'a> {
+    pub fn new(fonts: &'a mut FontSet, config: &PdfConfig) -> Self {
+        let (width, height) = config.page_size.dimensions();
+        let size = config.font_size.clamp(6.0, 24.0);
+        let margin = config.margin_mm.clamp(5.0, 60.0) * MM;
+        // 页眉页脚占用页边距内侧的两行
+        let top = height - margin - if config.header { size * 2.0 } else { 0.0 };
+        let bottom = margin + if config.footer { size * 2.0 } else { 0.0 };
+        Self {
+            fonts,
+            size,
+            margin,
+            left: margin,
+            right: width - margin,
+            top,
+            bottom,
+            pages: vec![Page::default()],
+            current: 0,
+            images: Vec::new(),
+            loaded: HashMap::new(),
+            y: top,
+            spans: Vec::new(),
+            bold: 0,
+            italic: 0,
+            strike: 0,
+            link: None,
+            heading: None,
+            quotes: Vec::new(),
+            lists: Vec::new(),
+            marker: None,
+            code: None,
+            table: None,
+            image: None,
+        }
+    }
+
+    // 笔记标题和一行说明(更新时间、标签)
+    pub fn title(&mut self, title: &str, subtitle: &str) {
+        self.heading = Some(0);
+        let style = self.style();
+        self.push_text(title, style);
+        self.end_heading();
+        if !subtitle.is_empty() {
+            let style = SpanStyle { size: self.size * 0.85, color: MUTED, ..self.style() };
+            self.push_text(subtitle, style);
+            self.flush();
+            self.gap(self.size);
+        }
+    }
+
+    pub fn markdown(&mut self, text: &str) {
+        let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+        for event in Parser::new_ext(text, options) {
+            match event {
+                Event::Start(tag) => self.start(tag),
+                Event::End(tag) => self.end(tag),
+                Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                    let style = self.style();
+                    self.push_text(&text, style);
+                }
+                Event::Code(text) => {
+                    let mut style = self.style();
+                    style.font.mono = true;
+                    style.code = true;
+                    self.push_text(&text, style);
+                }
+                Event::SoftBreak => {
+                    let style = self.style();
+                    self.push_text(" ", style);
+                }
+                Event::HardBreak => {
+                    let style = self.style();
+                    self.push_text("\n", style);
+                }
+                Event::Rule => {
+                    self.flush();
+                    self.ensure(self.size);
+                    let y = self.y - self.size * 0.5;
+                    let x = self.indent();
+                    self.push(Op::Line { x1: x, y1: y, x2: self.right, y2: y, width: 0.8, color: RULE });
+                    self.y -= self.size;
+                }
+                Event::TaskListMarker(checked) => self.marker = Some(Marker::Task(checked)),
+                Event::FootnoteReference(name) => {
+                    let style = self.style();
+                    self.push_text(&format!("[{}]", name), style);
+                }
+                _ => {}
+            }
+        }
+        self.flush();
+    }
+
+    // 绘制页眉(标题)和页脚(页码), 返回所有页面和图片
+    pub fn finish(mut self, title: &str, config: &PdfConfig) -> (Vec<Page>, Vec<Image>) {
+        self.flush();
+        let (_, height) = config.page_size.dimensions();
+        let size = self.size * 0.8;
+        let style = SpanStyle {
+            font: TextStyle::default(),
+            size,
+            color: MUTED,
+            strike: false,
+            code: false,
+            link: None,
+        };
+        let count = self.pages.len();
+        for index in 0..count {
+            self.current = index;
+            if config.header {
+                // 标题过长时截断
+                let mut text = title.to_string();
+                let max = self.right - self.left;
+                if self.fonts.measure(style.font, &text, size) > max {
+                    while !text.is_empty() && self.fonts.measure(style.font, &format!("{}…", text), size) > max {
+                        text.pop();
+                    }
+                    text.push('…');
+                }
+                let baseline = height - self.margin - size;
+                self.draw_text(self.left, baseline, &text, &style);
+                let y = baseline - size * 0.5;
+                self.push(Op::Line { x1: self.left, y1: y, x2: self.right, y2: y, width: 0.5, color: RULE });
+            }
+            if config.footer {
+                let text = format!("{} / {}", index + 1, count);
+                let width = self.fonts.measure(style.font, &text, size);
+                let x = (self.left + self.right - width) / 2.0;
+                self.draw_text(x, self.margin, &text, &style);
+            }
+        }
+        (self.pages, self.images)
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.flush(),
+            Tag::Heading { level, .. } => {
+                self.flush();
+                self.gap(self.size * 0.6);
+                self.heading = Some(level as usize - 1);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush();
+                self.quotes.push(self.indent());
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let depth = self.lists.len().saturating_sub(1);
+                self.marker = Some(match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        Marker::Number(*number - 1)
+                    }
+                    _ => Marker::Bullet(depth),
+                });
+            }
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strike += 1,
+            Tag::Link { dest_url, .. } => self.link = Some(dest_url.to_string()),
+            Tag::Image { dest_url, .. } => self.image = Some((dest_url.to_string(), String::new())),
+            Tag::Table(aligns) => {
+                self.flush();
+                self.table = Some(Table { aligns, rows: Vec::new(), head: 0 });
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::TableCell => self.spans.clear(),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => {
+                self.flush();
+                self.gap(self.size * 0.6);
+            }
+            TagEnd::Heading(_) => self.end_heading(),
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.quotes.pop();
+                self.gap(self.size * 0.4);
+            }
+            TagEnd::CodeBlock => {
+                if let Some((lang, code)) = self.code.take() {
+                    self.code_block(&lang, &code);
+                }
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.gap(self.size * 0.6);
+                }
+            }
+            TagEnd::Item => {
+                self.flush();
+                self.marker = None;
+            }
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Strikethrough => self.strike = self.strike.saturating_sub(1),
+            TagEnd::Link => self.link = None,
+            TagEnd::Image => {
+                if let Some((url, alt)) = self.image.take() {
+                    self.image_block(&url, &alt);
+                }
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    table.head = table.rows.len();
+                }
+            }
+            TagEnd::TableCell => {
+                let spans = std::mem::take(&mut self.spans);
+                if let Some(row) = self.table.as_mut().and_then(|t| t.rows.last_mut()) {
+                    row.push(spans);
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.table_block(table);
+                    self.gap(self.size * 0.6);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 当前行内样式
+    fn style(&self) -> SpanStyle {
+        let size = self.heading.map_or(self.size, |level| self.size * HEADING_SCALE[level]);
+        let color = if self.link.is_some() {
+            LINK
+        } else if !self.quotes.is_empty() {
+            MUTED
+        } else {
+            TEXT
+        };
+        SpanStyle {
+            font: TextStyle { bold: self.bold > 0 || self.heading.is_some(), italic: self.italic > 0, mono: false },
+            size,
+            color,
+            strike: self.strike > 0,
+            code: false,
+            link: self.link.clone(),
+        }
+    }
+
+    fn push_text(&mut self, text: &str, style: SpanStyle) {
+        if let Some((_, alt)) = self.image.as_mut() {
+            alt.push_str(text);
+        } else if let Some((_, code)) = self.code.as_mut() {
+            code.push_str(text);
+        } else {
+            self.spans.push(Span { text: text.to_string(), style });
+        }
+    }
+
+    fn push(&mut self, op: Op) {
+        self.pages[self.current].ops.push(op);
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Page::default());
+        self.current = self.pages.len() - 1;
+        self.y = self.top;
+    }
+
+    // 剩余空间不足时换页(页首的内容不再换页)
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < self.bottom && self.y < self.top {
+            self.new_page();
+        }
+    }
+
+    // 块之间的间距, 页首不留空
+    fn gap(&mut self, height: f32) {
+        if self.y < self.top {
+            self.y -= height;
+        }
+    }
+
+    fn indent(&self) -> f32 {
+        self.left + self.lists.len() as f32 * LIST_INDENT + self.quotes.len() as f32 * QUOTE_INDENT
+    }
+
+    // 按单词、中日韩字符折行, 过长的单词按字符折行
+    fn wrap(&self, spans: &[Span], width: f32) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let mut line = Line::default();
+        for span in spans {
+            let style = &span.style;
+            for token in tokens(&span.text) {
+                if token == "\n" {
+                    line.size = line.size.max(style.size);
+                    lines.push(std::mem::take(&mut line));
+                    continue;
+                }
+                let token_width = self.fonts.measure(style.font, token, style.size);
+                let visible = self.fonts.measure(style.font, token.trim_end(), style.size);
+                if line.width + visible > width && !line.pieces.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                    if token.trim().is_empty() {
+                        continue;
+                    }
+                }
+                if visible > width {
+                    for ch in token.chars() {
+                        let ch = ch.encode_utf8(&mut [0; 4]).to_string();
+                        let ch_width = self.fonts.measure(style.font, &ch, style.size);
+                        if line.width + ch_width > width && !line.pieces.is_empty() {
+                            lines.push(std::mem::take(&mut line));
+                        }
+                        line.append(&ch, style, ch_width);
+                    }
+                    continue;
+                }
+                line.append(token, style, token_width);
+            }
+        }
+        if !line.pieces.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    // 排版缓存的行内文本
+    fn flush(&mut self) {
+        // 表格单元格的内容在表格结束时统一排版
+        if self.table.is_some() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.spans);
+        if spans.iter().all(|s| s.text.trim().is_empty()) {
+            return;
+        }
+        let x = self.indent();
+        let lines = self.wrap(&spans, self.right - x);
+        for (idx, line) in lines.iter().enumerate() {
+            let height = line.height();
+            self.ensure(height);
+            if idx == 0 && let Some(marker) = self.marker.take() {
+                self.draw_marker(marker, x, line);
+            }
+            self.draw_line(line, x, self.y);
+            self.quote_bars(height);
+            self.y -= height;
+        }
+    }
+
+    fn end_heading(&mut self) {
+        let Some(level) = self.heading else { return };
+        // 标题至少与下一行正文在同一页
+        let size = self.size * HEADING_SCALE[level];
+        self.ensure((size + self.size * 2.0) * LINE_SPACING);
+        self.flush();
+        self.heading = None;
+        if level < 2 {
+            let y = self.y - 2.0;
+            let x = self.indent();
+            self.push(Op::Line { x1: x, y1: y, x2: self.right, y2: y, width: 0.6, color: RULE });
+            self.y -= 4.0;
+        }
+        self.y -= self.size * 0.4;
+    }
+
+    fn baseline(line: &Line, top: f32) -> f32 {
+        top - line.size * (LINE_SPACING - 1.0) / 2.0 - line.size * 0.82
+    }
+
+    fn draw_line(&mut self, line: &Line, x: f32, top: f32) {
+        let baseline = Self::baseline(line, top);
+        for piece in line.pieces.iter() {
+            let style = &piece.style;
+            let px = x + piece.x;
+            if style.code {
+                let (w, h) = (piece.width + 2.0, style.size * 1.2);
+                self.push(Op::Rect { x: px - 1.0, y: baseline - style.size * 0.3, w, h, color: CODE_BG, stroke: None });
+            }
+            self.draw_text(px, baseline, &piece.text, style);
+            if style.strike {
+                let y = baseline + style.size * 0.3;
+                self.push(Op::Line { x1: px, y1: y, x2: px + piece.width, y2: y, width: style.size * 0.06, color: style.color });
+            }
+            if let Some(url) = style.link.as_ref() {
+                let y = baseline - style.size * 0.12;
+                let end = px + self.fonts.measure(style.font, piece.text.trim_end(), style.size);
+                self.push(Op::Line { x1: px, y1: y, x2: end, y2: y, width: style.size * 0.05, color: LINK });
+                // 只有外部链接可以在PDF中打开
+                if url.contains("://") && !url.starts_with(crate::io::NOTE_LINK_SCHEME) || url.starts_with("mailto:") {
+                    let rect = [px, baseline - style.size * 0.3, end, baseline + style.size * 0.9];
+                    self.push(Op::Link { rect, url: url.clone() });
+                }
+            }
+        }
+    }
+
+    // 按字体拆分为多个文本操作
+    fn draw_text(&mut self, x: f32, baseline: f32, text: &str, style: &SpanStyle) {
+        let mut cursor = x;
+        let mut run: Option<(usize, bool, f32, Vec<u16>)> = None;
+        for ch in text.chars() {
+            let glyph = self.fonts.glyph(style.font, ch);
+            match run.as_mut() {
+                Some((face, fake_bold, _, glyphs)) if *face == glyph.face && *fake_bold == glyph.fake_bold => glyphs.push(glyph.id),
+                _ => {
+                    if let Some(run) = run.take() {
+                        self.push_run(run, baseline, style);
+                    }
+                    run = Some((glyph.face, glyph.fake_bold, cursor, vec![glyph.id]));
+                }
+            }
+            cursor += glyph.width * style.size / 1000.0;
+        }
+        if let Some(run) = run {
+            self.push_run(run, baseline, style);
+        }
+    }
+
+    fn push_run(&mut self, (face, fake_bold, x, glyphs): (usize, bool, f32, Vec<u16>), y: f32, style: &SpanStyle) {
+        self.push(Op::Text {
+            x,
+            y,
+            size: style.size,
+            face,
+            glyphs,
+            color: style.color,
+            italic: style.font.italic,
+            fake_bold,
+        });
+    }
+
+    fn draw_marker(&mut self, marker: Marker, x: f32, line: &Line) {
+        let baseline = Self::baseline(line, self.y);
+        let size = self.size;
+        let style = SpanStyle {
+            font: TextStyle::default(),
+            size,
+            color: TEXT,
+            strike: false,
+            code: false,
+            link: None,
+        };
+        match marker {
+            Marker::Bullet(depth) => {
+                let side = size * 0.32;
+                let (bx, by) = (x - LIST_INDENT * 0.6, baseline + size * 0.18);
+                let stroke = (depth % 2 == 1).then_some(0.6);
+                self.push(Op::Rect { x: bx, y: by, w: side, h: side, color: TEXT, stroke });
+            }
+            Marker::Number(number) => {
+                let text = format!("{}.", number);
+                let width = self.fonts.measure(style.font, &text, size);
+                self.draw_text(x - width - 4.0, baseline, &text, &style);
+            }
+            Marker::Task(checked) => {
+                let side = size * 0.75;
+                let (bx, by) = (x - LIST_INDENT + 2.0, baseline - size * 0.05);
+                self.push(Op::Rect { x: bx, y: by, w: side, h: side, color: TEXT, stroke: Some(0.7) });
+                if checked {
+                    let (x1, y1) = (bx + side * 0.2, by + side * 0.5);
+                    let (x2, y2) = (bx + side * 0.42, by + side * 0.22);
+                    let (x3, y3) = (bx + side * 0.82, by + side * 0.8);
+                    self.push(Op::Line { x1, y1, x2, y2, width: 1.0, color: TEXT });
+                    self.push(Op::Line { x1: x2, y1: y2, x2: x3, y2: y3, width: 1.0, color: TEXT });
+                }
+            }
+        }
+    }
+
+    // 引用块左侧的竖线, 逐行绘制以便跨页
+    fn quote_bars(&mut self, height: f32) {
+        for x in self.quotes.clone() {
+            self.push(Op::Rect { x: x + 2.0, y: self.y - height, w: 2.5, h: height, color: RULE, stroke: None });
+        }
+    }
+
+    fn code_block(&mut self, lang: &str, code: &str) {
+        self.marker = None;
+        let x = self.indent();
+        let width = self.right - x;
+        let style = SpanStyle {
+            font: TextStyle { mono: true, ..Default::default() },
+            size: self.size * 0.9,
+            color: TEXT,
+            strike: false,
+            code: false,
+            link: None,
+        };
+        let mut lines = Vec::new();
+        for spans in highlight(lang, code, &style) {
+            let wrapped = self.wrap(&spans, width - CODE_PADDING * 2.0);
+            if wrapped.is_empty() {
+                lines.push(Line { size: style.size, ..Default::default() });
+            }
+            lines.extend(wrapped);
+        }
+
+        // 背景逐行绘制, 跨页时每页各自有背景
+        self.ensure(CODE_PADDING + style.size * LINE_SPACING);
+        self.push(Op::Rect { x, y: self.y - CODE_PADDING, w: width, h: CODE_PADDING, color: CODE_BG, stroke: None });
+        self.quote_bars(CODE_PADDING);
+        self.y -= CODE_PADDING;
+        for line in lines.iter() {
+            let height = line.height();
+            self.ensure(height);
+            self.push(Op::Rect { x, y: self.y - height, w: width, h: height, color: CODE_BG, stroke: None });
+            self.draw_line(line, x + CODE_PADDING, self.y);
+            self.quote_bars(height);
+            self.y -= height;
+        }
+        self.push(Op::Rect { x, y: self.y - CODE_PADDING, w: width, h: CODE_PADDING, color: CODE_BG, stroke: None });
+        self.quote_bars(CODE_PADDING);
+        self.y -= CODE_PADDING;
+        self.gap(self.size * 0.6);
+    }
+
+    fn image_block(&mut self, url: &str, alt: &str) {
+        let index = if self.table.is_none() { self.load_image(url) } else { None };
+        let Some(index) = index else {
+            // 远程图片和无法读取的图片显示替代文本
+            let mut style = self.style();
+            style.font.italic = true;
+            style.color = MUTED;
+            self.push_text(&format!("[{}]", if alt.is_empty() { url } else { alt }), style);
+            return;
+        };
+        self.flush();
+        let (width, height) = self.images[index].size();
+        let x = self.indent();
+        // 按96dpi换算, 缩小到不超过版心
+        let scale = 0.75f32
+            .min((self.right - x) / width)
+            .min((self.top - self.bottom) / height);
+        let (w, h) = (width * scale, height * scale);
+        self.ensure(h);
+        self.push(Op::Image { index, x, y: self.y - h, w, h });
+        self.quote_bars(h);
+        self.y -= h + self.size * 0.4;
+    }
+
+    fn load_image(&mut self, url: &str) -> Option<usize> {
+        if url.contains("://") && !url.starts_with("file://") {
+            return None;
+        }
+        let path = PathBuf::from(
+            percent_encoding::percent_decode_str(url.trim_start_matches("file://")).decode_utf8_lossy().to_string()
+        );
+        if let Some(index) = self.loaded.get(&path) {
+            return *index;
+        }
+        let index = match std::fs::read(&path).map_err(anyhow::Error::from).and_then(Image::decode) {
+            Ok(image) => {
+                self.images.push(image);
+                Some(self.images.len() - 1)
+            }
+            Err(e) => {
+                crate::log_warn!("export_pdf"; "failed to load image {}: {}", path.display(), e);
+                None
+            }
+        };
+        self.loaded.insert(path, index);
+        index
+    }
+
+    fn table_block(&mut self, table: Table) {
+        let Table { aligns, mut rows, head } = table;
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        for span in rows.iter_mut().take(head).flatten().flatten() {
+            span.style.font.bold = true;
+        }
+
+        // 列宽按内容宽度分配, 超出版心时按比例缩小
+        let x = self.indent();
+        let available = self.right - x;
+        let mut natural = vec![CELL_PADDING * 2.0; columns];
+        for row in rows.iter() {
+            for (idx, cell) in row.iter().enumerate() {
+                let width: f32 = cell.iter().map(|s| self.fonts.measure(s.style.font, &s.text, s.style.size)).sum();
+                natural[idx] = natural[idx].max(width + CELL_PADDING * 2.0);
+            }
+        }
+        let total: f32 = natural.iter().sum();
+        let widths: Vec<f32> = if total <= available {
+            natural
+        } else {
+            natural.iter().map(|w| available * (0.5 / columns as f32 + 0.5 * w / total)).collect()
+        };
+
+        let laid: Vec<(Vec<Vec<Line>>, f32)> = rows.iter()
+            .map(|row| {
+                let cells: Vec<Vec<Line>> = (0..columns)
+                    .map(|idx| row.get(idx).map(|cell| self.wrap(cell, widths[idx] - CELL_PADDING * 2.0)).unwrap_or_default())
+                    .collect();
+                let content = cells.iter()
+                    .map(|lines| lines.iter().map(Line::height).sum::<f32>())
+                    .fold(self.size * LINE_SPACING, f32::max);
+                (cells, content + CELL_PADDING * 2.0)
+            })
+            .collect();
+        for (idx, (cells, height)) in laid.iter().enumerate() {
+            if self.y - height < self.bottom && self.y < self.top {
+                self.new_page();
+                // 跨页时重复表头
+                if idx >= head {
+                    for (cells, height) in laid.iter().take(head) {
+                        self.draw_row(x, &widths, &aligns, cells, *height, true);
+                    }
+                }
+            }
+            self.draw_row(x, &widths, &aligns, cells, *height, idx < head);
+        }
+    }
+
+    fn draw_row(&mut self, x: f32, widths: &[f32], aligns: &[Alignment], cells: &[Vec<Line>], height: f32, header: bool) {
+        let mut cx = x;
+        for (idx, lines) in cells.iter().enumerate() {
+            let w = widths[idx];
+            if header {
+                self.push(Op::Rect { x: cx, y: self.y - height, w, h: height, color: HEADER_BG, stroke: None });
+            }
+            self.push(Op::Rect { x: cx, y: self.y - height, w, h: height, color: RULE, stroke: Some(0.5) });
+            let mut top = self.y - CELL_PADDING;
+            for line in lines.iter() {
+                let space = w - CELL_PADDING * 2.0 - line.width;
+                let offset = match aligns.get(idx) {
+                    Some(Alignment::Center) => space / 2.0,
+                    Some(Alignment::Right) => space,
+                    _ => 0.0,
+                };
+                self.draw_line(line, cx + CELL_PADDING + offset.max(0.0), top);
+                top -= line.height();
+            }
+            cx += w;
+        }
+        self.y -= height;
+    }
+}
+
+// 代码高亮, 每个源代码行返回一组带颜色的文本
+fn highlight(lang: &str, code: &str, style: &SpanStyle) -> Vec<Vec<Span>> {
+    let code = code.replace('\t', "    ");
+    let mut highlighter = HighlightLines::new(syntax::find_syntax(lang), &CODE_THEME);
+    LinesWithEndings::from(&code)
+        .map(|line| match highlighter.highlight_line(line, &SYNTAXES) {
+            Ok(ranges) => ranges.into_iter()
+                .map(|(s, text)| Span {
+                    text: text.trim_end_matches(['\r', '\n']).to_string(),
+                    style: SpanStyle {
+                        font: TextStyle {
+                            bold: s.font_style.contains(FontStyle::BOLD),
+                            italic: s.font_style.contains(FontStyle::ITALIC),
+                            mono: true,
+                        },
+                        color: [s.foreground.r, s.foreground.g, s.foreground.b].map(|c| c as f32 / 255.0),
+                        ..style.clone()
+                    },
+                })
+                .filter(|span| !span.text.is_empty())
+                .collect(),
+            Err(_) => vec![Span { text: line.trim_end_matches(['\r', '\n']).to_string(), style: style.clone() }],
+        })
+        .collect()
+}
+
+// 可以在其后断行的文本片段: 空白之后、中日韩字符前后, 换行单独作为一段
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, ch)) = chars.next() {
+        let end = idx + ch.len_utf8();
+        if ch == '\n' {
+            if start < idx {
+                tokens.push(&text[start..idx]);
+            }
+            tokens.push("\n");
+            start = end;
+            continue;
+        }
+        let breakable = match chars.peek() {
+            None => true,
+            Some(&(_, next)) => next == '\n'
+                || is_cjk(ch)
+                || is_cjk(next)
+                || (ch.is_whitespace() && !next.is_whitespace()),
+        };
+        if breakable {
+            tokens.push(&text[start..end]);
+            start = end;
+        }
+    }
+    tokens
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x2E80..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF | 0x20000..=0x2FFFF
+    )
+}
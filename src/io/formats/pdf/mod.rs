@@ -0,0 +1,319 @@
+mod fonts;
+mod layout;
+
+use std::path::Path;
+use chrono::{Datelike, Timelike};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use pdf_writer::types::{ActionType, AnnotationType, CidFontType, FontFlags, SystemInfo, TextRenderingMode, UnicodeCmap};
+use crate::data::ExportNote;
+use crate::io::PdfConfig;
+use fonts::{FontFace, FontSet};
+use layout::{Image, Layout, Op, Page};
+
+/// PDF导出: 排版Markdown并嵌入字体子集(思源黑体优先)
+pub(in crate::io) struct PdfHandler;
+
+impl PdfHandler {
+    pub fn export_note(&self, note: &ExportNote, output_path: &Path, config: &PdfConfig) -> anyhow::Result<()> {
+        let bytes = self.render(note, config)?;
+        std::fs::write(output_path, bytes)?;
+        Ok(())
+    }
+
+    pub fn render(&self, note: &ExportNote, config: &PdfConfig) -> anyhow::Result<Vec<u8>> {
+        let mut fonts = FontSet::load()?;
+        let mut layout = Layout::new(&mut fonts, config);
+        let mut subtitle: Vec<String> = note.updated
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .into_iter()
+            .collect();
+        let mut tags: Vec<_> = note.tags().iter().map(|t| format!("#{}", t)).collect();
+        tags.sort();
+        subtitle.extend(tags);
+        layout.title(note.title(), &subtitle.join("  "));
+        layout.markdown(note.content());
+        let (pages, images) = layout.finish(note.title(), config);
+        write_document(note.title(), config, &pages, &images, &fonts)
+    }
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec_zlib(data, 6)
+}
+
+fn write_document(title: &str, config: &PdfConfig, pages: &[Page], images: &[Image], fonts: &FontSet) -> anyhow::Result<Vec<u8>> {
+    let (width, height) = config.page_size.dimensions();
+    let mut alloc = Ref::new(1);
+    let catalog_id = alloc.bump();
+    let tree_id = alloc.bump();
+    let info_id = alloc.bump();
+    let page_ids: Vec<(Ref, Ref)> = pages.iter().map(|_| (alloc.bump(), alloc.bump())).collect();
+    // 只嵌入用到的字体
+    let font_ids: Vec<Option<Ref>> = fonts.faces.iter()
+        .map(|face| face.as_ref().filter(|f| !f.used.is_empty()).map(|_| alloc.bump()))
+        .collect();
+    let image_ids: Vec<Ref> = images.iter().map(|_| alloc.bump()).collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id)
+        .kids(page_ids.iter().map(|(page_id, _)| *page_id))
+        .count(pages.len() as i32);
+    let now = chrono::Local::now();
+    let date = pdf_writer::Date::new(now.year() as u16)
+        .month(now.month() as u8)
+        .day(now.day() as u8)
+        .hour(now.hour() as u8)
+        .minute(now.minute() as u8)
+        .second(now.second() as u8);
+    pdf.document_info(info_id)
+        .title(TextStr(title))
+        .creator(TextStr("Cloud Notepad"))
+        .creation_date(date);
+
+    for (page, (page_id, content_id)) in pages.iter().zip(page_ids.iter()) {
+        let content = deflate(&write_content(page));
+        pdf.stream(*content_id, &content).filter(Filter::FlateDecode);
+
+        let mut pdf_page = pdf.page(*page_id);
+        pdf_page.media_box(Rect::new(0.0, 0.0, width, height))
+            .parent(tree_id)
+            .contents(*content_id);
+        let mut resources = pdf_page.resources();
+        let mut font_dict = resources.fonts();
+        for (idx, id) in font_ids.iter().enumerate() {
+            if let Some(id) = id {
+                font_dict.pair(Name(format!("F{}", idx).as_bytes()), *id);
+            }
+        }
+        font_dict.finish();
+        let mut image_dict = resources.x_objects();
+        for (idx, id) in image_ids.iter().enumerate() {
+            image_dict.pair(Name(format!("Im{}", idx).as_bytes()), *id);
+        }
+        image_dict.finish();
+        resources.finish();
+
+        let mut annotations = pdf_page.annotations();
+        for op in page.ops.iter() {
+            if let Op::Link { rect: [x1, y1, x2, y2], url } = op {
+                let mut annotation = annotations.push();
+                annotation.subtype(AnnotationType::Link)
+                    .rect(Rect::new(*x1, *y1, *x2, *y2))
+                    .border(0.0, 0.0, 0.0, None);
+                annotation.action()
+                    .action_type(ActionType::Uri)
+                    .uri(Str(url.as_bytes()));
+            }
+        }
+    }
+
+    for (idx, (face, id)) in fonts.faces.iter().zip(font_ids.iter()).enumerate() {
+        if let (Some(face), Some(id)) = (face, id) {
+            write_font(&mut pdf, &mut alloc, *id, idx, face)?;
+        }
+    }
+    for (image, id) in images.iter().zip(image_ids.iter()) {
+        write_image(&mut pdf, &mut alloc, *id, image);
+    }
+    Ok(pdf.finish())
+}
+
+fn write_content(page: &Page) -> Vec<u8> {
+    let mut content = Content::new();
+    for op in page.ops.iter() {
+        match op {
+            Op::Text { x, y, size, face, glyphs, color: [r, g, b], italic, fake_bold } => {
+                // 使用 Identity-H 编码, 每个字形2字节
+                let bytes: Vec<u8> = glyphs.iter().flat_map(|id| id.to_be_bytes()).collect();
+                content.save_state();
+                content.begin_text();
+                content.set_font(Name(format!("F{}", face).as_bytes()), *size);
+                content.set_fill_rgb(*r, *g, *b);
+                if *fake_bold {
+                    content.set_text_rendering_mode(TextRenderingMode::FillStroke);
+                    content.set_stroke_rgb(*r, *g, *b);
+                    content.set_line_width(size * 0.04);
+                }
+                // 斜体通过倾斜文字矩阵模拟
+                let skew = if *italic { 0.2 } else { 0.0 };
+                content.set_text_matrix([1.0, 0.0, skew, 1.0, *x, *y]);
+                content.show(Str(&bytes));
+                content.end_text();
+                content.restore_state();
+            }
+            Op::Rect { x, y, w, h, color: [r, g, b], stroke } => {
+                content.save_state();
+                match stroke {
+                    Some(width) => {
+                        content.set_stroke_rgb(*r, *g, *b).set_line_width(*width);
+                        content.rect(*x, *y, *w, *h).stroke();
+                    }
+                    None => {
+                        content.set_fill_rgb(*r, *g, *b);
+                        content.rect(*x, *y, *w, *h).fill_nonzero();
+                    }
+                }
+                content.restore_state();
+            }
+            Op::Line { x1, y1, x2, y2, width, color: [r, g, b] } => {
+                content.save_state();
+                content.set_stroke_rgb(*r, *g, *b).set_line_width(*width);
+                content.move_to(*x1, *y1).line_to(*x2, *y2).stroke();
+                content.restore_state();
+            }
+            Op::Image { index, x, y, w, h } => {
+                content.save_state();
+                content.transform([*w, 0.0, 0.0, *h, *x, *y]);
+                content.x_object(Name(format!("Im{}", index).as_bytes()));
+                content.restore_state();
+            }
+            Op::Link { .. } => {}
+        }
+    }
+    content.finish()
+}
+
+// 嵌入字体子集: Type0 + CIDFont, 字形id直接作为CID
+fn write_font(pdf: &mut Pdf, alloc: &mut Ref, type0_id: Ref, idx: usize, face: &FontFace) -> anyhow::Result<()> {
+    let cid_id = alloc.bump();
+    let descriptor_id = alloc.bump();
+    let cmap_id = alloc.bump();
+    let file_id = alloc.bump();
+    let is_cff = face.is_cff();
+    // 子集字体名称需要6个大写字母的前缀
+    let base_font = format!("CNPDF{}+{}", (b'A' + idx as u8) as char, face.name());
+    let system_info = SystemInfo {
+        registry: Str(b"Adobe"),
+        ordering: Str(b"Identity"),
+        supplement: 0,
+    };
+
+    pdf.type0_font(type0_id)
+        .base_font(Name(base_font.as_bytes()))
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(cid_id)
+        .to_unicode(cmap_id);
+
+    let mut cid = pdf.cid_font(cid_id);
+    cid.subtype(if is_cff { CidFontType::Type0 } else { CidFontType::Type2 })
+        .base_font(Name(base_font.as_bytes()))
+        .system_info(system_info)
+        .font_descriptor(descriptor_id)
+        .default_width(0.0);
+    if !is_cff {
+        cid.cid_to_gid_map_predefined(Name(b"Identity"));
+    }
+    let mut widths = cid.widths();
+    for id in face.used.keys() {
+        widths.consecutive(*id, [face.advance(*id)]);
+    }
+    widths.finish();
+    cid.finish();
+
+    let bbox = face.face.global_bounding_box();
+    let mut descriptor = pdf.font_descriptor(descriptor_id);
+    descriptor.name(Name(base_font.as_bytes()))
+        .flags(FontFlags::SYMBOLIC)
+        .bbox(Rect::new(face.scale(bbox.x_min), face.scale(bbox.y_min), face.scale(bbox.x_max), face.scale(bbox.y_max)))
+        .italic_angle(0.0)
+        .ascent(face.scale(face.face.ascender()))
+        .descent(face.scale(face.face.descender()))
+        .cap_height(face.scale(face.face.capital_height().unwrap_or(face.face.ascender())))
+        .stem_v(80.0);
+    if is_cff {
+        descriptor.font_file3(file_id);
+    } else {
+        descriptor.font_file2(file_id);
+    }
+    descriptor.finish();
+
+    // 复制文本时还原为原字符
+    let mut cmap = UnicodeCmap::new(Name(b"Custom"), system_info);
+    for (id, ch) in face.used.iter() {
+        cmap.pair(*id, *ch);
+    }
+    pdf.cmap(cmap_id, &cmap.finish());
+
+    let mut glyphs: Vec<u16> = face.used.keys().copied().collect();
+    if glyphs.first() != Some(&0) {
+        glyphs.insert(0, 0);
+    }
+    let subset = subsetter::subset(face.data, face.index, subsetter::Profile::pdf(&glyphs))
+        .map_err(|e| anyhow::anyhow!("Failed to subset font {}: {}", face.name(), e))?;
+    let data = deflate(&subset);
+    let mut stream = pdf.stream(file_id, &data);
+    stream.filter(Filter::FlateDecode);
+    if is_cff {
+        stream.pair(Name(b"Subtype"), Name(b"OpenType"));
+    }
+    Ok(())
+}
+
+fn write_image(pdf: &mut Pdf, alloc: &mut Ref, id: Ref, image: &Image) {
+    match image {
+        Image::Jpeg { data, width, height, components } => {
+            let mut xobject = pdf.image_xobject(id, data);
+            xobject.filter(Filter::DctDecode);
+            xobject.width(*width as i32).height(*height as i32).bits_per_component(8);
+            xobject.color_space_name(Name(match components {
+                1 => b"DeviceGray".as_slice(),
+                4 => b"DeviceCMYK".as_slice(),
+                _ => b"DeviceRGB".as_slice(),
+            }));
+        }
+        Image::Pixels { data, alpha, width, height, gray } => {
+            let mask_id = alpha.as_ref().map(|_| alloc.bump());
+            let compressed = deflate(data);
+            let mut xobject = pdf.image_xobject(id, &compressed);
+            xobject.filter(Filter::FlateDecode);
+            xobject.width(*width as i32).height(*height as i32).bits_per_component(8);
+            xobject.color_space_name(Name(if *gray { b"DeviceGray".as_slice() } else { b"DeviceRGB".as_slice() }));
+            if let Some(mask_id) = mask_id {
+                xobject.s_mask(mask_id);
+            }
+            xobject.finish();
+
+            if let (Some(alpha), Some(mask_id)) = (alpha, mask_id) {
+                let compressed = deflate(alpha);
+                let mut mask = pdf.image_xobject(mask_id, &compressed);
+                mask.filter(Filter::FlateDecode);
+                mask.width(*width as i32).height(*height as i32).bits_per_component(8);
+                mask.color_space_name(Name(b"DeviceGray"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+
+    #[test]
+    fn test_render_pdf() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let image = dir.join("dot.png");
+        let mut encoder = png::Encoder::new(std::fs::File::create(&image).unwrap(), 2, 2);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.write_header().unwrap().write_image_data(&[255u8; 16]).unwrap();
+
+        let content = format!(
+            "# Heading\n\nSome **bold**, *italic* and `code` with a [link](https://example.com).\n\n\
+             - [x] done\n- [ ] todo\n  1. nested\n\n> quote\n\n```rust\nfn main() {{}}\n```\n\n\
+             | a | b |\n|---|--:|\n| 1 | 2 |\n\n![dot]({})\n\n{}",
+            image.display(),
+            "long paragraph text ".repeat(400),
+        );
+        let note = ExportNote::new(None, "PDF".to_string(), HashSet::new(), content, None, None);
+        let bytes = PdfHandler.render(&note, &PdfConfig::default()).unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(bytes.starts_with(b"%PDF-"));
+        // 长段落需要分页
+        assert!(text.matches("/Type /Page\n").count() > 1);
+        assert!(text.contains("/Identity-H") && text.contains("/FontFile2"));
+        assert!(text.contains("/URI (https://example.com)"));
+        assert!(text.contains("/SMask"));
+    }
+}
@@ -1,53 +1,241 @@
-use std::path::Path;
-use anyhow::Result;
-use super::{ImportResult, ImportConfig, formats::{MarkdownHandler, JsonHandler}};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use glob::{MatchOptions, Pattern};
+use crate::error::{NotepadError, NotepadResult};
+use super::{BackupData, BackupManifest, ImportResult, ImportConfig, ImportReport, ImportSource, formats::{MarkdownHandler, JsonHandler, ObsidianHandler, EnexHandler, JoplinHandler, NotionHandler, BackupHandler}};
 
 pub struct Importer;
 
 impl Importer {
-    // 从文件或目录导入
-    pub fn import(input_path: &Path, config: &ImportConfig) -> Result<Vec<ImportResult>> {
+    // 从文件或目录导入(目录中读取失败的文件记录到报告中)
+    pub fn import(input_path: &Path, config: &ImportConfig, report: &mut ImportReport) -> NotepadResult<Vec<ImportResult>> {
         if input_path.is_dir() {
-            Self::import_directory(input_path, config)
+            let source = match config.source {
+                ImportSource::Auto if ObsidianHandler::is_vault(input_path) => ImportSource::Obsidian,
+                ImportSource::Auto if JoplinHandler::is_export(input_path) => ImportSource::Joplin,
+                source => source,
+            };
+            if source == ImportSource::Joplin {
+                return Ok(JoplinHandler.import_directory(input_path, config, report));
+            }
+            let obsidian = source == ImportSource::Obsidian;
+            let filter = PathFilter::new(config, obsidian)?;
+            let mut visited = Visited::default();
+            let mut results = Vec::new();
+            Self::import_directory(input_path, input_path, config, &filter, &mut visited, &mut results, report)?;
+            if obsidian {
+                ObsidianHandler.link_notes(input_path, &mut results, config);
+            }
+            Ok(results)
+        } else if input_path.extension().is_some_and(|ext| ext == "jex") {
+            JoplinHandler.import_jex(input_path, config, report).map_err(NotepadError::format)
         } else {
-            let result = Self::import_file(input_path, config)?;
-            Ok(vec![result])
+            Self::import_file(input_path, config)
         }
     }
 
-    // 导入单个文件
-    fn import_file(file_path: &Path, config: &ImportConfig) -> Result<ImportResult> {
+    // 读取单个Markdown文件(镜像目录使用), 没有 updated 时以文件修改时间为准
+    pub fn read_markdown(path: &Path) -> NotepadResult<ImportResult> {
+        let handler = MarkdownHandler;
+        handler.import_note(path, &ImportConfig::default()).map_err(NotepadError::format)
+    }
+
+    // 读取完整备份的清单
+    pub fn read_backup_manifest(path: &Path) -> NotepadResult<BackupManifest> {
+        BackupHandler.read_manifest(path).map_err(NotepadError::format)
+    }
+
+    // 读取完整备份的笔记数据和设置
+    pub fn import_backup(path: &Path, config: &ImportConfig) -> NotepadResult<(BackupData, serde_json::Value)> {
+        BackupHandler.import(path, &config.backup_passphrase).map_err(NotepadError::format)
+    }
+
+    // 恢复完整备份中的附件
+    pub fn import_backup_attachments(path: &Path, config: &ImportConfig, overwrite: bool) -> NotepadResult<usize> {
+        BackupHandler.extract_attachments(path, &config.attachments_dir, overwrite).map_err(NotepadError::format)
+    }
+
+    // 导入单个文件(JSON备份可包含多个笔记)
+    fn import_file(file_path: &Path, config: &ImportConfig) -> NotepadResult<Vec<ImportResult>> {
         match file_path.extension().and_then(|s| s.to_str()) {
             Some("md") | Some("markdown") => {
                 let handler = MarkdownHandler;
-                handler.import_note(file_path, config)
+                Ok(vec![handler.import_note(file_path, config).map_err(NotepadError::format)?])
             }
             Some("json") => {
                 let handler = JsonHandler;
-                handler.import_note(file_path, config)
+                handler.import_notes(file_path, config).map_err(NotepadError::format)
             }
-            _ => Err(anyhow::anyhow!("Unsupported file format!")),
+            Some("enex") => {
+                let handler = EnexHandler;
+                handler.import_notes(file_path, config).map_err(NotepadError::format)
+            }
+            Some("zip") => {
+                let handler = NotionHandler;
+                handler.import_zip(file_path, config).map_err(NotepadError::format)
+            }
+            _ => Err(NotepadError::Format("Unsupported file format!".to_string())),
+        }
+    }
+
+    // 导入目录(递归导入子目录, 跳过符号链接形成的循环)
+    fn import_directory(
+        root: &Path,
+        dir_path: &Path,
+        config: &ImportConfig,
+        filter: &PathFilter,
+        visited: &mut Visited,
+        results: &mut Vec<ImportResult>,
+        report: &mut ImportReport,
+    ) -> NotepadResult<()> {
+        let canonical = dir_path.canonicalize()?;
+        // 目录在当前路径上时为循环; 经其他符号链接再次到达的目录已经导入过, 直接跳过
+        if visited.path.contains(&canonical) {
+            report.push_failure(dir_path, "Symlink loop detected, directory skipped".to_string());
+            return Ok(());
         }
+        if !visited.all.insert(canonical.clone()) {
+            return Ok(());
+        }
+        visited.path.push(canonical);
+        let imported = Self::import_entries(root, dir_path, config, filter, visited, results, report);
+        visited.path.pop();
+        imported
     }
 
-    // 导入目录
-    fn import_directory(dir_path: &Path, config: &ImportConfig) -> Result<Vec<ImportResult>> {
-        let mut results = Vec::new();
+    fn import_entries(
+        root: &Path,
+        dir_path: &Path,
+        config: &ImportConfig,
+        filter: &PathFilter,
+        visited: &mut Visited,
+        results: &mut Vec<ImportResult>,
+        report: &mut ImportReport,
+    ) -> NotepadResult<()> {
 
-        for entry in std::fs::read_dir(dir_path)? {
-            let entry = entry?;
+        let mut entries = std::fs::read_dir(dir_path)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
             let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            if filter.is_excluded(relative) {
+                continue;
+            }
 
-            if path.is_file() {
+            if path.is_dir() {
+                if config.recursive
+                    && let Err(e) = Self::import_directory(root, &path, config, filter, visited, results, report) {
+                    report.push_failure(&path, e.to_string());
+                }
+            } else if path.is_file() && filter.is_included(relative) && filter.is_note(&path) {
                 match Self::import_file(&path, config) {
-                    Ok(result) => results.push(result),
-                    Err(e) => {
-                        eprintln!("Failed to import {}: {}", path.display(), e);
+                    Ok(imported) => {
+                        for mut result in imported {
+                            if config.folder_tags
+                                && let Some(tag) = folder_tag(relative) {
+                                result.note.add_tag(tag);
+                            }
+                            results.push(result);
+                        }
                     }
+                    Err(e) => report.push_failure(&path, e.to_string()),
                 }
             }
         }
+        Ok(())
+    }
+}
+
+// 递归导入时已访问的目录(规范化路径)
+#[derive(Default)]
+struct Visited {
+    all: HashSet<PathBuf>,
+    path: Vec<PathBuf>,     // 从根目录到当前目录
+}
+
+// 相对目录路径转换为层级标签(标签中不能包含空白)
+fn folder_tag(relative: &Path) -> Option<String> {
+    let folders: Vec<String> = relative.parent()?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().split_whitespace().collect::<Vec<_>>().join("_"))
+        .collect();
+    (!folders.is_empty()).then(|| folders.join("/"))
+}
+
+// 按glob模式筛选导入的文件
+struct PathFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    markdown_only: bool,    // 其他文件作为附件, 不单独导入
+}
+
+impl PathFilter {
+    fn new(config: &ImportConfig, obsidian: bool) -> NotepadResult<Self> {
+        let parse = |patterns: &str| patterns.split_whitespace()
+            .map(Pattern::new)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| NotepadError::Invalid(format!("Invalid glob pattern: {}", e)));
+        let mut exclude = parse(&config.exclude)?;
+        if obsidian {
+            // 仓库配置和回收站
+            exclude.extend(parse(".obsidian .trash")?);
+        }
+        Ok(Self {
+            include: parse(&config.include)?,
+            exclude,
+            markdown_only: obsidian,
+        })
+    }
+
+    fn is_note(&self, path: &Path) -> bool {
+        !self.markdown_only || path.extension().is_some_and(|ext| ext == "md")
+    }
+
+    // 模式可以匹配相对路径(如 drafts/**)或文件名(如 *.tmp.md)
+    fn matches(patterns: &[Pattern], relative: &Path) -> bool {
+        let options = MatchOptions { require_literal_separator: true, ..Default::default() };
+        let name = relative.file_name().map(Path::new).unwrap_or(relative);
+        patterns.iter().any(|p| p.matches_path_with(relative, options) || p.matches_path_with(name, options))
+    }
+
+    fn is_included(&self, relative: &Path) -> bool {
+        self.include.is_empty() || Self::matches(&self.include, relative)
+    }
+
+    fn is_excluded(&self, relative: &Path) -> bool {
+        Self::matches(&self.exclude, relative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recursive_import() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("work/rust")).unwrap();
+        std::fs::create_dir_all(dir.join("drafts")).unwrap();
+        std::fs::create_dir_all(dir.join("shared")).unwrap();
+        std::fs::write(dir.join("top.md"), "top").unwrap();
+        std::fs::write(dir.join("shared/common.md"), "common").unwrap();
+        std::fs::write(dir.join("work/rust/ownership.md"), "borrow").unwrap();
+        std::fs::write(dir.join("drafts/wip.md"), "wip").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir, dir.join("work/loop")).unwrap();
+        // 经两条路径到达的目录不是循环, 只导入一次
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("shared"), dir.join("work/shared")).unwrap();
 
-        Ok(results)
+        let config = ImportConfig { exclude: "drafts".to_string(), ..Default::default() };
+        let mut report = ImportReport::new(dir);
+        let results = Importer::import(dir, &config, &mut report).unwrap();
+        let titles: Vec<_> = results.iter().map(|r| r.note().title()).collect();
+        assert_eq!(titles, vec!["common", "top", "ownership"]);
+        assert!(results[2].note().tags().contains("work/rust"));
+        assert!(results[1].note().tags().is_empty());
+        #[cfg(unix)]
+        assert_eq!(report.failed(), 1);
     }
 }
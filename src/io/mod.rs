@@ -4,13 +4,29 @@ pub mod formats;
 
 pub use importer::Importer;
 pub use exporter::Exporter;
+pub use formats::backup::{BackupData, BackupManifest, BackupNote, RestoreMode, BACKUP_EXTENSION};
 
-use crate::data::ExportNote;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::data::{ExportNote, Note};
+
+// 笔记链接的URL前缀(如 note://<id>#heading)
+pub const NOTE_LINK_SCHEME: &str = "note://";
+
+// 导入时需要复制的附件
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub source: PathBuf,    // 原文件
+    pub target: PathBuf,    // 相对于附件目录的保存路径
+    pub temporary: bool,    // 原文件是解码出的临时文件, 复制后删除
+}
 
 // 导入结果
 pub struct ImportResult {
     note: ExportNote,
+    source: PathBuf,
     warnings: Vec<String>,
+    attachments: Vec<Attachment>,
 }
 
 impl ImportResult {
@@ -18,9 +34,168 @@ impl ImportResult {
         &self.note
     }
 
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
     pub fn warnings(&self) -> &Vec<String> {
         &self.warnings
     }
+
+    pub fn attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+}
+
+// 导入预览中检测到的冲突
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportConflict {
+    SameId(String),     // 已有相同id的笔记
+    SameTitle(String),  // 已有同名但内容不同的笔记(记录其id)
+    Trashed(String),    // 回收站中有相同id的笔记
+}
+
+impl ImportConflict {
+    // 冲突的已有笔记id
+    pub fn existing_id(&self) -> &str {
+        match self {
+            ImportConflict::SameId(id) | ImportConflict::SameTitle(id) | ImportConflict::Trashed(id) => id,
+        }
+    }
+}
+
+// 导入预览中的一个待导入笔记
+pub struct ImportCandidate {
+    pub result: ImportResult,
+    pub conflict: Option<ImportConflict>,
+    pub existing: Option<Note>,     // 冲突的已有笔记(回收站中的笔记不在笔记本中)
+    pub strategy: MergeStrategy,    // 存在冲突时的处理方式
+}
+
+// 导入预览：已解析但尚未写入的笔记
+pub struct ImportPlan {
+    pub report: ImportReport,       // 已记录读取失败的文件
+    pub candidates: Vec<ImportCandidate>,
+}
+
+impl ImportPlan {
+    pub fn conflicts(&self) -> usize {
+        self.candidates.iter().filter(|c| c.conflict.is_some()).count()
+    }
+}
+
+// 单条导入记录的处理结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum ImportStatus {
+    Imported,       // 新建笔记
+    Skipped,        // 已有笔记, 按 MergeStrategy::Skip 跳过
+    Renamed,        // 已有笔记, 以新id导入
+    Overwritten,    // 覆盖已有笔记
+    Merged,         // 与已有笔记自动合并(或内容一致)
+    Conflicted,     // 合并存在冲突, 等待手动处理
+    Failed(String), // 读取或保存失败
+}
+
+impl ImportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportStatus::Imported => "imported",
+            ImportStatus::Skipped => "skipped",
+            ImportStatus::Renamed => "renamed",
+            ImportStatus::Overwritten => "overwritten",
+            ImportStatus::Merged => "merged",
+            ImportStatus::Conflicted => "conflicted",
+            ImportStatus::Failed(_) => "failed",
+        }
+    }
+}
+
+// 导入报告中的一条记录
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportEntry {
+    pub source: PathBuf,
+    pub title: Option<String>,      // 读取失败时没有标题
+    pub note_id: Option<String>,
+    #[serde(flatten)]
+    pub status: ImportStatus,
+    pub warnings: Vec<String>,
+}
+
+// 导入报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub source: PathBuf,
+    pub entries: Vec<ImportEntry>,
+}
+
+impl ImportReport {
+    pub fn new(source: &Path) -> Self {
+        Self {
+            source: source.to_path_buf(),
+            entries: Vec::new(),
+        }
+    }
+
+    // 记录读取失败的文件
+    pub fn push_failure(&mut self, source: &Path, reason: String) {
+        self.entries.push(ImportEntry {
+            source: source.to_path_buf(),
+            title: None,
+            note_id: None,
+            status: ImportStatus::Failed(reason),
+            warnings: Vec::new(),
+        });
+    }
+
+    // 记录某个笔记的处理结果
+    pub fn push_result(&mut self, result: &ImportResult, note_id: Option<String>, status: ImportStatus) {
+        self.entries.push(ImportEntry {
+            source: result.source.clone(),
+            title: Some(result.note.title().to_string()),
+            note_id,
+            status,
+            warnings: result.warnings.clone(),
+        });
+    }
+
+    pub fn count(&self, status: &ImportStatus) -> usize {
+        self.entries.iter().filter(|e| &e.status == status).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.entries.iter().filter(|e| matches!(e.status, ImportStatus::Failed(_))).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.entries.iter().map(|e| e.warnings.len()).sum()
+    }
+
+    // 成功写入的笔记数
+    pub fn saved(&self) -> usize {
+        self.entries.iter()
+            .filter(|e| matches!(
+                e.status,
+                ImportStatus::Imported | ImportStatus::Renamed | ImportStatus::Overwritten | ImportStatus::Merged
+            ))
+            .count()
+    }
+
+    // 一行摘要, 如 "Imported 42 notes, 3 skipped, 1 failed"
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Imported {} notes", self.saved());
+        for (count, label) in [
+            (self.count(&ImportStatus::Skipped), "skipped"),
+            (self.count(&ImportStatus::Conflicted), "conflicted"),
+            (self.failed(), "failed"),
+            (self.warnings(), "warnings"),
+        ] {
+            if count > 0 {
+                summary.push_str(&format!(", {} {}", count, label));
+            }
+        }
+        summary
+    }
 }
 
 // 导入配置
@@ -28,6 +203,13 @@ impl ImportResult {
 pub struct ImportConfig {
     pub merge_strategy: MergeStrategy,  // 冲突处理策略
     pub preserve_timestamps: bool,      //是否保留原时间戳
+    pub backup_passphrase: String,      // 加密备份的口令
+    pub recursive: bool,                // 是否导入子目录
+    pub folder_tags: bool,              // 是否将相对目录路径作为层级标签(如 work/rust)
+    pub include: String,                // 导入的文件(以空格分隔的glob模式, 空表示全部)
+    pub exclude: String,                // 排除的文件或目录(以空格分隔的glob模式)
+    pub source: ImportSource,           // 目录的来源应用
+    pub attachments_dir: PathBuf,       // 导入的附件保存目录
 }
 
 impl Default for ImportConfig {
@@ -35,34 +217,100 @@ impl Default for ImportConfig {
         Self {
             merge_strategy: MergeStrategy::Rename,
             preserve_timestamps: true,
+            backup_passphrase: String::new(),
+            recursive: true,
+            folder_tags: true,
+            include: String::new(),
+            exclude: String::new(),
+            source: ImportSource::Auto,
+            attachments_dir: PathBuf::from("attachments"),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportSource {
+    Auto,       // 根据目录内容自动识别
+    Markdown,   // 普通Markdown/JSON文件
+    Obsidian,   // Obsidian 仓库(解析标签、双链和附件)
+    Joplin,     // Joplin RAW 导出目录
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MergeStrategy {
     Skip,       // 跳过已有笔记
     Overwrite,  // 覆盖已有笔记
     Rename,     // 重命名新笔记(添加后缀)
+    Merge,      // 与已有笔记三方合并
 }
 
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{title}";
+
 // 导出配置
 #[derive(Debug, Clone)]
 pub struct ExportConfig {
     pub format: ExportFormat,   
+    pub pdf: PdfConfig,         // PDF导出的页面设置
+    pub filename_template: String,  // 导出多个文件时的文件名模板, 如 {created:%Y-%m-%d}-{title} 或 {tag}/{title}
 }
 
 impl Default for ExportConfig {
     fn default() -> Self {
         Self {
             format: ExportFormat::Markdown(true),
+            pdf: PdfConfig::default(),
+            filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExportFormat {
     Markdown(bool),   // 单个或多个.md 文件, 是否包含元数据
     Json,             // 单一JSON文件(完整备份)
     Html,             // HTML格式(可选)
-}
\ No newline at end of file
+    Pdf,              // 分页的PDF文件(嵌入字体)
+    Docx,             // Word文档
+    Odt,              // OpenDocument文本
+}
+
+// PDF导出的页面设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PdfConfig {
+    pub page_size: PageSize,
+    pub margin_mm: f32,     // 四周页边距(毫米)
+    pub font_size: f32,     // 正文字号(磅)
+    pub header: bool,       // 页眉显示笔记标题
+    pub footer: bool,       // 页脚显示页码
+}
+
+impl Default for PdfConfig {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::A4,
+            margin_mm: 20.0,
+            font_size: 11.0,
+            header: true,
+            footer: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PageSize {
+    A4,
+    A5,
+    Letter,
+}
+
+impl PageSize {
+    // 页面宽高(磅)
+    pub fn dimensions(&self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::A5 => (419.53, 595.28),
+            PageSize::Letter => (612.0, 792.0),
+        }
+    }
+}
@@ -3,7 +3,9 @@ pub use services::AppState;
 
 pub mod data;
 
+#[cfg(feature = "gui")]
 pub mod ui;
+#[cfg(feature = "gui")]
 pub use ui::app::NoteApp;
 
 pub mod utils;
@@ -12,3 +14,14 @@ pub use utils::i18n;
 pub mod markdown;
 
 pub mod io;
+
+pub mod error;
+pub use error::{NotepadError, NotepadResult};
+
+pub mod sync;
+pub mod server;
+
+pub mod cli;
+
+pub mod store;
+pub use store::NotesStore;
@@ -1,16 +1,21 @@
+#[cfg(feature = "gui")]
 mod nodes;
 pub mod syntax;
+#[cfg(feature = "gui")]
 pub mod renderer;
 
+#[cfg(feature = "gui")]
 use nodes::Node;
 
 /// 渲染上下文
+#[cfg(feature = "gui")]
 struct RenderConfig<'a> {
     text: &'a str,
     cursor_pos: Option<usize>,
     theme: Theme,
 }
 
+#[cfg(feature = "gui")]
 impl<'a> RenderConfig<'a> {
     pub fn new(text: &'a str, cursor_pos: Option<usize>, theme: Theme) -> Self {
         Self { text, cursor_pos, theme }
@@ -26,6 +31,7 @@ impl<'a> RenderConfig<'a> {
 }
 
 /// 主题
+#[cfg(feature = "gui")]
 #[derive(Clone)]
 struct Theme {
     text_color: Color32,
@@ -41,6 +47,7 @@ struct Theme {
     task_done_color: Color32,
 }
 
+#[cfg(feature = "gui")]
 impl Default for Theme {
     fn default() -> Self {
         Self {
@@ -59,4 +66,5 @@ impl Default for Theme {
     }
 }
 
+#[cfg(feature = "gui")]
 use egui::{TextFormat, FontId, FontFamily, Color32, text::LayoutJob};
\ No newline at end of file
@@ -0,0 +1,21 @@
+use once_cell::sync::Lazy;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+// 代码高亮的语法和主题(首次使用时加载)
+pub static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+pub static THEMES: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+// 浅色背景上使用的高亮主题
+pub const LIGHT_THEME: &str = "InspiredGitHub";
+// 深色背景上使用的高亮主题
+pub const DARK_THEME: &str = "base16-ocean.dark";
+
+/// 按代码块的语言标记(如 rust、py)查找语法, 未知语言按纯文本处理
+pub fn find_syntax(lang: &str) -> &'static SyntaxReference {
+    SYNTAXES.find_syntax_by_token(lang).unwrap_or_else(|| SYNTAXES.find_syntax_plain_text())
+}
+
+pub fn theme(name: &str) -> Theme {
+    THEMES.themes.get(name).cloned().unwrap_or_default()
+}
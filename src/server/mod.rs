@@ -0,0 +1,207 @@
+pub mod api;
+pub mod store;
+
+pub use store::ServerStore;
+
+use std::io::Read;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use anyhow::Result;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response};
+use crate::data::{NoteVersion, SyncRecord};
+use api::{ChangesResponse, ErrorResponse, LoginRequest, LoginResponse, PushResponse};
+
+/// 自托管笔记同步服务(REST/JSON)
+pub struct Server {
+    http: tiny_http::Server,
+    store: Mutex<ServerStore>,
+}
+
+// 处理结果：状态码 + JSON 响应体
+type Reply = (u16, String);
+
+// 请求体大小上限
+const MAX_BODY: u64 = 16 * 1024 * 1024;
+
+impl Server {
+    pub fn bind(addr: &str, store: ServerStore) -> Result<Self> {
+        let http = tiny_http::Server::http(addr).map_err(|e| anyhow::anyhow!("bind {} failed: {}", addr, e))?;
+        Ok(Self { http, store: Mutex::new(store) })
+    }
+
+    /// 实际监听的地址(绑定端口0时用于获取分配的端口)
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// 处理请求直到服务被关闭
+    pub fn run(&self) {
+        for mut request in self.http.incoming_requests() {
+            let (status, body) = self.handle(&mut request).unwrap_or_else(|e| error(500, &e.to_string()));
+            let response = Response::from_string(body)
+                .with_status_code(status)
+                .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+            if let Err(e) = request.respond(response) {
+                crate::log_error!("send_response"; "failed to send response: {}", e);
+            }
+        }
+    }
+
+    /// 停止服务(使 run 返回)
+    pub fn shutdown(&self) {
+        self.http.unblock();
+    }
+
+    fn handle(&self, request: &mut Request) -> Result<Reply> {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let segments: Vec<String> = path.trim_matches('/').split('/').map(api::percent_decode).collect();
+        let segments: Vec<&str> = segments.iter().map(|s| s.as_str()).collect();
+        let method = request.method().clone();
+
+        // 登录不需要令牌
+        if let (Method::Post, ["api", "login"]) = (&method, segments.as_slice()) {
+            let Some(body) = read_body(request)? else { return Ok(error(413, "request body too large")) };
+            let login: LoginRequest = serde_json::from_str(&body)?;
+            let token = self.store.lock().unwrap().login(&login.username, &login.password)?;
+            return match token {
+                Some(token) => ok(&LoginResponse { username: login.username, token }),
+                None => Ok(error(401, "invalid username or password")),
+            };
+        }
+
+        // 其余接口需要 Bearer 令牌
+        let token = request.headers().iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .and_then(|h| h.value.as_str().strip_prefix("Bearer ").map(|s| s.to_string()));
+        let Some(token) = token else { return Ok(error(401, "missing token")) };
+        let Some(username) = self.store.lock().unwrap().authenticate(&token)? else {
+            return Ok(error(401, "invalid token"));
+        };
+
+        match (&method, segments.as_slice()) {
+            (Method::Post, ["api", "logout"]) => {
+                self.store.lock().unwrap().logout(&token)?;
+                ok(&())
+            }
+            (Method::Get, ["api", "changes"]) => {
+                let since = query_param(query, "since").and_then(|s| s.parse().ok()).unwrap_or(0);
+                let (records, cursor) = self.store.lock().unwrap().changes_since(&username, since)?;
+                ok(&ChangesResponse { cursor, records })
+            }
+            (Method::Get, ["api", "notes", note_id]) => {
+                match self.store.lock().unwrap().get_record(&username, note_id)? {
+                    Some(record) => ok(&record),
+                    None => Ok(error(404, "note not found")),
+                }
+            }
+            (Method::Put, ["api", "notes", note_id]) => {
+                let Some(body) = read_body(request)? else { return Ok(error(413, "request body too large")) };
+                let record: SyncRecord = serde_json::from_str(&body)?;
+                if record.id() != *note_id {
+                    return Ok(error(400, "note id mismatch"));
+                }
+                let expected = query_param(query, "expected").and_then(|s| s.parse().ok());
+                match self.store.lock().unwrap().put_record(&username, &record, expected)? {
+                    Ok(revision) => ok(&PushResponse { revision }),
+                    // 修订号冲突时返回服务端当前记录
+                    Err(current) => Ok((409, serde_json::to_string(&current)?)),
+                }
+            }
+            (Method::Get, ["api", "notes", note_id, "versions"]) => {
+                ok(&self.store.lock().unwrap().list_versions(&username, note_id)?)
+            }
+            (Method::Put, ["api", "notes", note_id, "versions"]) => {
+                let Some(body) = read_body(request)? else { return Ok(error(413, "request body too large")) };
+                let version: NoteVersion = serde_json::from_str(&body)?;
+                if version.note_id() != *note_id {
+                    return Ok(error(400, "note id mismatch"));
+                }
+                if !self.store.lock().unwrap().put_version(&username, &version)? {
+                    return Ok(error(404, "note not found"));
+                }
+                ok(&())
+            }
+            _ => Ok(error(404, "not found")),
+        }
+    }
+}
+
+// 读取请求体，超过 MAX_BODY 时返回 None
+fn read_body(request: &mut Request) -> Result<Option<String>> {
+    if request.body_length().is_some_and(|len| len as u64 > MAX_BODY) {
+        return Ok(None);
+    }
+    let mut body = String::new();
+    request.as_reader().take(MAX_BODY + 1).read_to_string(&mut body)?;
+    if body.len() as u64 > MAX_BODY {
+        return Ok(None);
+    }
+    Ok(Some(body))
+}
+
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+fn ok<T: Serialize>(body: &T) -> Result<Reply> {
+    Ok((200, serde_json::to_string(body)?))
+}
+
+fn error(status: u16, message: &str) -> Reply {
+    let body = ErrorResponse { error: message.to_string() };
+    (status, serde_json::to_string(&body).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::data::{Database, Note};
+    use crate::sync::{HttpRemote, SyncEngine};
+    use super::*;
+
+    #[test]
+    fn test_http_sync_between_clients() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        let mut store = ServerStore::open(dir.join("server.db")).unwrap();
+        store.add_user("alice", "secret").unwrap();
+        let server = Arc::new(Server::bind("127.0.0.1:0", store).unwrap());
+        let url = format!("http://{}", server.local_addr().unwrap());
+        let handle = std::thread::spawn({
+            let server = server.clone();
+            move || server.run()
+        });
+
+        assert!(HttpRemote::login(&url, "alice", "wrong").is_err());
+        // 超大请求体被拒绝
+        let oversized = vec![b' '; MAX_BODY as usize + 1];
+        assert!(matches!(ureq::post(format!("{}/api/login", url)).send(&oversized[..]), Err(ureq::Error::StatusCode(413))));
+        let token = HttpRemote::login(&url, "alice", "secret").unwrap().token;
+
+        let mut a = Database::open(dir.join("a.db")).unwrap();
+        let mut b = Database::open(dir.join("b.db")).unwrap();
+        let mut note = Note::new("shared".to_string());
+        note.update_content("hello\n".to_string());
+        a.save_note(&note).unwrap();
+
+        let mut remote = HttpRemote::new(&url, &token);
+        assert_eq!(SyncEngine::new(&mut a, &mut remote).run().unwrap().pushed, 1);
+        let mut remote = HttpRemote::new(&url, &token);
+        assert_eq!(SyncEngine::new(&mut b, &mut remote).run().unwrap().pulled, 1);
+        assert_eq!(b.load_all_notes().unwrap().find_note(note.id()).unwrap().content(), "hello\n");
+
+        // 注销后令牌失效
+        HttpRemote::new(&url, &token).logout().unwrap();
+        let mut remote = HttpRemote::new(&url, &token);
+        assert!(SyncEngine::new(&mut b, &mut remote).run().is_err());
+
+        server.shutdown();
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,237 @@
+use std::path::Path;
+use anyhow::Result;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::{SaltString, rand_core::OsRng}};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use crate::data::{NoteVersion, SyncRecord};
+
+// 登录令牌的有效期
+const TOKEN_TTL: Duration = Duration::days(30);
+
+/// 服务端存储：按用户隔离的笔记记录、版本和登录令牌
+pub struct ServerStore {
+    connection: Connection,
+}
+
+impl ServerStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        // 用户表
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS users (
+                username TEXT PRIMARY KEY,
+                password_hash TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // 登录令牌表
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS tokens (
+                token TEXT PRIMARY KEY,
+                username TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // 笔记记录表(包含墓碑)，seq 为全局递增的变更序号
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS records (
+                username TEXT NOT NULL,
+                note_id TEXT NOT NULL,
+                revision INTEGER NOT NULL,
+                seq INTEGER NOT NULL,
+                payload TEXT NOT NULL,
+                PRIMARY KEY (username, note_id)
+            )",
+            [],
+        )?;
+
+        // 笔记版本表
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS versions (
+                username TEXT NOT NULL,
+                id TEXT NOT NULL,
+                note_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                PRIMARY KEY (username, id)
+            )",
+            [],
+        )?;
+        Ok(Self { connection })
+    }
+
+    /// 新增或更新用户
+    pub fn add_user(&mut self, username: &str, password: &str) -> Result<()> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| anyhow::anyhow!("hash password failed: {}", e))?
+            .to_string();
+        self.connection.execute(
+            "INSERT OR REPLACE INTO users (username, password_hash) VALUES (?1, ?2)",
+            [username, &hash],
+        )?;
+        Ok(())
+    }
+
+    /// 校验密码并签发令牌
+    pub fn login(&mut self, username: &str, password: &str) -> Result<Option<String>> {
+        let hash: Option<String> = self.connection
+            .query_row("SELECT password_hash FROM users WHERE username = ?1", [username], |row| row.get(0))
+            .optional()?;
+        let Some(hash) = hash else { return Ok(None) };
+        let hash = PasswordHash::new(&hash).map_err(|e| anyhow::anyhow!("invalid password hash: {}", e))?;
+        if Argon2::default().verify_password(password.as_bytes(), &hash).is_err() {
+            return Ok(None);
+        }
+
+        self.prune_tokens()?;
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        self.connection.execute(
+            "INSERT INTO tokens (token, username, created_at) VALUES (?1, ?2, ?3)",
+            [&token, username, &Utc::now().to_rfc3339()],
+        )?;
+        Ok(Some(token))
+    }
+
+    pub fn logout(&mut self, token: &str) -> Result<()> {
+        self.connection.execute("DELETE FROM tokens WHERE token = ?1", [token])?;
+        Ok(())
+    }
+
+    /// 根据令牌获取用户名(令牌过期时删除并返回None)
+    pub fn authenticate(&self, token: &str) -> Result<Option<String>> {
+        let row: Option<(String, String)> = self.connection
+            .query_row(
+                "SELECT username, created_at FROM tokens WHERE token = ?1",
+                [token],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((username, created_at)) = row else { return Ok(None) };
+        let expired = DateTime::parse_from_rfc3339(&created_at)
+            .map_or(true, |created_at| created_at.with_timezone(&Utc) + TOKEN_TTL < Utc::now());
+        if expired {
+            self.connection.execute("DELETE FROM tokens WHERE token = ?1", [token])?;
+            return Ok(None);
+        }
+        Ok(Some(username))
+    }
+
+    // 删除所有过期的令牌
+    fn prune_tokens(&self) -> Result<()> {
+        let cutoff = (Utc::now() - TOKEN_TTL).to_rfc3339();
+        self.connection.execute("DELETE FROM tokens WHERE created_at < ?1", [&cutoff])?;
+        Ok(())
+    }
+
+    /// 获取序号之后的变更，返回 (记录, 新游标)
+    pub fn changes_since(&self, username: &str, since: u64) -> Result<(Vec<SyncRecord>, u64)> {
+        let mut stmt = self.connection.prepare(
+            "SELECT seq, payload FROM records WHERE username = ?1 AND seq > ?2 ORDER BY seq"
+        )?;
+        let mut cursor = since;
+        let mut records = Vec::new();
+        for row in stmt.query_map(rusqlite::params![username, since], |row| {
+            Ok((row.get::<_, u64>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (seq, payload) = row?;
+            cursor = cursor.max(seq);
+            records.push(serde_json::from_str(&payload)?);
+        }
+        Ok((records, cursor))
+    }
+
+    pub fn get_record(&self, username: &str, note_id: &str) -> Result<Option<SyncRecord>> {
+        let payload: Option<String> = self.connection
+            .query_row(
+                "SELECT payload FROM records WHERE username = ?1 AND note_id = ?2",
+                [username, note_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(payload.map(|p| serde_json::from_str(&p)).transpose()?)
+    }
+
+    /// 写入记录，当前修订号与期望不一致时返回 Err(当前记录)
+    pub fn put_record(&mut self, username: &str, record: &SyncRecord, expected: Option<u64>) -> Result<Result<u64, SyncRecord>> {
+        let tx = self.connection.transaction()?;
+        let current: Option<String> = tx
+            .query_row(
+                "SELECT payload FROM records WHERE username = ?1 AND note_id = ?2",
+                [username, record.id()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let current: Option<SyncRecord> = current.map(|p| serde_json::from_str(&p)).transpose()?;
+        if let Some(current) = current.as_ref()
+            && Some(current.revision()) != expected {
+            return Ok(Err(current.clone()));
+        }
+
+        let revision = current.map(|r| r.revision()).unwrap_or_default() + 1;
+        let mut record = record.clone();
+        record.set_revision(revision);
+        tx.execute(
+            "INSERT OR REPLACE INTO records (username, note_id, revision, seq, payload)
+            VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(seq), 0) + 1 FROM records), ?4)",
+            rusqlite::params![username, record.id(), revision, serde_json::to_string(&record)?],
+        )?;
+        tx.commit()?;
+        Ok(Ok(revision))
+    }
+
+    pub fn list_versions(&self, username: &str, note_id: &str) -> Result<Vec<NoteVersion>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT payload FROM versions WHERE username = ?1 AND note_id = ?2"
+        )?;
+        let payloads = stmt.query_map([username, note_id], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(payloads.iter()
+            .map(|p| serde_json::from_str(p))
+            .collect::<serde_json::Result<Vec<_>>>()?)
+    }
+
+    /// 写入版本，用户没有该笔记的记录时返回 false
+    pub fn put_version(&mut self, username: &str, version: &NoteVersion) -> Result<bool> {
+        if self.get_record(username, version.note_id())?.is_none() {
+            return Ok(false);
+        }
+        self.connection.execute(
+            "INSERT OR REPLACE INTO versions (username, id, note_id, payload) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![username, version.id(), version.note_id(), serde_json::to_string(version)?],
+        )?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{Note, RecordState};
+    use super::*;
+
+    #[test]
+    fn test_tokens_and_versions() {
+        let mut store = ServerStore::open(":memory:").unwrap();
+        store.add_user("alice", "secret").unwrap();
+        let token = store.login("alice", "secret").unwrap().unwrap();
+        assert_eq!(store.authenticate(&token).unwrap().as_deref(), Some("alice"));
+
+        // 过期的令牌失效并被删除
+        let created_at = (Utc::now() - TOKEN_TTL - Duration::minutes(1)).to_rfc3339();
+        store.connection.execute("UPDATE tokens SET created_at = ?1", [&created_at]).unwrap();
+        assert!(store.authenticate(&token).unwrap().is_none());
+        let count: usize = store.connection.query_row("SELECT COUNT(*) FROM tokens", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+
+        // 只能为已有的笔记记录写入版本
+        let note = Note::new("plan".to_string());
+        let version = NoteVersion::new("v1", &note);
+        assert!(!store.put_version("alice", &version).unwrap());
+        store.put_record("alice", &SyncRecord::new(note, RecordState::Active, 1), None).unwrap().unwrap();
+        assert!(store.put_version("alice", &version).unwrap());
+        assert!(!store.put_version("bob", &version).unwrap());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::data::SyncScope;
+use crate::error::{NotepadError, NotepadResult};
+use crate::sync::HttpRemote;
+use super::{AppState, NotifyService};
+
+/// 已登录的同步服务账户
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub server: String,
+    pub username: String,
+    pub(super) token: String,
+}
+
+pub trait AccountService {
+    fn account(&self) -> Option<&Account>;
+    // 登录同步服务
+    fn login(&mut self, server: &str, username: &str, password: &str) -> NotepadResult<()>;
+    // 注销并清除本地保存的令牌
+    fn logout(&mut self) -> NotepadResult<()>;
+}
+
+impl AccountService for AppState {
+    fn account(&self) -> Option<&Account> {
+        self.account.as_ref()
+    }
+
+    fn login(&mut self, server: &str, username: &str, password: &str) -> NotepadResult<()> {
+        self.ensure_plaintext_remote()?;
+        let response = HttpRemote::login(server, username, password).map_err(NotepadError::remote)?;
+        let account = Account {
+            server: server.trim_end_matches('/').to_string(),
+            username: response.username,
+            token: response.token,
+        };
+
+        let mut conn = self.db_conn.lock().unwrap();
+        // 更换同步目标后需要重新比较全部笔记
+        conn.reset_sync_state(SyncScope::Remote)?;
+        conn.set_sync_meta("server", Some(&account.server))?;
+        conn.set_sync_meta("username", Some(&account.username))?;
+        conn.set_sync_meta("token", Some(&account.token))?;
+        drop(conn);
+        self.notify_success("login", format!("Logged in to {} as {}", account.server, account.username));
+        self.account = Some(account);
+        self.last_sync_report = None;
+        Ok(())
+    }
+
+    fn logout(&mut self) -> NotepadResult<()> {
+        if let Some(account) = self.account.take() {
+            // 服务端不可达时也允许本地注销
+            if let Err(e) = HttpRemote::new(&account.server, &account.token).logout() {
+                self.notify_warning("logout", None, format!("Failed to revoke token on {}: {}", account.server, e));
+            }
+        }
+        let mut conn = self.db_conn.lock().unwrap();
+        conn.reset_sync_state(SyncScope::Remote)?;
+        for key in ["server", "username", "token"] {
+            conn.set_sync_meta(key, None)?;
+        }
+        self.last_sync_report = None;
+        Ok(())
+    }
+}
+
+impl AppState {
+    // 读取本地保存的登录信息
+    pub(super) fn load_account(db: &crate::data::Database) -> NotepadResult<Option<Account>> {
+        let (Some(server), Some(username), Some(token)) = (
+            db.sync_meta("server")?,
+            db.sync_meta("username")?,
+            db.sync_meta("token")?,
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(Account { server, username, token }))
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::error::{NotepadError, NotepadResult};
+use crate::i18n::Language;
+use crate::io::{BackupData, BackupManifest, BackupNote, DEFAULT_FILENAME_TEMPLATE, ExportFormat, Exporter, ImportSource, Importer, MergeStrategy, PdfConfig, RestoreMode};
+use super::{AppState, NotifyService, TabService, Theme};
+
+pub trait BackupService {
+    // 创建完整备份(笔记、回收站、版本历史、附件和设置)
+    fn create_backup(&self, output_path: &Path) -> NotepadResult<BackupManifest>;
+    // 读取备份清单(恢复前确认)
+    fn read_backup_manifest(&self, input_path: &Path) -> NotepadResult<BackupManifest>;
+    // 从备份恢复, 替换当前保险库或合并到当前保险库
+    fn restore_backup(&mut self, input_path: &Path, mode: RestoreMode) -> NotepadResult<()>;
+}
+
+// 备份中保存的设置(不包含备份口令和附件目录等本机路径)
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupSettings {
+    theme: Theme,
+    language: Language,
+    export_format: ExportFormat,
+    #[serde(default)]
+    pdf: PdfConfig,
+    #[serde(default = "default_filename_template")]
+    filename_template: String,
+    merge_strategy: MergeStrategy,
+    preserve_timestamps: bool,
+    recursive: bool,
+    folder_tags: bool,
+    include: String,
+    exclude: String,
+    source: ImportSource,
+}
+
+impl BackupService for AppState {
+    fn create_backup(&self, output_path: &Path) -> NotepadResult<BackupManifest> {
+        // 先保存正在编辑的笔记
+        self.flush_modified_note(None)?;
+        let db = self.db_conn.lock().unwrap();
+        let data = BackupData {
+            notes: db.load_notes_with_trash()?
+                .into_iter()
+                .map(|(note, trashed)| BackupNote { note, trashed })
+                .collect(),
+            versions: self.versions().load_all(&db)?,
+        };
+        let settings = serde_json::to_value(self.backup_settings()).map_err(NotepadError::format)?;
+        let manifest = Exporter::export_backup(
+            &data,
+            &settings,
+            &self.import_config.attachments_dir,
+            output_path,
+            |json| db.seal_backup(json),
+        )?;
+        drop(db);
+        self.notify_success("create_backup", format!(
+            "Backed up {} notes, {} in trash, {} versions and {} attachments to {}",
+            manifest.notes, manifest.trashed, manifest.versions, manifest.attachments, output_path.display(),
+        ));
+        Ok(manifest)
+    }
+
+    fn read_backup_manifest(&self, input_path: &Path) -> NotepadResult<BackupManifest> {
+        Importer::read_backup_manifest(input_path)
+    }
+
+    fn restore_backup(&mut self, input_path: &Path, mode: RestoreMode) -> NotepadResult<()> {
+        self.flush_modified_note(None)?;
+        let (data, settings) = Importer::import_backup(input_path, &self.import_config)?;
+        let notes: Vec<_> = data.notes.into_iter().map(|n| (n.note, n.trashed)).collect();
+        let replace = mode == RestoreMode::Replace;
+
+        let (written, notebook) = {
+            let mut db = self.db_conn.lock().unwrap();
+            (self.versions().restore_notes(&mut db, &notes, &data.versions, replace)?, db.load_all_notes()?)
+        };
+        *self.notebook.lock().unwrap() = notebook;
+        self.report_quarantined();
+        // 已解锁笔记的密钥可能已失效, 关闭不存在的笔记
+        self.unlocked_notes.clear();
+        let closed: Vec<String> = {
+            let notebook = self.notebook.lock().unwrap();
+            self.recent_notes().into_iter()
+                .filter(|id| notebook.find_note(id).is_none())
+                .cloned()
+                .collect()
+        };
+        for id in closed {
+            self.close_note(&id);
+        }
+
+        // 附件恢复到本机的附件目录, 按恢复方式覆盖或跳过已存在的文件; 替换时同时恢复设置
+        let attachments = Importer::import_backup_attachments(input_path, &self.import_config, replace)?;
+        if replace && !settings.is_null() {
+            match serde_json::from_value(settings) {
+                Ok(settings) => self.apply_backup_settings(settings),
+                Err(e) => self.notify_warning("restore_backup", None, format!("Skipped invalid settings in backup: {}", e)),
+            }
+        }
+        self.notify_success("restore_backup", format!(
+            "Restored {} notes and {} attachments from {}", written, attachments, input_path.display(),
+        ));
+        Ok(())
+    }
+}
+
+fn default_filename_template() -> String {
+    DEFAULT_FILENAME_TEMPLATE.to_string()
+}
+
+impl AppState {
+    fn backup_settings(&self) -> BackupSettings {
+        BackupSettings {
+            theme: self.theme,
+            language: self.language,
+            export_format: self.export_config.format.clone(),
+            pdf: self.export_config.pdf.clone(),
+            filename_template: self.export_config.filename_template.clone(),
+            merge_strategy: self.import_config.merge_strategy.clone(),
+            preserve_timestamps: self.import_config.preserve_timestamps,
+            recursive: self.import_config.recursive,
+            folder_tags: self.import_config.folder_tags,
+            include: self.import_config.include.clone(),
+            exclude: self.import_config.exclude.clone(),
+            source: self.import_config.source,
+        }
+    }
+
+    fn apply_backup_settings(&mut self, settings: BackupSettings) {
+        self.theme = settings.theme;
+        self.language = settings.language;
+        self.export_config.format = settings.export_format;
+        self.export_config.pdf = settings.pdf;
+        self.export_config.filename_template = settings.filename_template;
+        self.import_config.merge_strategy = settings.merge_strategy;
+        self.import_config.preserve_timestamps = settings.preserve_timestamps;
+        self.import_config.recursive = settings.recursive;
+        self.import_config.folder_tags = settings.folder_tags;
+        self.import_config.include = settings.include;
+        self.import_config.exclude = settings.exclude;
+        self.import_config.source = settings.source;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{Database, Note, NoteVersion};
+    use crate::services::{NoteService, TrashService, VersionService};
+    use super::*;
+
+    #[test]
+    fn test_backup_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("attachments/n1")).unwrap();
+        std::fs::write(dir.join("attachments/n1/a.png"), [1u8, 2, 3]).unwrap();
+        let mut state = AppState::with_database(Database::open(dir.join("a.db")).unwrap()).unwrap();
+        state.import_config.attachments_dir = dir.join("attachments");
+        state.theme = Theme::Light;
+
+        let mut kept = Note::new("kept".to_string());
+        kept.update_content("v2".to_string());
+        kept.add_tag("keep".to_string());
+        let trashed = Note::new("trashed".to_string());
+        for note in [&kept, &trashed] {
+            state.save_note(note).unwrap();
+            state.notebook.lock().unwrap().insert_or_replace_note(note.clone());
+        }
+        state.db_conn.lock().unwrap().save_version(&NoteVersion::new("first", &kept)).unwrap();
+        state.move_to_trash(trashed.id()).unwrap();
+
+        let backup = dir.join("vault.cnbackup");
+        let manifest = state.create_backup(&backup).unwrap();
+        assert_eq!((manifest.notes, manifest.trashed, manifest.versions, manifest.attachments), (1, 1, 1, 1));
+        assert!(!manifest.encrypted);
+
+        // 恢复到新的保险库
+        let mut restored = AppState::with_database(Database::open(dir.join("b.db")).unwrap()).unwrap();
+        restored.import_config.attachments_dir = dir.join("restored");
+        let other = Note::new("other".to_string());
+        restored.save_note(&other).unwrap();
+        restored.restore_backup(&backup, RestoreMode::Merge).unwrap();
+        assert!(restored.get_note(other.id()).is_some());
+        assert_eq!(restored.theme, Theme::Dark);
+        assert_eq!(std::fs::read(dir.join("restored/n1/a.png")).unwrap(), [1u8, 2, 3]);
+
+        restored.restore_backup(&backup, RestoreMode::Replace).unwrap();
+        assert!(restored.get_note(other.id()).is_none());
+        let note = restored.get_note(kept.id()).unwrap();
+        assert!(note.tags().contains("keep") && note.content() == "v2");
+        assert_eq!(restored.get_deleted_notes().unwrap().len(), 1);
+        assert_eq!(restored.list_versions(kept.id()).unwrap().len(), 1);
+        assert_eq!(restored.theme, Theme::Light);
+        // 附件目录是本机路径, 不随设置恢复
+        assert_eq!(restored.import_config.attachments_dir, dir.join("restored"));
+        drop((state, restored));
+    }
+}
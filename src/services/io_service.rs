@@ -1,19 +1,27 @@
 use std::path::Path;
-use anyhow::Result;
-use crate::data::{Note, ExportNote};
-use crate::io::{ExportConfig, Exporter, ImportConfig, ImportResult, Importer, MergeStrategy};
+use crate::data::{Database, ExportNote, Note, NoteMerge, Notebook, VersionStore};
+use crate::error::{NotepadError, NotepadResult};
+use crate::io::{Attachment, ExportConfig, ExportFormat, Exporter, ImportCandidate, ImportConfig, ImportConflict, ImportPlan, ImportReport, ImportStatus, Importer, MergeStrategy};
 use super::AppState;
-use super::NoteService;
+use super::{NoteService, NotifyService, merge_service::{PreparedMerge, prepare_merge}};
 
 pub trait IoService {
     fn export_config(&self) -> &ExportConfig;
     fn import_config(&self) -> &ImportConfig;
     // 导出笔记
-    fn export_note(&self, note_id: &str, output_path: &Path) -> Result<()>;
+    fn export_note(&self, note_id: &str, output_path: &Path) -> NotepadResult<()>;
     // 导出所有笔记
-    fn export_all_notes(&self, output_dir: &Path) -> Result<()>;
-    // 导入笔记
-    fn import(&mut self, input_path: &Path) -> Result<usize>;
+    fn export_all_notes(&self, output_dir: &Path) -> NotepadResult<()>;
+    // 导入笔记(冲突按全局策略处理)，返回每个笔记的处理结果
+    fn import(&mut self, input_path: &Path) -> NotepadResult<&ImportReport>;
+    // 解析待导入的笔记并检测冲突(不写入)
+    fn preview_import(&self, input_path: &Path) -> NotepadResult<ImportPlan>;
+    // 按每个笔记选择的处理方式在同一事务中导入
+    fn commit_import(&mut self, plan: ImportPlan) -> NotepadResult<&ImportReport>;
+    // 最近一次导入的报告
+    fn last_import_report(&self) -> Option<&ImportReport>;
+    // 导出最近一次导入的报告
+    fn export_import_report(&self, output_path: &Path) -> NotepadResult<()>;
 }
 
 impl IoService for AppState {
@@ -25,67 +33,324 @@ impl IoService for AppState {
         &self.import_config
     }
 
-    fn export_note(&self, note_id: &str, output_path: &Path) -> Result<()> {
+    fn export_note(&self, note_id: &str, output_path: &Path) -> NotepadResult<()> {
         if let Some(note) = self.get_note(note_id) {
+            if note.is_sealed() {
+                return Err(NotepadError::Locked(note.title().to_string()));
+            }
             let export_note = ExportNote::from_note(&note);
-            Exporter::export_note(&export_note, output_path, &self.export_config)
+            Exporter::export_note(&export_note, output_path, &self.export_config)?;
+            self.notify_success("export_note", format!("Exported {} to {}", note.title(), output_path.display()));
+            Ok(())
+        } else {
+            Err(NotepadError::NotFound(note_id.to_string()))
+        }
+    }
+
+    fn export_all_notes(&self, output_dir: &Path) -> NotepadResult<()> {
+        let (count, encrypted) = {
+            let conn = self.db_conn.lock().unwrap();
+            export_notebook(&conn, &self.notebook.lock().unwrap(), output_dir, &self.export_config)?
+        };
+        if encrypted {
+            self.notify_success("export_all_notes", format!("Exported encrypted backup of {} notes", count));
+        } else {
+            self.notify_success("export_all_notes", format!("Exported {} notes to {}", count, output_dir.display()));
+        }
+        Ok(())
+    }
+
+    fn import(&mut self, input_path: &Path) -> NotepadResult<&ImportReport> {
+        let plan = self.preview_import(input_path)?;
+        self.commit_import(plan)
+    }
+
+    fn preview_import(&self, input_path: &Path) -> NotepadResult<ImportPlan> {
+        let trashed: Vec<Note> = self.db_conn.lock().unwrap().load_notes_with_trash()?.into_iter()
+            .filter_map(|(note, deleted)| deleted.then_some(note))
+            .collect();
+        plan_import(input_path, &self.import_config, &self.filter_notes("")?, &trashed)
+    }
+
+    fn commit_import(&mut self, plan: ImportPlan) -> NotepadResult<&ImportReport> {
+        let mut report = plan.report;
+        let ResolvedImport { notes, merges, attachments } = {
+            let conn = self.db_conn.lock().unwrap();
+            let notebook = self.notebook.lock().unwrap();
+            resolve_plan(&plan.candidates, &mut report, &conn, self.versions(), &notebook)
+        };
+
+        // 所有笔记在同一个事务中写入
+        let sealed = notes.iter()
+            .map(|note| self.seal_note(note).map(|note| note.into_owned()))
+            .collect::<NotepadResult<Vec<_>>>()?;
+        self.db_conn.lock().unwrap().save_notes(&sealed)?;
+        let mut notebook = self.notebook.lock().unwrap();
+        for note in notes {
+            notebook.insert_or_replace_note(note);
+        }
+        drop(notebook);
+        for merge in merges {
+            self.push_pending_merge(merge);
+        }
+        copy_attachments(&plan.candidates, attachments, &self.import_config, &mut report);
+
+        if report.failed() > 0 {
+            self.notify_warning("import", None, report.summary());
         } else {
-            Err(anyhow::anyhow!("The note id is not existed: {}", note_id))
+            self.notify_success("import", report.summary());
         }
+        Ok(self.last_import_report.insert(report))
+    }
+
+    fn last_import_report(&self) -> Option<&ImportReport> {
+        self.last_import_report.as_ref()
     }
 
-    fn export_all_notes(&self, output_dir: &Path) -> Result<()> {
-        let notes = self.get_all_notes_for_export()?;
-        Exporter::export_all(notes, output_dir, &self.export_config)
+    fn export_import_report(&self, output_path: &Path) -> NotepadResult<()> {
+        let report = self.last_import_report.as_ref()
+            .ok_or_else(|| NotepadError::Invalid("No import report to export".to_string()))?;
+        Exporter::export_import_report(report, output_path)?;
+        self.notify_success("export_import_report", format!("Exported import report to {}", output_path.display()));
+        Ok(())
     }
+}
 
-    fn import(&mut self, input_path: &Path) -> Result<usize> {
-        let results = Importer::import(input_path, &self.import_config)?;
-        self.save_imported_notes(results)
+// 导出全部笔记(不含未解锁的加锁笔记), 返回导出的笔记数和是否导出为加密备份
+pub(crate) fn export_notebook(db: &Database, notebook: &Notebook, output_dir: &Path, config: &ExportConfig) -> NotepadResult<(usize, bool)> {
+    let notes: Vec<ExportNote> = notebook.filter_notes("").into_iter()
+        .filter(|note| !note.is_sealed())
+        .map(ExportNote::from_note)
+        .collect();
+    let count = notes.len();
+    if config.format == ExportFormat::Json {
+        // 启用保险库时备份同样加密
+        let json = serde_json::to_string_pretty(&notes).map_err(NotepadError::format)?;
+        if let Some(backup) = db.seal_backup(&json)? {
+            Exporter::export_encrypted_backup(&backup, output_dir)?;
+            return Ok((count, true));
+        }
     }
+    if config.format == ExportFormat::Html {
+        Exporter::export_site(&notes, &notebook.get_tags_with_count(), output_dir)?;
+    } else {
+        Exporter::export_all(notes, output_dir, config)?;
+    }
+    Ok((count, false))
 }
 
-impl AppState {
-    fn get_all_notes_for_export(&self) -> Result<Vec<ExportNote>> {
-        Ok(
-            self.filter_notes("")?.iter()
-            .map(|note| ExportNote::from_note(note))
-            .collect()
-        )
-    }
-
-    fn save_imported_notes(&mut self, results: Vec<ImportResult>) -> Result<usize> {
-        let mut count = 0;
-        for result in results.iter() {
-            let note = result.note().to_note();
-            if let Err(e) = self.save_imported_note(&note) {
-                eprintln!("Failed to import note: {}", e);
+// 解析待导入的笔记并与已有笔记(及回收站中的笔记)比较, 检测冲突(不写入)
+pub(crate) fn plan_import(input_path: &Path, config: &ImportConfig, notes: &[Note], trashed: &[Note]) -> NotepadResult<ImportPlan> {
+    let mut report = ImportReport::new(input_path);
+    let results = Importer::import(input_path, config, &mut report)?;
+
+    let candidates = results.into_iter()
+        .map(|result| {
+            let incoming = result.note().to_note();
+            let (conflict, existing) = if let Some(n) = notes.iter().find(|n| n.id() == incoming.id()) {
+                (Some(ImportConflict::SameId(n.id().to_string())), Some(n.clone()))
+            } else if let Some(n) = trashed.iter().find(|n| n.id() == incoming.id()) {
+                (Some(ImportConflict::Trashed(n.id().to_string())), Some(n.clone()))
             } else {
-                count += 1;
+                match notes.iter().find(|n| n.title() == incoming.title() && n.content() != incoming.content()) {
+                    Some(n) => (Some(ImportConflict::SameTitle(n.id().to_string())), Some(n.clone())),
+                    None => (None, None),
+                }
+            };
+            // 同名笔记默认作为新笔记导入(保留两者)
+            let strategy = match conflict {
+                Some(ImportConflict::SameTitle(_)) => MergeStrategy::Rename,
+                _ => config.merge_strategy.clone(),
+            };
+            ImportCandidate { result, conflict, existing, strategy }
+        })
+        .collect();
+    Ok(ImportPlan { report, candidates })
+}
+
+// 按处理方式确定导入结果(不写入)并记录到报告中
+pub(crate) struct ResolvedImport<'a> {
+    pub notes: Vec<Note>,                           // 需要写入的笔记
+    pub merges: Vec<NoteMerge>,                     // 需要手动处理冲突的合并
+    pub attachments: Vec<(usize, &'a Attachment)>,  // 写入后复制的附件(及对应的报告记录)
+}
+
+pub(crate) fn resolve_plan<'a>(
+    candidates: &'a [ImportCandidate],
+    report: &mut ImportReport,
+    db: &Database,
+    versions: VersionStore,
+    notebook: &Notebook,
+) -> ResolvedImport<'a> {
+    let mut resolved = ResolvedImport { notes: Vec::new(), merges: Vec::new(), attachments: Vec::new() };
+    for candidate in candidates.iter() {
+        let mut note = candidate.result.note().to_note();
+        let status = match resolve_import(candidate, &mut note, db, versions, notebook) {
+            Ok((status, PreparedImport::Write)) => {
+                resolved.notes.push(note.clone());
+                resolved.attachments.extend(candidate.result.attachments().iter().map(|a| (report.entries.len(), a)));
+                status
             }
-        }
+            Ok((status, PreparedImport::Merge(merge))) => {
+                resolved.merges.push(*merge);
+                status
+            }
+            Ok((status, PreparedImport::None)) => status,
+            Err(e) => {
+                crate::log_error!("import", note = note.id(); "failed to import note {}: {}", note.title(), e);
+                ImportStatus::Failed(e.to_string())
+            }
+        };
+        report.push_result(&candidate.result, Some(note.id().to_string()), status);
+    }
+    resolved
+}
 
-        Ok(count)
+// 笔记写入后复制附件, 失败时记录到对应笔记的警告中
+pub(crate) fn copy_attachments(candidates: &[ImportCandidate], attachments: Vec<(usize, &Attachment)>, config: &ImportConfig, report: &mut ImportReport) {
+    for (idx, attachment) in attachments {
+        let target = config.attachments_dir.join(&attachment.target);
+        let copied = target.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::copy(&attachment.source, &target));
+        if let Err(e) = copied {
+            report.entries[idx].warnings.push(format!("failed to copy attachment {}: {}", attachment.source.display(), e));
+        }
+    }
+    // 解码或解包出的临时文件(包括跳过的笔记的)
+    for attachment in candidates.iter().flat_map(|c| c.result.attachments()) {
+        if attachment.temporary {
+            let _ = std::fs::remove_file(&attachment.source);
+        }
     }
+}
 
-    // 导入笔记
-    fn save_imported_note(&mut self, note: &Note) -> Result<()> {
-        let mut note = note.clone();
-        if let Some(_) = self.get_note(note.id()) {
-            match self.import_config.merge_strategy {
-                MergeStrategy::Skip => return Ok(()),
-                MergeStrategy::Rename => {
-                    *note.id_mut() = uuid::Uuid::new_v4().to_string();
+// 按选择的处理方式确定导入结果(不写入数据库, 可能修改笔记id)
+fn resolve_import(
+    candidate: &ImportCandidate,
+    note: &mut Note,
+    db: &Database,
+    versions: VersionStore,
+    notebook: &Notebook,
+) -> NotepadResult<(ImportStatus, PreparedImport)> {
+    let Some(conflict) = &candidate.conflict else {
+        return Ok((ImportStatus::Imported, PreparedImport::Write));
+    };
+    match (conflict, &candidate.strategy) {
+        (_, MergeStrategy::Skip) => Ok((ImportStatus::Skipped, PreparedImport::None)),
+        // 同名笔记本身id不同, 直接作为新笔记导入
+        (ImportConflict::SameTitle(_), MergeStrategy::Rename) => Ok((ImportStatus::Imported, PreparedImport::Write)),
+        (ImportConflict::SameId(_) | ImportConflict::Trashed(_), MergeStrategy::Rename) => {
+            *note.id_mut() = uuid::Uuid::new_v4().to_string();
+            Ok((ImportStatus::Renamed, PreparedImport::Write))
+        }
+        (_, MergeStrategy::Overwrite) => {
+            *note.id_mut() = conflict.existing_id().to_string();
+            Ok((ImportStatus::Overwritten, PreparedImport::Write))
+        }
+        (_, MergeStrategy::Merge) => {
+            *note.id_mut() = conflict.existing_id().to_string();
+            // 与回收站中的笔记合并后, 合并结果从回收站中恢复
+            let ours = notebook.find_note(note.id()).or_else(|| candidate.existing.clone());
+            if ours.as_ref().is_some_and(|n| n.is_sealed()) {
+                return Err(NotepadError::Locked(note.title().to_string()));
+            }
+            match prepare_merge(db, versions, ours, note.clone())? {
+                PreparedMerge::Unchanged => Ok((ImportStatus::Merged, PreparedImport::None)),
+                PreparedMerge::Merged(merged) => {
+                    *note = *merged;
+                    Ok((ImportStatus::Merged, PreparedImport::Write))
                 }
-                _ => (),
-            };
+                PreparedMerge::Conflicted(merge) => Ok((ImportStatus::Conflicted, PreparedImport::Merge(merge))),
+            }
         }
+    }
+}
 
-        // 先保存笔记到数据库中
-        self.save_note(&note)?;
-        // 再更新内存中笔记信息
-        let mut notebook = self.notebook.lock().unwrap();
-        notebook.insert_or_replace_note(note);
-        Ok(())
+// 导入单个笔记需要执行的写入
+enum PreparedImport {
+    None,
+    Write,
+    Merge(Box<NoteMerge>),
+}
+#[cfg(test)]
+mod tests {
+    use crate::data::Database;
+    use crate::services::TrashService;
+    use super::*;
+
+    #[test]
+    fn test_import_report() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("import");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.md"), "---\ntitle: A\ncreated: yesterday\n---\nbody").unwrap();
+        std::fs::write(dir.join("b.json"), "{ not json").unwrap();
+        let path = temp.path().join("notes.db");
+        let mut state = AppState::with_database(Database::open(&path).unwrap()).unwrap();
+
+        let report = state.import(&dir).unwrap();
+        assert_eq!(report.count(&ImportStatus::Imported), 1);
+        assert_eq!(report.failed(), 1);
+        let entry = report.entries.iter().find(|e| e.status == ImportStatus::Imported).unwrap();
+        assert_eq!(entry.title.as_deref(), Some("A"));
+        assert_eq!(entry.warnings.len(), 1);
+
+        // 报告可以导出为JSON
+        let json = serde_json::to_value(report).unwrap();
+        assert!(json["entries"].as_array().unwrap().iter().any(|e| e["status"] == "failed" && e["reason"].is_string()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_preview_and_commit_import() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("import");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = temp.path().join("notes.db");
+        let mut state = AppState::with_database(Database::open(&path).unwrap()).unwrap();
+        let mut existing = Note::new("plan".to_string());
+        existing.update_content("old".to_string());
+        state.save_note(&existing).unwrap();
+        state.notebook.lock().unwrap().insert_or_replace_note(existing.clone());
+
+        std::fs::write(dir.join("plan.md"), "new").unwrap();
+        std::fs::write(dir.join("other.md"), "other").unwrap();
+        let mut plan = state.preview_import(&dir).unwrap();
+        // 预览阶段不写入
+        assert_eq!(state.filter_notes("").unwrap().len(), 1);
+        assert_eq!(plan.conflicts(), 1);
+        let candidate = plan.candidates.iter_mut().find(|c| c.conflict.is_some()).unwrap();
+        assert_eq!(candidate.conflict, Some(ImportConflict::SameTitle(existing.id().to_string())));
+        candidate.strategy = MergeStrategy::Overwrite;
+
+        let report = state.commit_import(plan).unwrap();
+        assert_eq!(report.count(&ImportStatus::Overwritten), 1);
+        assert_eq!(report.count(&ImportStatus::Imported), 1);
+        assert_eq!(state.get_note(existing.id()).unwrap().content(), "new");
+        let stored = Database::open(&path).unwrap().load_all_notes().unwrap();
+        assert_eq!(stored.find_note(existing.id()).unwrap().content(), "new");
+    }
+
+    #[test]
+    fn test_import_conflicts_with_trashed_note() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let mut state = AppState::in_memory().unwrap();
+        let mut note = Note::new("plan".to_string());
+        note.update_content("old".to_string());
+        state.update_note(note.clone()).unwrap();
+        state.save_note(&note).unwrap();
+        state.export_config.format = ExportFormat::Json;
+        state.export_note(note.id(), &dir.join("plan.json")).unwrap();
+        state.move_to_trash(note.id()).unwrap();
+
+        // 回收站中的同id笔记报告为冲突, 跳过时保持不变
+        let mut plan = state.preview_import(&dir.join("plan.json")).unwrap();
+        assert_eq!(plan.candidates[0].conflict, Some(ImportConflict::Trashed(note.id().to_string())));
+        assert_eq!(plan.candidates[0].existing.as_ref().unwrap().content(), "old");
+        plan.candidates[0].strategy = MergeStrategy::Skip;
+        state.commit_import(plan).unwrap();
+        assert!(state.get_note(note.id()).is_none());
+        assert_eq!(state.get_deleted_notes().unwrap().len(), 1);
+    }
+}
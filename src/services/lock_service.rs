@@ -0,0 +1,226 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+use crate::data::{Note, NoteVersion};
+use crate::error::{NotepadError, NotepadResult};
+use crate::utils::crypto::{self, VaultHeader};
+use super::{AppState, NoticeKind};
+use super::{NoteService, NotifyService};
+
+// 无操作超过该时长后自动重新锁定已解锁的笔记
+const RELOCK_AFTER: Duration = Duration::from_secs(5 * 60);
+
+pub trait LockService {
+    // 为笔记设置密码(加密内容和历史版本)
+    fn lock_note(&mut self, note_id: &str, password: &str) -> NotepadResult<()>;
+    // 移除笔记密码(解密内容和历史版本)
+    fn remove_note_lock(&mut self, note_id: &str, password: &str) -> NotepadResult<()>;
+    // 在本次会话中解锁笔记
+    fn unlock_note(&mut self, note_id: &str, password: &str) -> NotepadResult<()>;
+    // 立即重新锁定笔记
+    fn relock_note(&mut self, note_id: &str) -> NotepadResult<()>;
+    // 记录用户操作(用于空闲检测)
+    fn touch_activity(&mut self);
+    // 空闲超时后重新锁定所有笔记，返回距下次超时的时长
+    fn relock_idle_notes(&mut self) -> NotepadResult<Option<Duration>>;
+}
+
+impl LockService for AppState {
+    fn lock_note(&mut self, note_id: &str, password: &str) -> NotepadResult<()> {
+        if password.is_empty() {
+            return Err(NotepadError::Invalid("The password must not be empty".to_string()));
+        }
+        let Some(mut note) = self.get_note(note_id) else {
+            return Err(NotepadError::NotFound(note_id.to_string()));
+        };
+        if note.is_locked() {
+            return Err(NotepadError::Invalid("The note is already locked".to_string()));
+        }
+        // 仓库中的版本历史不能改写(加锁后仍会保留明文)
+        if self.git_history.is_some() {
+            return Err(NotepadError::Invalid("Disable git version history before setting a note password".to_string()));
+        }
+        self.flush_modified_note(None)?;
+
+        let (header, cipher) = VaultHeader::create(password).map_err(NotepadError::crypto)?;
+        note.set_lock(Some(header));
+        self.unlocked_notes.insert(note_id.to_string(), cipher.clone());
+        self.save_note(&note)?;
+        self.versions().map_contents(&mut self.db_conn.lock().unwrap(), note_id, |content| {
+            Ok(if crypto::is_locked_text(&content) { content } else { cipher.lock_text(&content) })
+        })?;
+        // 内存中保留明文，直到重新锁定
+        self.notebook.lock().unwrap().insert_or_replace_note(note);
+        self.notify(NoticeKind::Success, "lock_note", Some(note_id), "Note password set".to_string());
+        Ok(())
+    }
+
+    fn remove_note_lock(&mut self, note_id: &str, password: &str) -> NotepadResult<()> {
+        let Some(mut note) = self.get_note(note_id) else {
+            return Err(NotepadError::NotFound(note_id.to_string()));
+        };
+        let Some(header) = note.lock() else {
+            return Err(NotepadError::Invalid("The note is not locked".to_string()));
+        };
+        let cipher = header.unlock(password).map_err(|e| NotepadError::Locked(e.to_string()))?;
+        if self.git_history.is_some() {
+            return Err(NotepadError::Invalid("Disable git version history before removing a note password".to_string()));
+        }
+        self.flush_modified_note(None)?;
+
+        if note.is_sealed() {
+            note.set_content(cipher.unlock_text(note.content()).map_err(NotepadError::crypto)?);
+        }
+        note.set_lock(None);
+        self.unlocked_notes.remove(note_id);
+        self.save_note(&note)?;
+        self.versions().map_contents(&mut self.db_conn.lock().unwrap(), note_id, |content| {
+            if crypto::is_locked_text(&content) { cipher.unlock_text(&content).map_err(NotepadError::crypto) } else { Ok(content) }
+        })?;
+        self.notebook.lock().unwrap().insert_or_replace_note(note);
+        self.notify(NoticeKind::Success, "remove_note_lock", Some(note_id), "Note password removed".to_string());
+        Ok(())
+    }
+
+    fn unlock_note(&mut self, note_id: &str, password: &str) -> NotepadResult<()> {
+        let Some(mut note) = self.get_note(note_id) else {
+            return Err(NotepadError::NotFound(note_id.to_string()));
+        };
+        let Some(header) = note.lock() else {
+            return Ok(());
+        };
+        let cipher = header.unlock(password).map_err(|e| {
+            crate::log_warn!("unlock_note", note = note_id; "wrong note password");
+            NotepadError::Locked(e.to_string())
+        })?;
+        if note.is_sealed() {
+            note.set_content(cipher.unlock_text(note.content()).map_err(NotepadError::crypto)?);
+            self.notebook.lock().unwrap().insert_or_replace_note(note);
+        }
+        self.unlocked_notes.insert(note_id.to_string(), cipher);
+        self.last_activity = Instant::now();
+        Ok(())
+    }
+
+    fn relock_note(&mut self, note_id: &str) -> NotepadResult<()> {
+        // 先以密文保存未保存的修改
+        self.flush_modified_note(None)?;
+        if let Some(cipher) = self.unlocked_notes.remove(note_id)
+            && let Some(mut note) = self.get_note(note_id)
+            && !note.is_sealed() {
+            note.set_content(cipher.lock_text(note.content()));
+            self.notebook.lock().unwrap().insert_or_replace_note(note);
+        }
+        Ok(())
+    }
+
+    fn touch_activity(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    fn relock_idle_notes(&mut self) -> NotepadResult<Option<Duration>> {
+        if self.unlocked_notes.is_empty() {
+            return Ok(None);
+        }
+        let idle = self.last_activity.elapsed();
+        if idle < RELOCK_AFTER {
+            return Ok(Some(RELOCK_AFTER - idle));
+        }
+        let note_ids: Vec<String> = self.unlocked_notes.keys().cloned().collect();
+        for note_id in note_ids.iter() {
+            self.relock_note(note_id)?;
+        }
+        self.notify(
+            NoticeKind::Info,
+            "relock_idle_notes",
+            None,
+            format!("Locked {} notes after {} minutes of inactivity", note_ids.len(), idle.as_secs() / 60),
+        );
+        Ok(None)
+    }
+}
+
+impl AppState {
+    // 写入数据库前加密已解锁笔记的内容
+    pub(super) fn seal_note<'a>(&self, note: &'a Note) -> NotepadResult<Cow<'a, Note>> {
+        if !note.is_locked() || note.is_sealed() {
+            return Ok(Cow::Borrowed(note));
+        }
+        let Some(cipher) = self.unlocked_notes.get(note.id()) else {
+            return Err(NotepadError::Locked(note.title().to_string()));
+        };
+        let mut note = note.clone();
+        note.set_content(cipher.lock_text(note.content()));
+        Ok(Cow::Owned(note))
+    }
+
+    // 解密已解锁笔记的历史版本
+    pub(super) fn open_versions(&self, note_id: &str, versions: Vec<NoteVersion>) -> Vec<NoteVersion> {
+        let Some(cipher) = self.unlocked_notes.get(note_id) else {
+            return versions;
+        };
+        versions.into_iter()
+            .map(|mut version| {
+                if let Ok(content) = cipher.unlock_text(version.content()) {
+                    version.set_content(content);
+                }
+                version
+            })
+            .collect()
+    }
+
+    // 重新加载笔记后恢复本次会话中已解锁笔记的明文
+    pub(super) fn reveal_unlocked_notes(&self) {
+        let mut notebook = self.notebook.lock().unwrap();
+        for (note_id, cipher) in self.unlocked_notes.iter() {
+            if let Some(mut note) = notebook.find_note(note_id)
+                && note.is_sealed()
+                && let Ok(content) = cipher.unlock_text(note.content()) {
+                note.set_content(content);
+                notebook.insert_or_replace_note(note);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::Database;
+    use crate::services::VersionService;
+    use super::*;
+
+    #[test]
+    fn test_lock_and_unlock_note() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("notes.db");
+        let mut state = AppState::with_database(Database::open(&path).unwrap()).unwrap();
+        let mut note = Note::new("bank".to_string());
+        note.update_content("pin: 1234".to_string());
+        let id = note.id().to_string();
+        state.update_note(note.clone()).unwrap();
+        state.save_note(&note).unwrap();
+        state.save_version("first", &note).unwrap();
+
+        state.lock_note(&id, "pw").unwrap();
+        assert!(state.filter_notes("1234").unwrap().is_empty());
+        assert_eq!(state.filter_notes("bank").unwrap().len(), 1);
+
+        // 数据库中的内容和历史版本均为密文
+        let stored = Database::open(&path).unwrap().load_all_notes().unwrap().find_note(&id).unwrap();
+        assert!(stored.is_sealed() && !stored.content().contains("1234"));
+        let versions = Database::open(&path).unwrap().load_version_history(&id).unwrap();
+        assert!(!versions[0].content().contains("1234"));
+
+        state.relock_note(&id).unwrap();
+        assert!(state.get_note(&id).unwrap().is_sealed());
+        assert!(state.unlock_note(&id, "wrong").is_err());
+        state.unlock_note(&id, "pw").unwrap();
+        assert_eq!(state.get_note(&id).unwrap().content(), "pin: 1234");
+        assert_eq!(state.list_versions(&id).unwrap()[0].content(), "pin: 1234");
+
+        state.remove_note_lock(&id, "pw").unwrap();
+        let stored = Database::open(&path).unwrap().load_all_notes().unwrap().find_note(&id).unwrap();
+        assert!(!stored.is_locked());
+        assert_eq!(stored.content(), "pin: 1234");
+        drop(state);
+    }
+}
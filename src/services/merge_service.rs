@@ -0,0 +1,123 @@
+use crate::data::{Database, Note, NoteMerge, HunkResolution, VersionStore};
+use crate::error::{NotepadError, NotepadResult};
+use super::AppState;
+use super::NoteService;
+
+/// 合并结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOutcome {
+    Unchanged,      // 两个副本内容一致
+    Merged,         // 自动合并成功并已保存
+    Conflicted,     // 存在冲突，已加入待处理列表
+}
+
+pub trait MergeService {
+    // 将对方副本合并到本地笔记
+    fn merge_note(&mut self, theirs: Note) -> NotepadResult<MergeOutcome>;
+    // 待处理(有冲突)的合并
+    fn pending_merges(&self) -> &[NoteMerge];
+    // 处理某个合并中的冲突片段
+    fn resolve_hunk(&mut self, merge_idx: usize, hunk_idx: usize, resolution: HunkResolution);
+    // 应用已处理完冲突的合并
+    fn apply_merge(&mut self, merge_idx: usize) -> NotepadResult<()>;
+    // 放弃合并(保留本地副本)
+    fn discard_merge(&mut self, merge_idx: usize) -> NotepadResult<()>;
+}
+
+impl MergeService for AppState {
+    fn merge_note(&mut self, theirs: Note) -> NotepadResult<MergeOutcome> {
+        match self.prepare_merge(theirs)? {
+            PreparedMerge::Unchanged => Ok(MergeOutcome::Unchanged),
+            PreparedMerge::Merged(note) => {
+                self.store_merged_note(*note)?;
+                Ok(MergeOutcome::Merged)
+            }
+            PreparedMerge::Conflicted(merge) => {
+                self.push_pending_merge(*merge);
+                Ok(MergeOutcome::Conflicted)
+            }
+        }
+    }
+
+    fn pending_merges(&self) -> &[NoteMerge] {
+        &self.pending_merges
+    }
+
+    fn resolve_hunk(&mut self, merge_idx: usize, hunk_idx: usize, resolution: HunkResolution) {
+        if let Some(merge) = self.pending_merges.get_mut(merge_idx) {
+            merge.resolve(hunk_idx, resolution);
+        }
+    }
+
+    fn apply_merge(&mut self, merge_idx: usize) -> NotepadResult<()> {
+        let Some(note) = self.pending_merges.get(merge_idx).and_then(|m| m.to_note()) else {
+            return Err(NotepadError::Conflict("the merge still has unresolved conflicts".to_string()));
+        };
+        self.store_merged_note(note)?;
+        let merge = self.pending_merges.remove(merge_idx);
+        // 同步产生的冲突在下次同步时以合并结果覆盖远端
+        self.db_conn.lock().unwrap().resolve_sync_conflict(merge.note_id())?;
+        Ok(())
+    }
+
+    fn discard_merge(&mut self, merge_idx: usize) -> NotepadResult<()> {
+        if merge_idx < self.pending_merges.len() {
+            let merge = self.pending_merges.remove(merge_idx);
+            self.db_conn.lock().unwrap().resolve_sync_conflict(merge.note_id())?;
+        }
+        Ok(())
+    }
+}
+
+// 尚未保存的合并结果
+pub(crate) enum PreparedMerge {
+    Unchanged,
+    Merged(Box<Note>),
+    Conflicted(Box<NoteMerge>),
+}
+
+// 计算对方副本与本地副本 ours 的合并结果(不写入数据库)
+pub(crate) fn prepare_merge(db: &Database, versions: VersionStore, ours: Option<Note>, theirs: Note) -> NotepadResult<PreparedMerge> {
+    let Some(ours) = ours else {
+        // 本地不存在时直接保存对方副本
+        return Ok(PreparedMerge::Merged(Box::new(theirs)));
+    };
+
+    if ours.title() == theirs.title()
+        && ours.content() == theirs.content()
+        && theirs.tags().is_subset(ours.tags()) {
+        return Ok(PreparedMerge::Unchanged);
+    }
+
+    let history = versions.load(db, ours.id())?;
+    let base = NoteMerge::find_common_ancestor(&history, &ours, &theirs);
+    let merge = NoteMerge::new(base, &ours, &theirs);
+    match merge.to_note() {
+        Some(note) => Ok(PreparedMerge::Merged(Box::new(note))),
+        None => Ok(PreparedMerge::Conflicted(Box::new(merge))),
+    }
+}
+
+impl AppState {
+    // 计算与本地副本的合并结果(不写入数据库)
+    fn prepare_merge(&self, theirs: Note) -> NotepadResult<PreparedMerge> {
+        let ours = self.get_note(theirs.id());
+        prepare_merge(&self.db_conn.lock().unwrap(), self.versions(), ours, theirs)
+    }
+
+    // 同一笔记只保留最新的待处理合并
+    pub(super) fn push_pending_merge(&mut self, merge: NoteMerge) {
+        self.pending_merges.retain(|m| m.note_id() != merge.note_id());
+        self.pending_merges.push(merge);
+    }
+
+    // 保存合并后的笔记
+    fn store_merged_note(&mut self, note: Note) -> NotepadResult<()> {
+        // 先保存笔记到数据库中
+        self.save_note(&note)?;
+        // 再更新内存中笔记信息
+        let mut notebook = self.notebook.lock().unwrap();
+        notebook.insert_or_replace_note(note);
+        Ok(())
+    }
+}
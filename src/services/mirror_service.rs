@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::data::SyncScope;
+use crate::error::{NotepadError, NotepadResult};
+use crate::sync::{MirrorRemote, MirrorWatcher, mirror::POLL_INTERVAL};
+use super::{AppState, NotifyService};
+
+pub trait MirrorService {
+    // 镜像目录(与数据库双向同步的Markdown文件目录)
+    fn mirror_folder(&self) -> Option<PathBuf>;
+    fn set_mirror_folder(&mut self, folder: Option<&Path>) -> NotepadResult<()>;
+    // 立即同步镜像目录
+    fn mirror_now(&mut self) -> NotepadResult<()>;
+    // 目录中的文件或笔记有变化时同步镜像目录，返回下次检查前的等待时间
+    fn poll_mirror(&mut self) -> NotepadResult<Option<Duration>>;
+}
+
+impl MirrorService for AppState {
+    fn mirror_folder(&self) -> Option<PathBuf> {
+        let conn = self.db_conn.lock().unwrap();
+        conn.sync_meta("mirror").ok().flatten().map(PathBuf::from)
+    }
+
+    fn set_mirror_folder(&mut self, folder: Option<&Path>) -> NotepadResult<()> {
+        if folder.is_some() {
+            self.ensure_plaintext_remote()?;
+        }
+        {
+            let mut conn = self.db_conn.lock().unwrap();
+            // 更换镜像目录后需要重新比较全部笔记
+            conn.reset_sync_state(SyncScope::Mirror)?;
+            conn.set_sync_meta("mirror", folder.and_then(|p| p.to_str()))?;
+        }
+        self.mirror_watcher = folder.map(MirrorWatcher::spawn);
+        self.mirror_checked = Some(Instant::now());
+        match folder {
+            Some(_) => self.mirror_now(),
+            None => Ok(()),
+        }
+    }
+
+    fn mirror_now(&mut self) -> NotepadResult<()> {
+        self.ensure_plaintext_remote()?;
+        let folder = self.mirror_folder()
+            .ok_or_else(|| NotepadError::Invalid("No mirror folder has been chosen".to_string()))?;
+        let mut remote = {
+            let conn = self.db_conn.lock().unwrap();
+            // 加锁笔记(含回收站中的)的明文不写入目录
+            let locked: HashSet<String> = conn.load_sync_records()?.into_iter()
+                .filter(|record| record.note().is_some_and(|note| note.is_locked()))
+                .map(|record| record.id().to_string())
+                .collect();
+            MirrorRemote::new(&folder, &conn.load_sync_states(SyncScope::Mirror)?, locked).map_err(NotepadError::remote)?
+        };
+        let result = self.run_sync(&mut remote, "mirror");
+        // 失败时等到下次变更再重试，避免重复报错
+        self.mirror_stamp = Some(self.db_conn.lock().unwrap().change_stamp()?);
+        let report = result?;
+
+        if report.pulled + report.deleted + report.merged + report.conflicts.len() > 0 {
+            self.notify_success("mirror", format!(
+                "Mirrored {}: {} updated from files, {} moved to trash, {} merged, {} conflicts",
+                folder.display(), report.pulled, report.deleted, report.merged, report.conflicts.len(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn poll_mirror(&mut self) -> NotepadResult<Option<Duration>> {
+        match self.mirror_checked {
+            Some(checked) => {
+                if self.mirror_watcher.is_none() {
+                    return Ok(None);
+                }
+                let elapsed = checked.elapsed();
+                if elapsed < POLL_INTERVAL {
+                    return Ok(Some(POLL_INTERVAL - elapsed));
+                }
+            }
+            // 启动后第一次检查
+            None => self.mirror_watcher = self.mirror_folder().map(|folder| MirrorWatcher::spawn(&folder)),
+        }
+        self.mirror_checked = Some(Instant::now());
+        // 启用保险库前配置的镜像目录不再同步
+        if self.db_conn.lock().unwrap().is_encrypted()? {
+            return Ok(None);
+        }
+        let Some(watcher) = self.mirror_watcher.as_ref() else {
+            return Ok(None);
+        };
+
+        let changed = watcher.take_changed();
+        let stamp = self.db_conn.lock().unwrap().change_stamp()?;
+        if changed || self.mirror_stamp != Some(stamp) {
+            self.mirror_now()?;
+        }
+        Ok(Some(POLL_INTERVAL))
+    }
+}
@@ -4,14 +4,25 @@ pub mod trash_service;
 pub mod version_service;
 pub mod settings_service;
 pub mod io_service;
+pub mod merge_service;
+pub mod sync_service;
+pub mod account_service;
+pub mod vault_service;
+pub mod lock_service;
+pub mod notify_service;
+pub mod backup_service;
+pub mod mirror_service;
 
-use anyhow::Ok;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use crate::{
-    data::{Database, Notebook},
-    io::{ExportConfig, ImportConfig}, 
+    data::{Database, GitHistory, Notebook, NoteMerge},
+    error::{NotepadError, NotepadResult},
+    io::{ExportConfig, ImportConfig, ImportReport}, 
     i18n::{self, Language, Translate}, 
-    utils::tab_manager::TabManager
+    utils::{crypto::Cipher, tab_manager::TabManager},
+    sync::{SyncReport, MirrorWatcher},
 };
 pub use {
     note_service::NoteService,
@@ -20,6 +31,14 @@ pub use {
     trash_service::TrashService,
     settings_service::SettingsService,
     io_service::IoService,
+    merge_service::{MergeService, MergeOutcome},
+    sync_service::SyncService,
+    account_service::{AccountService, Account},
+    vault_service::VaultService,
+    lock_service::LockService,
+    notify_service::{NotifyService, Notice, NoticeKind},
+    backup_service::BackupService,
+    mirror_service::MirrorService,
 };
 
 
@@ -28,6 +47,20 @@ pub struct AppState {
     notebook: Arc<Mutex<Notebook>>,
     recent_notes: TabManager<String>,
     modified_note: Arc<Mutex<Option<String>>>,
+    pending_merges: Vec<NoteMerge>,
+    last_sync_report: Option<SyncReport>,
+    last_import_report: Option<ImportReport>,
+    account: Option<Account>,
+    // 本次会话中已解锁的笔记及其笔记密钥
+    unlocked_notes: HashMap<String, Cipher>,
+    last_activity: Instant,
+    // 镜像目录的监视线程、上次同步时的笔记变更戳和上次检查时间
+    mirror_watcher: Option<MirrorWatcher>,
+    mirror_stamp: Option<u64>,
+    mirror_checked: Option<Instant>,
+    // 以git仓库保存版本历史(未启用时使用数据库)
+    git_history: Option<GitHistory>,
+    notifications: Mutex<notify_service::Notifications>,
     export_config: ExportConfig,
     import_config: ImportConfig,
     theme: Theme,
@@ -35,25 +68,75 @@ pub struct AppState {
 }
 
 impl AppState {
-    pub fn new() -> anyhow::Result<Self> {
-        // 初始化数据库连接并加载初始数据
-        let db = Database::new()?;
+    pub fn new() -> NotepadResult<Self> {
+        Self::with_database(Database::new()?)
+    }
+
+    /// 使用指定路径的数据库(未启用保险库时), 不存在时自动创建
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> NotepadResult<Self> {
+        let db = Database::open(path)?;
+        if db.is_locked()? {
+            return Err(NotepadError::Locked("The database is encrypted and must be unlocked first".to_string()));
+        }
+        Self::with_database(db)
+    }
+
+    /// 使用内存数据库(用于测试)
+    pub fn in_memory() -> NotepadResult<Self> {
+        Self::with_database(Database::open_in_memory()?)
+    }
+
+    /// 使用已打开(启用加密时需已解锁)的数据库加载初始数据
+    pub fn with_database(db: Database) -> NotepadResult<Self> {
         let notebook = db.load_all_notes()?;
+        let account = Self::load_account(&db)?;
+        let git_history = version_service::open_git_history(&db);
 
-        Ok(Self {
+        let state = Self {
             db_conn: Arc::new(Mutex::new(db)),
             notebook: Arc::new(Mutex::new(notebook)),
             recent_notes: TabManager::new(7),
             modified_note: Arc::new(Mutex::new(None)),
+            pending_merges: Vec::new(),
+            last_sync_report: None,
+            last_import_report: None,
+            account,
+            unlocked_notes: HashMap::new(),
+            last_activity: Instant::now(),
+            mirror_watcher: None,
+            mirror_stamp: None,
+            mirror_checked: None,
+            git_history,
+            notifications: Mutex::default(),
             export_config: ExportConfig::default(),
             import_config: ImportConfig::default(),
             theme: Theme::Dark,
             language: Language::English,
-        })
+        };
+        state.report_quarantined();
+        Ok(state)
+    }
+
+    // 将读取时跳过的损坏行移入隔离表并报告
+    fn report_quarantined(&self) {
+        let quarantined = match self.db_conn.lock().unwrap().quarantine_corrupt_rows() {
+            Ok(quarantined) => quarantined,
+            Err(e) => {
+                self.notify_error("quarantine", None, format!("Failed to move corrupt rows to quarantine: {}", e));
+                return;
+            }
+        };
+        for e in quarantined {
+            let row = match &e {
+                NotepadError::Corrupt { row, .. } => Some(row.as_str()),
+                _ => None,
+            };
+            self.notify_warning("quarantine", row, format!("{}, the row was moved to quarantine", e));
+        }
     }
 
     /// 刷新修改笔记
-    fn flush_modified_note(&self, note_id: Option<String>) -> anyhow::Result<()> {
+    fn flush_modified_note(&self, note_id: Option<String>) -> NotepadResult<()> {
         let mut modified_note = self.modified_note.lock().unwrap();
         if let Some(id) = modified_note.take() {
             if let Some(modified_note) = self.notebook.lock().unwrap().find_note(&id) {
@@ -65,7 +148,7 @@ impl AppState {
     }
 
     // /// 保存当前修改过的笔记，会做修改检查
-    // pub fn save_current_note(&mut self) -> anyhow::Result<()> {
+    // pub fn save_current_note(&mut self) -> NotepadResult<()> {
     //     if let Some(note_id) = self.current_note_id() {
     //         let notebook = self.notebook.lock().unwrap();
     //         if let Some(note) = notebook.find_note(note_id) {
@@ -77,7 +160,7 @@ impl AppState {
     // }
 
     // /// 全量保存
-    // pub fn save_all_note(&mut self) -> anyhow::Result<()> {
+    // pub fn save_all_note(&mut self) -> NotepadResult<()> {
     //     let mut conn = self.db_conn.lock().unwrap();
     //     let notebook = self.notebook.lock().unwrap();
     //     conn.save_notebook(&notebook)?;
@@ -92,7 +175,7 @@ impl Translate for AppState {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Theme {
     Dark,
     Light,
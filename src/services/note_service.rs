@@ -1,19 +1,20 @@
 use crate::data::Note;
+use crate::error::NotepadResult;
 use super::AppState;
 use super::{TabService, TrashService};
 
 pub trait NoteService {
-    fn create_note(&mut self) -> anyhow::Result<()>;
-    fn update_note(&mut self, note: Note) -> anyhow::Result<()>;
-    fn delete_note(&mut self, note_id: &str) -> anyhow::Result<()>;
-    fn save_note(&self, note: &Note) -> anyhow::Result<()>;
+    fn create_note(&mut self) -> NotepadResult<()>;
+    fn update_note(&mut self, note: Note) -> NotepadResult<()>;
+    fn delete_note(&mut self, note_id: &str) -> NotepadResult<()>;
+    fn save_note(&self, note: &Note) -> NotepadResult<()>;
     fn get_note(&self, note_id: &str) -> Option<Note>;
-    fn filter_notes(&self, key: &str) -> anyhow::Result<Vec<Note>>;
+    fn filter_notes(&self, key: &str) -> NotepadResult<Vec<Note>>;
 }
 
 impl NoteService for AppState {
     /// 新建笔记
-    fn create_note(&mut self) -> anyhow::Result<()> {
+    fn create_note(&mut self) -> NotepadResult<()> {
         let new_note = Note::new("untitled".to_string());
         self.load_note(new_note.id());
         self.update_note(new_note)
@@ -25,7 +26,7 @@ impl NoteService for AppState {
     }
 
     // 更新笔记
-    fn update_note(&mut self, note: Note) -> anyhow::Result<()> {
+    fn update_note(&mut self, note: Note) -> NotepadResult<()> {
         let note_id = note.id().to_string();
         {
             let mut notebook = self.notebook.lock().unwrap();
@@ -36,19 +37,21 @@ impl NoteService for AppState {
     }
 
     /// 保存指定笔记
-    fn save_note(&self, note: &Note) -> anyhow::Result<()> {
+    fn save_note(&self, note: &Note) -> NotepadResult<()> {
+        // 加锁笔记以密文保存
+        let note = self.seal_note(note)?;
         let mut conn = self.db_conn.lock().unwrap();
-        conn.save_note(note)?;
+        conn.save_note(&note)?;
         Ok(())
     }
 
     /// 删除笔记
-    fn delete_note(&mut self, note_id: &str) -> anyhow::Result<()> {
+    fn delete_note(&mut self, note_id: &str) -> NotepadResult<()> {
         self.move_to_trash(note_id)
     }
 
     /// 筛选笔记
-    fn filter_notes(&self, key: &str) -> anyhow::Result<Vec<Note>> {
+    fn filter_notes(&self, key: &str) -> NotepadResult<Vec<Note>> {
         Ok(self.notebook.lock().unwrap().filter_notes(key)
             .into_iter()
             .cloned()
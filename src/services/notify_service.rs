@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local};
+use crate::utils::logger::{self, Level};
+use super::AppState;
+
+// 通知历史最多保留的条数
+const HISTORY_CAPACITY: usize = 200;
+
+/// 通知类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoticeKind {
+    Success,
+    Info,
+    Warning,
+    Error,
+}
+
+impl NoticeKind {
+    // 提示框显示时长(错误显示更久)
+    fn toast_duration(&self) -> Duration {
+        match self {
+            NoticeKind::Error => Duration::from_secs(8),
+            NoticeKind::Warning => Duration::from_secs(6),
+            _ => Duration::from_secs(4),
+        }
+    }
+
+    fn log_level(&self) -> Level {
+        match self {
+            NoticeKind::Error => Level::Error,
+            NoticeKind::Warning => Level::Warn,
+            _ => Level::Info,
+        }
+    }
+}
+
+/// 一条通知
+#[derive(Debug, Clone)]
+pub struct Notice {
+    pub id: u64,
+    pub kind: NoticeKind,
+    pub operation: String,
+    pub note_id: Option<String>,
+    pub message: String,
+    pub time: DateTime<Local>,
+    created: Instant,
+    dismissed: bool,
+}
+
+impl Notice {
+    /// 提示框剩余显示时长(已过期或已关闭时为None)
+    pub fn toast_remaining(&self) -> Option<Duration> {
+        if self.dismissed {
+            return None;
+        }
+        self.kind.toast_duration().checked_sub(self.created.elapsed())
+    }
+}
+
+/// 通知中心：服务和界面推送成功/错误消息，同时写入日志
+#[derive(Debug, Default)]
+pub struct Notifications {
+    next_id: u64,
+    history: Vec<Notice>,
+}
+
+pub trait NotifyService {
+    fn notify(&self, kind: NoticeKind, operation: &str, note_id: Option<&str>, message: String);
+    // 通知历史(按时间顺序)
+    fn notices(&self) -> Vec<Notice>;
+    // 关闭提示框(保留在历史中)
+    fn dismiss_notice(&self, id: u64);
+    fn clear_notices(&self);
+
+    fn notify_success(&self, operation: &str, message: String) {
+        self.notify(NoticeKind::Success, operation, None, message);
+    }
+
+    fn notify_warning(&self, operation: &str, note_id: Option<&str>, message: String) {
+        self.notify(NoticeKind::Warning, operation, note_id, message);
+    }
+
+    fn notify_error(&self, operation: &str, note_id: Option<&str>, message: String) {
+        self.notify(NoticeKind::Error, operation, note_id, message);
+    }
+}
+
+impl NotifyService for AppState {
+    fn notify(&self, kind: NoticeKind, operation: &str, note_id: Option<&str>, message: String) {
+        logger::log(kind.log_level(), operation, note_id, message.clone());
+
+        let mut notifications = self.notifications.lock().unwrap();
+        notifications.next_id += 1;
+        let notice = Notice {
+            id: notifications.next_id,
+            kind,
+            operation: operation.to_string(),
+            note_id: note_id.map(|id| id.to_string()),
+            message,
+            time: Local::now(),
+            created: Instant::now(),
+            dismissed: false,
+        };
+        if notifications.history.len() == HISTORY_CAPACITY {
+            notifications.history.remove(0);
+        }
+        notifications.history.push(notice);
+    }
+
+    fn notices(&self) -> Vec<Notice> {
+        self.notifications.lock().unwrap().history.clone()
+    }
+
+    fn dismiss_notice(&self, id: u64) {
+        let mut notifications = self.notifications.lock().unwrap();
+        if let Some(notice) = notifications.history.iter_mut().find(|n| n.id == id) {
+            notice.dismissed = true;
+        }
+    }
+
+    fn clear_notices(&self) {
+        self.notifications.lock().unwrap().history.clear();
+    }
+}
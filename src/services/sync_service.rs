@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+use crate::data::SyncScope;
+use crate::sync::{FolderRemote, HttpRemote, SyncEngine, SyncRemote, SyncReport};
+use crate::error::{NotepadError, NotepadResult};
+use super::{AppState, NotifyService};
+
+pub trait SyncService {
+    // 同步目录
+    fn sync_folder(&self) -> Option<PathBuf>;
+    fn set_sync_folder(&mut self, folder: Option<&Path>) -> NotepadResult<()>;
+    // 是否已配置同步目标(账户或同步目录)
+    fn can_sync(&self) -> bool;
+    // 立即同步(已登录时同步到服务器，否则同步到同步目录)
+    fn sync_now(&mut self) -> NotepadResult<()>;
+    // 最近一次同步的结果
+    fn last_sync_report(&self) -> Option<&SyncReport>;
+}
+
+impl SyncService for AppState {
+    fn sync_folder(&self) -> Option<PathBuf> {
+        let conn = self.db_conn.lock().unwrap();
+        conn.sync_meta("folder").ok().flatten().map(PathBuf::from)
+    }
+
+    fn set_sync_folder(&mut self, folder: Option<&Path>) -> NotepadResult<()> {
+        if folder.is_some() {
+            self.ensure_plaintext_remote()?;
+        }
+        let mut conn = self.db_conn.lock().unwrap();
+        // 更换同步目录后需要重新比较全部笔记
+        conn.reset_sync_state(SyncScope::Remote)?;
+        conn.set_sync_meta("folder", folder.and_then(|p| p.to_str()))?;
+        self.last_sync_report = None;
+        Ok(())
+    }
+
+    fn can_sync(&self) -> bool {
+        self.account.is_some() || self.sync_folder().is_some()
+    }
+
+    fn sync_now(&mut self) -> NotepadResult<()> {
+        self.ensure_plaintext_remote()?;
+        if let Some(account) = self.account.clone() {
+            let mut remote = HttpRemote::new(&account.server, &account.token);
+            self.sync_with(&mut remote)
+        } else if let Some(folder) = self.sync_folder() {
+            let mut remote = FolderRemote::new(&folder).map_err(NotepadError::remote)?;
+            self.sync_with(&mut remote)
+        } else {
+            Err(NotepadError::Invalid("No sync folder has been chosen".to_string()))
+        }
+    }
+
+    fn last_sync_report(&self) -> Option<&SyncReport> {
+        self.last_sync_report.as_ref()
+    }
+}
+
+impl AppState {
+    // 同步目录、服务器和镜像目录中保存的是明文，启用保险库时不允许写出
+    // (保险库密钥只属于本机, 加密后的记录在其他设备上无法解密)
+    pub(super) fn ensure_plaintext_remote(&self) -> NotepadResult<()> {
+        if self.db_conn.lock().unwrap().is_encrypted()? {
+            return Err(NotepadError::Locked("sync and mirroring are not available while the vault is enabled".to_string()));
+        }
+        Ok(())
+    }
+
+    fn sync_with<R: SyncRemote>(&mut self, remote: &mut R) -> NotepadResult<()> {
+        let report = self.run_sync(remote, "sync")?;
+        self.notify_success("sync", format!(
+            "Synced: {} pushed, {} pulled, {} deleted, {} merged, {} conflicts",
+            report.pushed, report.pulled, report.deleted, report.merged, report.conflicts.len(),
+        ));
+        self.last_sync_report = Some(report);
+        Ok(())
+    }
+
+    // 执行一次同步并重新加载笔记，冲突交给合并编辑器处理
+    pub(super) fn run_sync<R: SyncRemote>(&mut self, remote: &mut R, operation: &str) -> NotepadResult<SyncReport> {
+        // 先保存正在编辑的笔记
+        self.flush_modified_note(None)?;
+
+        let report = {
+            let mut conn = self.db_conn.lock().unwrap();
+            let report = SyncEngine::new(&mut conn, remote)
+                .with_versions(self.versions())
+                .run()
+                .map_err(NotepadError::remote)?;
+            // 重新加载同步后的笔记
+            *self.notebook.lock().unwrap() = conn.load_all_notes()?;
+            report
+        };
+        self.reveal_unlocked_notes();
+        self.report_quarantined();
+        for warning in report.warnings.iter() {
+            self.notify_warning(operation, None, warning.clone());
+        }
+
+        for merge in report.conflicts.iter().cloned() {
+            self.pending_merges.retain(|m| m.note_id() != merge.note_id());
+            self.pending_merges.push(merge);
+        }
+        Ok(report)
+    }
+}
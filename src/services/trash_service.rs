@@ -1,18 +1,19 @@
 use crate::data::DeleteNote;
+use crate::error::{NotepadError, NotepadResult};
 use super::AppState;
 use super::TabService;
 
 pub trait TrashService {
-    fn move_to_trash(&mut self, note_id: &str) -> anyhow::Result<()>;
-    fn restore_from_trash(&mut self, note_id: &str) -> anyhow::Result<()>;
-    fn empty_trash(&mut self) -> anyhow::Result<()>;
-    fn delete_permanently(&mut self, note_id: &str) -> anyhow::Result<()>;
-    fn get_deleted_notes(&self) -> anyhow::Result<Vec<DeleteNote>>;
+    fn move_to_trash(&mut self, note_id: &str) -> NotepadResult<()>;
+    fn restore_from_trash(&mut self, note_id: &str) -> NotepadResult<()>;
+    fn empty_trash(&mut self) -> NotepadResult<()>;
+    fn delete_permanently(&mut self, note_id: &str) -> NotepadResult<()>;
+    fn get_deleted_notes(&self) -> NotepadResult<Vec<DeleteNote>>;
 }
 
 impl TrashService for AppState {
     /// 删除指定笔记
-    fn move_to_trash(&mut self, note_id: &str) -> anyhow::Result<()> {
+    fn move_to_trash(&mut self, note_id: &str) -> NotepadResult<()> {
         {
             let mut notebook = self.notebook.lock().unwrap();
             // 从内存中移除
@@ -28,10 +29,11 @@ impl TrashService for AppState {
         Ok(())
     }
 
-    fn restore_from_trash(&mut self, note_id: &str) -> anyhow::Result<()> {
+    fn restore_from_trash(&mut self, note_id: &str) -> NotepadResult<()> {
         // 先从从数据库中恢复
-        let mut conn = self.db_conn.lock().unwrap();
-        let note = conn.restore_from_trash(note_id)?.unwrap();
+        let restored = self.db_conn.lock().unwrap().restore_from_trash(note_id)?;
+        self.report_quarantined();
+        let note = restored.ok_or_else(|| NotepadError::NotFound(note_id.to_string()))?;
 
         // 再从内存中恢复
         let mut notebook = self.notebook.lock().unwrap();
@@ -39,21 +41,23 @@ impl TrashService for AppState {
         Ok(())
     }
 
-    fn empty_trash(&mut self) -> anyhow::Result<()> {
+    fn empty_trash(&mut self) -> NotepadResult<()> {
         let mut conn = self.db_conn.lock().unwrap();
         conn.empty_trash()?;
         Ok(())
     }
 
-    fn delete_permanently(&mut self, note_id: &str) -> anyhow::Result<()> {
+    fn delete_permanently(&mut self, note_id: &str) -> NotepadResult<()> {
         let mut conn = self.db_conn.lock().unwrap();
         conn.delete_permanently(note_id)?;
         Ok(())
     }
 
-    fn get_deleted_notes(&self) -> anyhow::Result<Vec<DeleteNote>> {
+    fn get_deleted_notes(&self) -> NotepadResult<Vec<DeleteNote>> {
         let conn = self.db_conn.lock().unwrap();
         let deleted_notes = conn.get_deleted_notes()?;
+        drop(conn);
+        self.report_quarantined();
         Ok(deleted_notes)
     }
 }
\ No newline at end of file
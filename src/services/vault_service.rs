@@ -0,0 +1,54 @@
+use crate::error::{NotepadError, NotepadResult};
+use super::AppState;
+use super::{MirrorService, NotifyService, SyncService};
+
+pub trait VaultService {
+    // 是否启用了保险库加密
+    fn is_vault_enabled(&self) -> bool;
+    // 启用加密(加密全部已有笔记)
+    fn enable_vault(&mut self, passphrase: &str) -> NotepadResult<()>;
+    // 关闭加密(解密全部笔记)
+    fn disable_vault(&mut self, passphrase: &str) -> NotepadResult<()>;
+    // 修改保险库口令
+    fn change_passphrase(&mut self, old_passphrase: &str, new_passphrase: &str) -> NotepadResult<()>;
+}
+
+impl VaultService for AppState {
+    fn is_vault_enabled(&self) -> bool {
+        self.db_conn.lock().unwrap().is_encrypted().unwrap_or(false)
+    }
+
+    fn enable_vault(&mut self, passphrase: &str) -> NotepadResult<()> {
+        if passphrase.is_empty() {
+            return Err(NotepadError::Invalid("The passphrase must not be empty".to_string()));
+        }
+        if self.git_history.is_some() {
+            return Err(NotepadError::Invalid("Disable git version history before enabling the vault".to_string()));
+        }
+        // 同步和镜像会把笔记以明文写出本机
+        if self.can_sync() || self.mirror_folder().is_some() {
+            return Err(NotepadError::Invalid("Log out and clear the sync and mirror folders before enabling the vault".to_string()));
+        }
+        // 先保存正在编辑的笔记，确保其内容也被加密
+        self.flush_modified_note(None)?;
+        self.db_conn.lock().unwrap().enable_encryption(passphrase)?;
+        self.notify_success("enable_vault", "Vault encryption enabled".to_string());
+        Ok(())
+    }
+
+    fn disable_vault(&mut self, passphrase: &str) -> NotepadResult<()> {
+        self.flush_modified_note(None)?;
+        self.db_conn.lock().unwrap().disable_encryption(passphrase)?;
+        self.notify_success("disable_vault", "Vault encryption disabled".to_string());
+        Ok(())
+    }
+
+    fn change_passphrase(&mut self, old_passphrase: &str, new_passphrase: &str) -> NotepadResult<()> {
+        if new_passphrase.is_empty() {
+            return Err(NotepadError::Invalid("The passphrase must not be empty".to_string()));
+        }
+        self.db_conn.lock().unwrap().change_passphrase(old_passphrase, new_passphrase)?;
+        self.notify_success("change_passphrase", "Vault passphrase changed".to_string());
+        Ok(())
+    }
+}
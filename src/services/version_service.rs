@@ -1,29 +1,90 @@
-use crate::data::{Note, NoteVersion};
+use crate::data::{GitHistory, Note, NoteVersion, VersionStore};
+use crate::error::{NotepadError, NotepadResult};
 use super::AppState;
 use super::NoteService;
 
 pub trait VersionService {
-    fn list_versions(&self, note_id: &str) -> anyhow::Result<Vec<NoteVersion>>;
-    fn save_version(&mut self, comment: &str, note: &Note) -> anyhow::Result<()>;
-    fn restore_version(&mut self, note_version: &NoteVersion) -> anyhow::Result<()>;
-    fn delete_version(&mut self, version_id: &str) -> anyhow::Result<()>;
+    fn list_versions(&self, note_id: &str) -> NotepadResult<Vec<NoteVersion>>;
+    fn save_version(&mut self, comment: &str, note: &Note) -> NotepadResult<()>;
+    fn restore_version(&mut self, note_version: &NoteVersion) -> NotepadResult<()>;
+    fn delete_version(&mut self, version_id: &str) -> NotepadResult<()>;
+    // 是否以git仓库保存版本历史
+    fn is_git_history_enabled(&self) -> bool;
+    // 切换版本历史的存储方式，启用时将已有版本迁移到git仓库，关闭时迁回数据库，返回迁移的版本数
+    fn set_git_history(&mut self, enabled: bool) -> NotepadResult<usize>;
 }
 
 impl VersionService for AppState {
-    fn list_versions(&self, note_id: &str) -> anyhow::Result<Vec<NoteVersion>> {
-        let conn = self.db_conn.lock().unwrap();
-        let note_versions = conn.load_version_history(note_id)?;
-        Ok(note_versions)
+    fn list_versions(&self, note_id: &str) -> NotepadResult<Vec<NoteVersion>> {
+        let note_versions = self.versions().load(&self.db_conn.lock().unwrap(), note_id)?;
+        self.report_quarantined();
+        Ok(self.open_versions(note_id, note_versions))
     }
 
-    fn save_version(&mut self, comment: &str, note: &Note) -> anyhow::Result<()> {
-        let note_version = NoteVersion::new(comment, note);
+    fn save_version(&mut self, comment: &str, note: &Note) -> NotepadResult<()> {
+        let note_version = NoteVersion::new(comment, self.seal_note(note)?.as_ref());
+        self.versions().save(&mut self.db_conn.lock().unwrap(), &note_version)
+    }
+
+    fn restore_version(&mut self, note_version: &NoteVersion) -> NotepadResult<()> {
+        let Some(history) = self.git_history.as_ref() else {
+            return self.apply_version(note_version);
+        };
+        // 从git仓库重新读取该版本
+        let version = history.load_version(note_version.note_id(), note_version.id()).map_err(NotepadError::history)?;
+        let versions = self.open_versions(note_version.note_id(), vec![version]);
+        self.apply_version(&versions[0])
+    }
+
+    fn delete_version(&mut self, version_id: &str) -> NotepadResult<()> {
+        self.versions().delete(&mut self.db_conn.lock().unwrap(), version_id)
+    }
+
+    fn is_git_history_enabled(&self) -> bool {
+        self.git_history.is_some()
+    }
+
+    fn set_git_history(&mut self, enabled: bool) -> NotepadResult<usize> {
         let mut conn = self.db_conn.lock().unwrap();
-        conn.save_version(&note_version)?;
-        Ok(())
+        if !enabled {
+            let Some(history) = self.git_history.as_ref() else {
+                return Ok(0);
+            };
+            // 仓库中的版本迁回数据库(仓库保留, 重新启用时按版本id跳过已提交的版本)
+            let versions = VersionStore::Git(history).load_all(&conn)?;
+            conn.save_versions(&versions)?;
+            conn.set_sync_meta("history", None)?;
+            self.git_history = None;
+            return Ok(versions.len());
+        }
+        if self.git_history.is_some() {
+            return Ok(0);
+        }
+        // 仓库中的内容不加密
+        if conn.is_encrypted()? {
+            return Err(NotepadError::Invalid("Git version history is not available while the vault is enabled".to_string()));
+        }
+        let dir = conn.directory()
+            .ok_or_else(|| NotepadError::Invalid("The database is not stored in a folder".to_string()))?;
+        let history = GitHistory::open(&dir.join(HISTORY_DIR)).map_err(NotepadError::history)?;
+        let migrated = history.migrate(&conn.load_all_versions()?).map_err(NotepadError::history)?;
+        conn.clear_versions()?;
+        conn.set_sync_meta("history", Some("git"))?;
+        self.git_history = Some(history);
+        Ok(migrated)
+    }
+}
+
+// 版本历史仓库(位于数据库所在目录)
+pub(super) const HISTORY_DIR: &str = "history";
+
+impl AppState {
+    // 当前的版本历史存储位置
+    pub(super) fn versions(&self) -> VersionStore<'_> {
+        VersionStore::new(self.git_history.as_ref())
     }
 
-    fn restore_version(&mut self, note_version: &NoteVersion) -> anyhow::Result<()> {
+    fn apply_version(&mut self, note_version: &NoteVersion) -> NotepadResult<()> {
         if let Some(mut note) = self.get_note(note_version.note_id()) {
             note.updated_by_note_version(note_version);
             let mut notebook = self.notebook.lock().unwrap();
@@ -31,10 +92,19 @@ impl VersionService for AppState {
         }
         Ok(())
     }
+}
 
-    fn delete_version(&mut self, version_id: &str) -> anyhow::Result<()> {
-        let mut conn = self.db_conn.lock().unwrap();
-        conn.delete_version(version_id)?;
-        Ok(())
+// 启动时打开已启用的git版本历史仓库
+pub(crate) fn open_git_history(db: &crate::data::Database) -> Option<GitHistory> {
+    if db.sync_meta("history").ok().flatten().as_deref() != Some("git") {
+        return None;
     }
-}
\ No newline at end of file
+    let dir = db.directory()?.join(HISTORY_DIR);
+    match GitHistory::open(&dir) {
+        Ok(history) => Some(history),
+        Err(e) => {
+            crate::log_warn!("open_git_history"; "failed to open {}: {}", dir.display(), e);
+            None
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::Path;
+use crate::error::{NotepadError, NotepadResult};
+use crate::data::{Database, DeleteNote, ExportNote, GitHistory, Note, Notebook, NoteVersion, VersionStore};
+use crate::io::{ExportConfig, ExportFormat, Exporter, ImportConfig, ImportReport};
+use crate::services::{io_service, version_service};
+
+/// 不依赖界面的笔记库, 供其他工具嵌入使用
+///
+/// 修改立即写入数据库; 加锁笔记以密文返回, 不能修改或导出
+pub struct NotesStore {
+    db: Database,
+    history: Option<GitHistory>,
+    import_config: ImportConfig,
+}
+
+impl NotesStore {
+    /// 打开指定路径的数据库(不存在时自动创建)
+    pub fn open<P: AsRef<Path>>(path: P) -> NotepadResult<Self> {
+        let db = Database::open(path)?;
+        if db.is_locked()? {
+            return Err(NotepadError::Locked("the database is encrypted, use open_with_passphrase".to_string()));
+        }
+        Ok(Self::with_database(db))
+    }
+
+    /// 打开启用了保险库的数据库
+    pub fn open_with_passphrase<P: AsRef<Path>>(path: P, passphrase: &str) -> NotepadResult<Self> {
+        let mut db = Database::open(path)?;
+        if db.is_locked()? {
+            db.unlock(passphrase)?;
+        }
+        Ok(Self::with_database(db))
+    }
+
+    /// 使用内存数据库
+    pub fn in_memory() -> NotepadResult<Self> {
+        Ok(Self::with_database(Database::open_in_memory()?))
+    }
+
+    fn with_database(db: Database) -> Self {
+        let history = version_service::open_git_history(&db);
+        Self { db, history, import_config: ImportConfig::default() }
+    }
+
+    fn version_store(&self) -> VersionStore<'_> {
+        VersionStore::new(self.history.as_ref())
+    }
+
+    fn notebook(&self) -> NotepadResult<Notebook> {
+        self.db.load_all_notes()
+    }
+
+    /// 将读取时跳过的损坏行移入隔离表, 返回被隔离的行
+    pub fn quarantine_corrupt_rows(&mut self) -> NotepadResult<Vec<NotepadError>> {
+        self.db.quarantine_corrupt_rows()
+    }
+
+    /// 所有笔记(不含回收站)
+    pub fn notes(&self) -> NotepadResult<Vec<Note>> {
+        self.search("")
+    }
+
+    /// 按标题和内容搜索(加锁笔记只匹配标题)
+    ///
+    /// 启用保险库时内容和标签以密文存储, 搜索需要读取全部笔记
+    pub fn search(&self, query: &str) -> NotepadResult<Vec<Note>> {
+        Ok(self.notebook()?.filter_notes(query).into_iter().cloned().collect())
+    }
+
+    /// 带有指定标签的笔记
+    pub fn tagged(&self, tag: &str) -> NotepadResult<Vec<Note>> {
+        Ok(self.notes()?.into_iter().filter(|note| note.tags().contains(tag)).collect())
+    }
+
+    pub fn get(&self, id: &str) -> NotepadResult<Note> {
+        self.db.load_note(id)?.ok_or_else(|| NotepadError::NotFound(id.to_string()))
+    }
+
+    /// 新建笔记并返回
+    pub fn create(&mut self, title: &str, content: &str) -> NotepadResult<Note> {
+        let mut note = Note::new(title.to_string());
+        note.update_content(content.to_string());
+        self.save(&note)?;
+        Ok(note)
+    }
+
+    /// 保存新的或修改后的笔记
+    pub fn save(&mut self, note: &Note) -> NotepadResult<()> {
+        if note.is_locked() || self.db.load_note(note.id())?.is_some_and(|n| n.is_locked()) {
+            return Err(NotepadError::Locked(note.title().to_string()));
+        }
+        self.db.save_note(note)
+    }
+
+    pub fn trash(&mut self, id: &str) -> NotepadResult<()> {
+        self.get(id)?;
+        self.db.move_to_trash(id)
+    }
+
+    pub fn restore(&mut self, id: &str) -> NotepadResult<()> {
+        match self.db.restore_from_trash(id)? {
+            Some(_) => Ok(()),
+            None => Err(NotepadError::NotFound(id.to_string())),
+        }
+    }
+
+    /// 回收站中的笔记
+    pub fn trashed(&self) -> NotepadResult<Vec<DeleteNote>> {
+        self.db.get_deleted_notes()
+    }
+
+    pub fn delete_permanently(&mut self, id: &str) -> NotepadResult<()> {
+        self.db.delete_permanently(id)
+    }
+
+    pub fn empty_trash(&mut self) -> NotepadResult<()> {
+        self.db.empty_trash()
+    }
+
+    /// 笔记的版本历史(从新到旧)
+    pub fn versions(&self, id: &str) -> NotepadResult<Vec<NoteVersion>> {
+        self.version_store().load(&self.db, id)
+    }
+
+    /// 为笔记的当前内容保存一个版本
+    pub fn save_version(&mut self, id: &str, comment: &str) -> NotepadResult<()> {
+        let note = self.get(id)?;
+        let version = NoteVersion::new(comment, &note);
+        VersionStore::new(self.history.as_ref()).save(&mut self.db, &version)
+    }
+
+    /// 将笔记恢复到指定版本
+    pub fn restore_version(&mut self, version: &NoteVersion) -> NotepadResult<()> {
+        let mut note = self.get(version.note_id())?;
+        if note.is_locked() {
+            return Err(NotepadError::Locked(note.title().to_string()));
+        }
+        note.updated_by_note_version(version);
+        self.db.save_note(&note)
+    }
+
+    pub fn export_note(&mut self, id: &str, path: &Path, format: ExportFormat) -> NotepadResult<()> {
+        let note = self.get(id)?;
+        if note.is_sealed() {
+            return Err(NotepadError::Locked(note.title().to_string()));
+        }
+        let config = ExportConfig { format, ..Default::default() };
+        Exporter::export_note(&ExportNote::from_note(&note), path, &config)
+    }
+
+    pub fn export_all(&mut self, dir: &Path, format: ExportFormat) -> NotepadResult<()> {
+        std::fs::create_dir_all(dir)?;
+        let config = ExportConfig { format, ..Default::default() };
+        io_service::export_notebook(&self.db, &self.notebook()?, dir, &config)?;
+        Ok(())
+    }
+
+    /// 导入文件或目录, 返回导入报告; 有冲突的合并不写入, 在报告中标记为 conflicted
+    pub fn import(&mut self, path: &Path) -> NotepadResult<ImportReport> {
+        let (trashed, notes): (Vec<_>, Vec<_>) = self.db.load_notes_with_trash()?.into_iter()
+            .partition(|(_, deleted)| *deleted);
+        let notes: Vec<Note> = notes.into_iter().map(|(note, _)| note).collect();
+        let trashed: Vec<Note> = trashed.into_iter().map(|(note, _)| note).collect();
+        let mut notebook = Notebook::default();
+        for note in notes.iter() {
+            notebook.insert_or_replace_note(note.clone());
+        }
+        let plan = io_service::plan_import(path, &self.import_config, &notes, &trashed)?;
+        let mut report = plan.report;
+        let resolved = io_service::resolve_plan(&plan.candidates, &mut report, &self.db, self.version_store(), &notebook);
+        self.db.save_notes(&resolved.notes)?;
+        io_service::copy_attachments(&plan.candidates, resolved.attachments, &self.import_config, &mut report);
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes_store() {
+        let mut store = NotesStore::in_memory().unwrap();
+        let mut note = store.create("日记", "today").unwrap();
+        note.add_tag("生活".to_string());
+        store.save(&note).unwrap();
+        store.save_version(note.id(), "v1").unwrap();
+
+        note.update_content("tomorrow".to_string());
+        store.save(&note).unwrap();
+        assert_eq!(store.search("tomorrow").unwrap().len(), 1);
+        assert_eq!(store.tagged("生活").unwrap().len(), 1);
+        let versions = store.versions(note.id()).unwrap();
+        store.restore_version(&versions[0]).unwrap();
+        assert_eq!(store.get(note.id()).unwrap().content(), "today");
+
+        store.trash(note.id()).unwrap();
+        assert!(store.notes().unwrap().is_empty());
+        assert!(matches!(store.get(note.id()), Err(NotepadError::NotFound(_))));
+        store.restore(note.id()).unwrap();
+        assert!(matches!(store.restore(note.id()), Err(NotepadError::NotFound(_))));
+        assert_eq!(store.get(note.id()).unwrap().title(), "日记");
+
+        // 导出后导入到另一个库
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        store.export_all(dir, ExportFormat::Markdown(true)).unwrap();
+        let mut other = NotesStore::in_memory().unwrap();
+        let report = other.import(dir).unwrap();
+        assert_eq!(report.saved(), 1);
+        assert_eq!(other.tagged("生活").unwrap()[0].content(), "today");
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use anyhow::Result;
+use crate::data::{Database, NoteMerge, NoteVersion, RecordState, SyncRecord, SyncState, VersionStore};
+use super::{PushOutcome, SyncRemote, SyncReport};
+
+/// 同步引擎：在本地数据库与远端之间交换笔记变更集
+pub struct SyncEngine<'a, R: SyncRemote> {
+    db: &'a mut Database,
+    remote: &'a mut R,
+    versions: VersionStore<'a>,
+    local_versions: HashMap<String, Vec<NoteVersion>>,  // 本次同步开始时读取的本地版本, 按笔记分组
+}
+
+impl<'a, R: SyncRemote> SyncEngine<'a, R> {
+    pub fn new(db: &'a mut Database, remote: &'a mut R) -> Self {
+        Self { db, remote, versions: VersionStore::Database, local_versions: HashMap::new() }
+    }
+
+    /// 指定本地版本历史的存储位置(默认为数据库)
+    pub fn with_versions(mut self, versions: VersionStore<'a>) -> Self {
+        self.versions = versions;
+        self
+    }
+
+    /// 执行一次完整同步
+    pub fn run(&mut self) -> Result<SyncReport> {
+        let cursor_key = format!("cursor:{}", self.remote.id());
+        let cursor = self.db.sync_meta(&cursor_key)?
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+
+        let changes = self.remote.fetch_changes(cursor)?;
+        let remote: HashMap<String, SyncRecord> = changes.records.into_iter()
+            .map(|r| (r.id().to_string(), r))
+            .collect();
+        let local: HashMap<String, SyncRecord> = self.db.load_sync_records()?.into_iter()
+            .map(|r| (r.id().to_string(), r))
+            .collect();
+        let states = self.db.load_sync_states(self.remote.scope())?;
+        // 一次读出所有本地版本, 避免逐篇笔记读取
+        self.local_versions.clear();
+        for version in self.versions.load_all(self.db)? {
+            self.local_versions.entry(version.note_id().to_string()).or_default().push(version);
+        }
+
+        let mut report = SyncReport { warnings: changes.warnings, ..Default::default() };
+        let ids: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+        for id in ids {
+            self.sync_note(
+                id,
+                local.get(id),
+                remote.get(id).cloned(),
+                states.get(id).copied(),
+                &mut report,
+            )?;
+        }
+
+        self.db.set_sync_meta(&cursor_key, Some(&changes.cursor.to_string()))?;
+        Ok(report)
+    }
+
+    // 同步单条笔记
+    fn sync_note(
+        &mut self,
+        id: &str,
+        local: Option<&SyncRecord>,
+        mut remote: Option<SyncRecord>,
+        state: Option<SyncState>,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        let state = state.unwrap_or_default();
+
+        // 上次同步留下的冲突尚未解决
+        if let Some(conflict_revision) = state.conflict_revision {
+            if remote.is_none() {
+                remote = self.remote.fetch_record(id)?;
+            }
+            if let (Some(local), Some(remote)) = (local, remote.as_ref())
+                && remote.revision() == conflict_revision {
+                if let Some(merge) = self.build_merge(local, remote)? {
+                    report.conflicts.push(merge);
+                }
+                return Ok(());
+            }
+        }
+
+        let local_changed = local.map(|r| r.revision()).unwrap_or_default() != state.local_revision;
+        let remote_changed = remote.as_ref().is_some_and(|r| r.revision() != state.remote_revision);
+
+        match (local, remote) {
+            (Some(local), Some(remote)) if local_changed && remote_changed => {
+                self.resolve_both_changed(local, &remote, state, report)
+            }
+            (Some(local), remote) if local_changed => {
+                let expected = remote.map(|r| r.revision())
+                    .or(Some(state.remote_revision).filter(|&rev| rev > 0));
+                self.push(local, expected, state, report)
+            }
+            (_, Some(remote)) if remote_changed => self.pull(&remote, report),
+            _ => Ok(()),
+        }
+    }
+
+    // 推送本地记录，远端已被修改时转为冲突处理
+    fn push(&mut self, local: &SyncRecord, expected: Option<u64>, state: SyncState, report: &mut SyncReport) -> Result<()> {
+        match self.remote.push(local, expected)? {
+            PushOutcome::Accepted(remote_revision) => {
+                report.pushed += 1;
+                self.push_versions(local.id())?;
+                self.db.save_sync_state(self.remote.scope(), local.id(), &SyncState {
+                    local_revision: local.revision(),
+                    remote_revision,
+                    conflict_revision: None,
+                }, local.note())?;
+                Ok(())
+            }
+            PushOutcome::Rejected(current) => self.resolve_both_changed(local, &current, state, report),
+        }
+    }
+
+    // 拉取远端记录并应用到本地
+    fn pull(&mut self, remote: &SyncRecord, report: &mut SyncReport) -> Result<()> {
+        let local_revision = self.db.apply_sync_record(remote)?;
+        if remote.state() == RecordState::Deleted {
+            report.deleted += 1;
+        } else {
+            report.pulled += 1;
+            self.pull_versions(remote.id())?;
+        }
+        self.db.save_sync_state(self.remote.scope(), remote.id(), &SyncState {
+            local_revision,
+            remote_revision: remote.revision(),
+            conflict_revision: None,
+        }, remote.note())?;
+        Ok(())
+    }
+
+    // 本地和远端都发生了修改
+    fn resolve_both_changed(&mut self, local: &SyncRecord, remote: &SyncRecord, state: SyncState, report: &mut SyncReport) -> Result<()> {
+        if local.same_content(remote) {
+            self.db.save_sync_state(self.remote.scope(), local.id(), &SyncState {
+                local_revision: local.revision(),
+                remote_revision: remote.revision(),
+                conflict_revision: None,
+            }, local.note())?;
+            return Ok(());
+        }
+
+        match (local.note(), remote.note()) {
+            (Some(_), Some(_)) => {
+                let Some(merge) = self.build_merge(local, remote)? else { return Ok(()) };
+                if let Some(note) = merge.to_note() {
+                    // 自动合并成功，保存后推送合并结果
+                    let merged_state = if local.state() == RecordState::Active || remote.state() == RecordState::Active {
+                        RecordState::Active
+                    } else {
+                        RecordState::Trashed
+                    };
+                    let mut merged = SyncRecord::new(note, merged_state, 0);
+                    let local_revision = self.db.apply_sync_record(&merged)?;
+                    merged.set_revision(local_revision);
+                    report.merged += 1;
+                    // 推送失败时保持原状态，下次同步重新处理
+                    if let PushOutcome::Accepted(remote_revision) = self.remote.push(&merged, Some(remote.revision()))? {
+                        self.db.save_sync_state(self.remote.scope(), local.id(), &SyncState {
+                            local_revision,
+                            remote_revision,
+                            conflict_revision: None,
+                        }, merged.note())?;
+                    }
+                } else {
+                    // 存在冲突，等待用户在合并编辑器中处理
+                    self.db.save_sync_state(self.remote.scope(), local.id(), &SyncState {
+                        conflict_revision: Some(remote.revision()),
+                        ..state
+                    }, None)?;
+                    report.conflicts.push(merge);
+                }
+                Ok(())
+            }
+            // 本地修改与远端删除冲突时保留修改
+            (Some(_), None) => self.push(local, Some(remote.revision()), state, report),
+            (None, Some(_)) => self.pull(remote, report),
+            (None, None) => {
+                self.db.save_sync_state(self.remote.scope(), local.id(), &SyncState {
+                    local_revision: local.revision(),
+                    remote_revision: remote.revision(),
+                    conflict_revision: None,
+                }, None)?;
+                Ok(())
+            }
+        }
+    }
+
+    // 上传远端缺少的本地版本历史
+    fn push_versions(&mut self, note_id: &str) -> Result<()> {
+        let remote: HashSet<String> = self.remote.fetch_versions(note_id)?.iter()
+            .map(|v| v.id().to_string())
+            .collect();
+        for version in self.local_versions.get(note_id).into_iter().flatten() {
+            if !remote.contains(version.id()) {
+                self.remote.push_version(version)?;
+            }
+        }
+        Ok(())
+    }
+
+    // 下载本地缺少的版本历史
+    fn pull_versions(&mut self, note_id: &str) -> Result<()> {
+        let local: HashSet<String> = self.local_versions.get(note_id).into_iter().flatten()
+            .map(|v| v.id().to_string())
+            .collect();
+        for version in self.remote.fetch_versions(note_id)? {
+            if !local.contains(version.id()) {
+                self.versions.save(self.db, &version)?;
+                self.local_versions.entry(note_id.to_string()).or_default().push(version);
+            }
+        }
+        Ok(())
+    }
+
+    // 构建三方合并：优先以上次同步的内容为祖先，否则从版本历史中查找
+    fn build_merge(&self, local: &SyncRecord, remote: &SyncRecord) -> Result<Option<NoteMerge>> {
+        let (Some(ours), Some(theirs)) = (local.note(), remote.note()) else {
+            return Ok(None);
+        };
+        let merge = match self.db.sync_base(self.remote.scope(), ours.id())? {
+            Some(base) => NoteMerge::new(Some(&NoteVersion::new("sync", &base)), ours, theirs),
+            None => {
+                let versions = self.versions.load(self.db, ours.id())?;
+                let base = NoteMerge::find_common_ancestor(&versions, ours, theirs);
+                NoteMerge::new(base, ours, theirs)
+            }
+        };
+        Ok(Some(merge))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::Note;
+    use crate::sync::FolderRemote;
+    use super::*;
+
+    fn sync(db: &mut Database, folder: &tempfile::TempDir) -> SyncReport {
+        let mut remote = FolderRemote::new(&folder.path().join("remote")).unwrap();
+        SyncEngine::new(db, &mut remote).run().unwrap()
+    }
+
+    fn find(db: &Database, id: &str) -> Option<Note> {
+        db.load_all_notes().unwrap().find_note(id)
+    }
+
+    #[test]
+    fn test_sync_create_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut a = Database::open(dir.path().join("a.db")).unwrap();
+        let mut b = Database::open(dir.path().join("b.db")).unwrap();
+
+        let mut note = Note::new("shared".to_string());
+        note.update_content("hello\n".to_string());
+        a.save_note(&note).unwrap();
+
+        assert_eq!(sync(&mut a, &dir).pushed, 1);
+        assert_eq!(sync(&mut b, &dir).pulled, 1);
+        assert_eq!(find(&b, note.id()).unwrap().content(), "hello\n");
+
+        // 再次同步不应产生任何变更
+        let report = sync(&mut b, &dir);
+        assert_eq!((report.pushed, report.pulled), (0, 0));
+
+        // 永久删除通过墓碑传播
+        b.delete_permanently(note.id()).unwrap();
+        assert_eq!(sync(&mut b, &dir).pushed, 1);
+        assert_eq!(sync(&mut a, &dir).deleted, 1);
+        assert!(find(&a, note.id()).is_none());
+    }
+
+    #[test]
+    fn test_sync_restore_deleted_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut a = Database::open(dir.path().join("a.db")).unwrap();
+        let mut b = Database::open(dir.path().join("b.db")).unwrap();
+
+        let mut note = Note::new("restored".to_string());
+        note.update_content("hello\n".to_string());
+        a.save_note(&note).unwrap();
+        sync(&mut a, &dir);
+        sync(&mut b, &dir);
+        a.delete_permanently(note.id()).unwrap();
+        sync(&mut a, &dir);
+        sync(&mut b, &dir);
+        assert!(find(&b, note.id()).is_none());
+
+        // 再次导入同一id的笔记后, 墓碑不应覆盖它
+        a.save_notes(std::slice::from_ref(&note)).unwrap();
+        assert_eq!(sync(&mut a, &dir).pushed, 1);
+        assert_eq!(sync(&mut b, &dir).pulled, 1);
+        assert_eq!(find(&b, note.id()).unwrap().content(), "hello\n");
+        sync(&mut a, &dir);
+        assert!(find(&a, note.id()).is_some());
+    }
+
+    #[test]
+    fn test_sync_trash_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut a = Database::open(dir.path().join("a.db")).unwrap();
+        let mut b = Database::open(dir.path().join("b.db")).unwrap();
+
+        let note = Note::new("to trash".to_string());
+        a.save_note(&note).unwrap();
+        sync(&mut a, &dir);
+        sync(&mut b, &dir);
+
+        a.move_to_trash(note.id()).unwrap();
+        sync(&mut a, &dir);
+        sync(&mut b, &dir);
+        assert!(find(&b, note.id()).is_none());
+        assert_eq!(b.get_deleted_notes().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_sync_merge_and_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut a = Database::open(dir.path().join("a.db")).unwrap();
+        let mut b = Database::open(dir.path().join("b.db")).unwrap();
+
+        let mut note = Note::new("shared".to_string());
+        note.update_content("one\ntwo\nthree\n".to_string());
+        a.save_note(&note).unwrap();
+        sync(&mut a, &dir);
+        sync(&mut b, &dir);
+        let mut note_b = find(&b, note.id()).unwrap();
+
+        // 双方修改不同的行，自动合并
+        note.update_content("one changed\ntwo\nthree\n".to_string());
+        a.save_note(&note).unwrap();
+        note_b.update_content("one\ntwo\nthree changed\n".to_string());
+        b.save_note(&note_b).unwrap();
+        sync(&mut a, &dir);
+        assert_eq!(sync(&mut b, &dir).merged, 1);
+        sync(&mut a, &dir);
+        assert_eq!(find(&a, note.id()).unwrap().content(), "one changed\ntwo\nthree changed\n");
+
+        // 双方修改同一行，产生冲突
+        let mut note = find(&a, note.id()).unwrap();
+        let mut note_b = find(&b, note.id()).unwrap();
+        note.update_content("ours\n".to_string());
+        a.save_note(&note).unwrap();
+        note_b.update_content("theirs\n".to_string());
+        b.save_note(&note_b).unwrap();
+        sync(&mut a, &dir);
+        assert_eq!(sync(&mut b, &dir).conflicts.len(), 1);
+        // 冲突未解决前会被重复报告，且不会覆盖远端
+        assert_eq!(sync(&mut b, &dir).conflicts.len(), 1);
+        assert_eq!(sync(&mut a, &dir).pulled, 0);
+    }
+}
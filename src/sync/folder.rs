@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use crate::data::{NoteVersion, SyncRecord};
+use super::{PushOutcome, RemoteChanges, SyncRemote};
+
+/// 以本地目录(网盘、Syncthing/Dropbox 共享目录等)作为远端
+pub struct FolderRemote {
+    root: PathBuf,
+}
+
+impl FolderRemote {
+    pub fn new(root: &Path) -> Result<Self> {
+        std::fs::create_dir_all(root.join("notes"))?;
+        Ok(Self { root: root.to_path_buf() })
+    }
+
+    // 笔记id一般为UUID，其他字符编码后再作为文件名
+    fn file_stem(id: &str) -> String {
+        if id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            id.to_string()
+        } else {
+            id.bytes().map(|b| format!("{:02x}", b)).collect()
+        }
+    }
+
+    fn record_path(&self, note_id: &str) -> PathBuf {
+        self.root.join("notes").join(format!("{}.json", Self::file_stem(note_id)))
+    }
+
+    fn versions_dir(&self, note_id: &str) -> PathBuf {
+        self.root.join("versions").join(Self::file_stem(note_id))
+    }
+
+    fn read_record(path: &Path) -> Result<SyncRecord> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+impl SyncRemote for FolderRemote {
+    fn id(&self) -> String {
+        format!("folder:{}", self.root.display())
+    }
+
+    // 目录远端没有变更日志，每次都返回全部记录
+    fn fetch_changes(&mut self, _cursor: u64) -> Result<RemoteChanges> {
+        let mut records = Vec::new();
+        let mut warnings = Vec::new();
+        for entry in std::fs::read_dir(self.root.join("notes"))? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            match Self::read_record(&path) {
+                Ok(record) => records.push(record),
+                Err(e) => warnings.push(format!("Skipped unreadable sync record {}: {}", path.display(), e)),
+            }
+        }
+        Ok(RemoteChanges { records, cursor: 0, warnings })
+    }
+
+    fn fetch_record(&mut self, note_id: &str) -> Result<Option<SyncRecord>> {
+        let path = self.record_path(note_id);
+        if path.exists() { Ok(Some(Self::read_record(&path)?)) } else { Ok(None) }
+    }
+
+    fn push(&mut self, record: &SyncRecord, expected: Option<u64>) -> Result<PushOutcome> {
+        let path = self.record_path(record.id());
+        let current = if path.exists() { Some(Self::read_record(&path)?) } else { None };
+
+        // 远端修订号与期望不一致说明其他设备已修改
+        if let Some(current) = current.as_ref()
+            && Some(current.revision()) != expected {
+            return Ok(PushOutcome::Rejected(Box::new(current.clone())));
+        }
+
+        let revision = current.map(|r| r.revision()).unwrap_or_default() + 1;
+        let mut record = record.clone();
+        record.set_revision(revision);
+
+        // 先写临时文件再重命名，避免同步工具读到写了一半的文件
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(&record)?)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(PushOutcome::Accepted(revision))
+    }
+
+    fn fetch_versions(&mut self, note_id: &str) -> Result<Vec<NoteVersion>> {
+        let dir = self.versions_dir(note_id);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut versions = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                versions.push(serde_json::from_str(&std::fs::read_to_string(&path)?)?);
+            }
+        }
+        Ok(versions)
+    }
+
+    fn push_version(&mut self, version: &NoteVersion) -> Result<()> {
+        let dir = self.versions_dir(version.note_id());
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.json", Self::file_stem(version.id())));
+        if !path.exists() {
+            std::fs::write(path, serde_json::to_string_pretty(version)?)?;
+        }
+        Ok(())
+    }
+}
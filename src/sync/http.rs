@@ -0,0 +1,135 @@
+use anyhow::Result;
+use crate::data::{NoteVersion, SyncRecord};
+use crate::server::api::{self, ChangesResponse, ErrorResponse, LoginRequest, LoginResponse, PushResponse};
+use super::{PushOutcome, RemoteChanges, SyncRemote};
+
+/// 自托管同步服务的客户端
+pub struct HttpRemote {
+    base_url: String,
+    token: String,
+    agent: ureq::Agent,
+}
+
+impl HttpRemote {
+    pub fn new(base_url: &str, token: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            agent: Self::agent(),
+        }
+    }
+
+    // 非2xx状态码由调用方自行处理
+    fn agent() -> ureq::Agent {
+        ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into()
+    }
+
+    /// 登录并获取令牌
+    pub fn login(base_url: &str, username: &str, password: &str) -> Result<LoginResponse> {
+        let url = format!("{}/api/login", base_url.trim_end_matches('/'));
+        let mut response = Self::agent().post(&url).send_json(LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        })?;
+        match response.status().as_u16() {
+            200 => Ok(response.body_mut().read_json()?),
+            status => Err(Self::error(status, response.body_mut().read_json().ok())),
+        }
+    }
+
+    /// 注销当前令牌
+    pub fn logout(&self) -> Result<()> {
+        let mut response = self.agent.post(self.url(&["logout"]))
+            .header("Authorization", self.auth())
+            .send_empty()?;
+        match response.status().as_u16() {
+            200 | 401 => Ok(()),
+            status => Err(Self::error(status, response.body_mut().read_json().ok())),
+        }
+    }
+
+    fn url(&self, segments: &[&str]) -> String {
+        let path: Vec<String> = segments.iter().map(|s| api::percent_encode(s)).collect();
+        format!("{}/api/{}", self.base_url, path.join("/"))
+    }
+
+    fn auth(&self) -> String {
+        format!("Bearer {}", self.token)
+    }
+
+    fn error(status: u16, body: Option<ErrorResponse>) -> anyhow::Error {
+        let message = body.map(|b| b.error).unwrap_or_default();
+        anyhow::anyhow!("server responded {}: {}", status, message)
+    }
+}
+
+impl SyncRemote for HttpRemote {
+    fn id(&self) -> String {
+        format!("http:{}", self.base_url)
+    }
+
+    fn fetch_changes(&mut self, cursor: u64) -> Result<RemoteChanges> {
+        let mut response = self.agent.get(self.url(&["changes"]))
+            .header("Authorization", self.auth())
+            .query("since", cursor.to_string())
+            .call()?;
+        match response.status().as_u16() {
+            200 => {
+                let changes: ChangesResponse = response.body_mut().read_json()?;
+                Ok(RemoteChanges { records: changes.records, cursor: changes.cursor, warnings: Vec::new() })
+            }
+            status => Err(Self::error(status, response.body_mut().read_json().ok())),
+        }
+    }
+
+    fn fetch_record(&mut self, note_id: &str) -> Result<Option<SyncRecord>> {
+        let mut response = self.agent.get(self.url(&["notes", note_id]))
+            .header("Authorization", self.auth())
+            .call()?;
+        match response.status().as_u16() {
+            200 => Ok(Some(response.body_mut().read_json()?)),
+            404 => Ok(None),
+            status => Err(Self::error(status, response.body_mut().read_json().ok())),
+        }
+    }
+
+    fn push(&mut self, record: &SyncRecord, expected: Option<u64>) -> Result<PushOutcome> {
+        let mut request = self.agent.put(self.url(&["notes", record.id()]))
+            .header("Authorization", self.auth());
+        if let Some(expected) = expected {
+            request = request.query("expected", expected.to_string());
+        }
+        let mut response = request.send_json(record)?;
+        match response.status().as_u16() {
+            200 => {
+                let push: PushResponse = response.body_mut().read_json()?;
+                Ok(PushOutcome::Accepted(push.revision))
+            }
+            409 => Ok(PushOutcome::Rejected(Box::new(response.body_mut().read_json()?))),
+            status => Err(Self::error(status, response.body_mut().read_json().ok())),
+        }
+    }
+
+    fn fetch_versions(&mut self, note_id: &str) -> Result<Vec<NoteVersion>> {
+        let mut response = self.agent.get(self.url(&["notes", note_id, "versions"]))
+            .header("Authorization", self.auth())
+            .call()?;
+        match response.status().as_u16() {
+            200 => Ok(response.body_mut().read_json()?),
+            status => Err(Self::error(status, response.body_mut().read_json().ok())),
+        }
+    }
+
+    fn push_version(&mut self, version: &NoteVersion) -> Result<()> {
+        let mut response = self.agent.put(self.url(&["notes", version.note_id(), "versions"]))
+            .header("Authorization", self.auth())
+            .send_json(version)?;
+        match response.status().as_u16() {
+            200 => Ok(()),
+            status => Err(Self::error(status, response.body_mut().read_json().ok())),
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use crate::data::{ExportNote, Note, NoteVersion, RecordState, SyncRecord, SyncScope, SyncState};
+use crate::io::{Exporter, Importer, exporter::sanitize_filename};
+use super::{PushOutcome, RemoteChanges, SyncRemote};
+
+// 文件已被删除(对应回收站)的修订号
+const REMOVED_REVISION: u64 = 1;
+// 加锁笔记的明文不写入镜像目录
+const SKIPPED_REVISION: u64 = 2;
+// 监视线程检查目录的间隔
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 镜像目录: 每个笔记一个带 front matter 的 .md 文件, 通过 front matter 中的 id 对应笔记,
+/// 文件的修改、新建和删除同步回数据库(删除的文件对应的笔记移入回收站)
+pub struct MirrorRemote {
+    root: PathBuf,
+    known: HashMap<String, u64>,        // 上次同步时各笔记文件的修订号
+    locked: HashSet<String>,            // 加锁的笔记, 目录中对应的文件被忽略
+    files: HashMap<String, PathBuf>,    // 笔记id对应的文件
+}
+
+impl MirrorRemote {
+    pub fn new(root: &Path, states: &HashMap<String, SyncState>, locked: HashSet<String>) -> Result<Self> {
+        std::fs::create_dir_all(root)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            known: states.iter().map(|(id, state)| (id.clone(), state.remote_revision)).collect(),
+            locked,
+            files: HashMap::new(),
+        })
+    }
+
+    // 文件内容的修订号(SQLite只能保存i64, 并避开保留值)
+    fn revision(data: &[u8]) -> u64 {
+        let digest = md5::compute(data);
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest.0[..8]);
+        (u64::from_le_bytes(bytes) & i64::MAX as u64).max(SKIPPED_REVISION + 1)
+    }
+
+    // 上次同步后文件被删除
+    fn is_removed(&self, note_id: &str) -> bool {
+        self.known.get(note_id).is_some_and(|&rev| rev != REMOVED_REVISION && rev != SKIPPED_REVISION)
+            && !self.files.contains_key(note_id)
+    }
+
+    // 读取文件, 没有id或id重复(复制的文件)时分配新id并写回 front matter
+    fn read_file(&mut self, path: &Path, warnings: &mut Vec<String>) -> Result<Option<SyncRecord>> {
+        let result = Importer::read_markdown(path)?;
+        let mut note = result.note().clone();
+        // front matter 无法解析时不作为新笔记, 避免原笔记被当作已删除
+        if note.id.is_none() && !result.warnings().is_empty() {
+            anyhow::bail!(result.warnings().join("; "));
+        }
+        warnings.extend(result.warnings().iter().map(|w| format!("{}: {}", path.display(), w)));
+
+        if let Some(id) = note.id.as_ref()
+            && self.files.contains_key(id) {
+            warnings.push(format!("Duplicate note id {} in {}, imported as a new note", id, path.display()));
+            note.id = None;
+        }
+        let id = match note.id.clone() {
+            Some(id) => id,
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                note.id = Some(id.clone());
+                write_file(path, &Exporter::render_markdown(&note)?)?;
+                id
+            }
+        };
+        self.files.insert(id.clone(), path.to_path_buf());
+        if self.locked.contains(&id) {
+            warnings.push(format!("Skipped {}: the note is locked", path.display()));
+            return Ok(None);
+        }
+
+        // 在其他编辑器中修改后 front matter 中的更新时间不会变化, 以文件修改时间为准
+        let data = std::fs::read(path)?;
+        let modified: DateTime<Utc> = std::fs::metadata(path)?.modified()?.into();
+        if note.updated.is_none_or(|updated| updated < modified) {
+            note.updated = Some(modified);
+        }
+        Ok(Some(SyncRecord::new(note.to_note(), RecordState::Active, Self::revision(&data))))
+    }
+
+    // 新笔记的文件名(按标题生成, 重名时添加序号)
+    fn new_path(&self, note: &Note) -> PathBuf {
+        let stem = match sanitize_filename(note.title()) {
+            title if title.is_empty() => "untitled".to_string(),
+            title => title,
+        };
+        let mut path = self.root.join(format!("{}.md", stem));
+        let mut idx = 1;
+        while path.exists() {
+            idx += 1;
+            path = self.root.join(format!("{}-{}.md", stem, idx));
+        }
+        path
+    }
+
+    fn remove_file(&mut self, note_id: &str) -> Result<()> {
+        if let Some(path) = self.files.remove(note_id)
+            && path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl SyncRemote for MirrorRemote {
+    fn id(&self) -> String {
+        format!("mirror:{}", self.root.display())
+    }
+
+    fn scope(&self) -> SyncScope {
+        SyncScope::Mirror
+    }
+
+    // 每次都扫描全部文件
+    fn fetch_changes(&mut self, _cursor: u64) -> Result<RemoteChanges> {
+        let mut paths = Vec::new();
+        markdown_files(&self.root, &mut paths)?;
+        paths.sort();
+
+        self.files.clear();
+        let mut records = Vec::new();
+        let mut warnings = Vec::new();
+        let mut unreadable = false;
+        for path in paths.iter() {
+            match self.read_file(path, &mut warnings) {
+                Ok(record) => records.extend(record),
+                Err(e) => {
+                    unreadable = true;
+                    warnings.push(format!("Skipped unreadable mirror file {}: {}", path.display(), e));
+                }
+            }
+        }
+
+        // 有文件无法读取时暂不处理删除
+        if !unreadable {
+            let removed: Vec<String> = self.known.keys().filter(|id| self.is_removed(id)).cloned().collect();
+            records.extend(removed.iter().map(|id| SyncRecord::trashed(id, REMOVED_REVISION)));
+        }
+        Ok(RemoteChanges { records, cursor: 0, warnings })
+    }
+
+    fn fetch_record(&mut self, note_id: &str) -> Result<Option<SyncRecord>> {
+        if let Some(path) = self.files.get(note_id).cloned()
+            && path.exists() {
+            let mut warnings = Vec::new();
+            self.files.remove(note_id);
+            return self.read_file(&path, &mut warnings);
+        }
+        Ok(self.is_removed(note_id).then(|| SyncRecord::trashed(note_id, REMOVED_REVISION)))
+    }
+
+    fn push(&mut self, record: &SyncRecord, expected: Option<u64>) -> Result<PushOutcome> {
+        // 文件在上次同步后被其他编辑器修改
+        if let Some(current) = self.fetch_record(record.id())?
+            && current.note().is_some()
+            && Some(current.revision()) != expected {
+            return Ok(PushOutcome::Rejected(Box::new(current)));
+        }
+
+        let revision = match record.note().filter(|_| record.state() == RecordState::Active) {
+            Some(note) if note.is_locked() => {
+                self.remove_file(record.id())?;
+                SKIPPED_REVISION
+            }
+            Some(note) => {
+                // 已有的文件保持原路径(标题修改后不重命名)
+                let path = match self.files.get(record.id()) {
+                    Some(path) => path.clone(),
+                    None => self.new_path(note),
+                };
+                let text = Exporter::render_markdown(&ExportNote::from_note(note))?;
+                write_file(&path, &text)?;
+                self.files.insert(record.id().to_string(), path);
+                Self::revision(text.as_bytes())
+            }
+            // 回收站中和已删除的笔记不保留文件
+            None => {
+                self.remove_file(record.id())?;
+                REMOVED_REVISION
+            }
+        };
+        self.known.insert(record.id().to_string(), revision);
+        Ok(PushOutcome::Accepted(revision))
+    }
+
+    // 镜像目录不保存版本历史
+    fn fetch_versions(&mut self, _note_id: &str) -> Result<Vec<NoteVersion>> {
+        Ok(Vec::new())
+    }
+
+    fn push_version(&mut self, _version: &NoteVersion) -> Result<()> {
+        Ok(())
+    }
+}
+
+// 先写临时文件再重命名, 避免其他编辑器读到写了一半的文件
+fn write_file(path: &Path, text: &str) -> Result<()> {
+    let tmp_path = path.with_extension("md.tmp");
+    std::fs::write(&tmp_path, text)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+// 递归查找目录中的Markdown文件(跳过 .git、.obsidian 等隐藏目录)
+fn markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            markdown_files(&path, files)?;
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 镜像目录监视: 后台线程定期比较Markdown文件的修改时间和大小
+pub struct MirrorWatcher {
+    root: PathBuf,
+    changed: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl MirrorWatcher {
+    pub fn spawn(root: &Path) -> Self {
+        let changed = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (dir, changed_flag, stopped_flag) = (root.to_path_buf(), changed.clone(), stopped.clone());
+        std::thread::spawn(move || {
+            let mut last = fingerprint(&dir);
+            while !stopped_flag.load(Ordering::Relaxed) {
+                std::thread::sleep(POLL_INTERVAL);
+                let current = fingerprint(&dir);
+                if current != last {
+                    changed_flag.store(true, Ordering::Relaxed);
+                    last = current;
+                }
+            }
+        });
+        Self { root: root.to_path_buf(), changed, stopped }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // 上次调用后目录中的文件是否有变化
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+}
+
+impl Drop for MirrorWatcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+// 目录中所有Markdown文件的路径、修改时间和大小(目录无法读取时为空)
+fn fingerprint(root: &Path) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    let mut files = Vec::new();
+    if markdown_files(root, &mut files).is_err() {
+        return Vec::new();
+    }
+    let mut fingerprint: Vec<_> = files.into_iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(&path).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let len = metadata.map(|m| m.len()).unwrap_or_default();
+            (path, modified, len)
+        })
+        .collect();
+    fingerprint.sort();
+    fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::Database;
+    use crate::sync::{SyncEngine, SyncReport};
+    use super::*;
+
+    fn mirror(db: &mut Database, root: &Path) -> SyncReport {
+        let states = db.load_sync_states(SyncScope::Mirror).unwrap();
+        let mut remote = MirrorRemote::new(root, &states, HashSet::new()).unwrap();
+        SyncEngine::new(db, &mut remote).run().unwrap()
+    }
+
+    fn find(db: &Database, id: &str) -> Option<Note> {
+        db.load_all_notes().unwrap().find_note(id)
+    }
+
+    #[test]
+    fn test_mirror_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("mirror");
+        let mut db = Database::open(dir.path().join("notes.db")).unwrap();
+
+        let mut note = Note::new("周报: 第一周".to_string());
+        note.update_content("one\ntwo\n".to_string());
+        db.save_note(&note).unwrap();
+
+        // 笔记写入以标题命名的文件, 再次同步没有变化
+        assert_eq!(mirror(&mut db, &root).pushed, 1);
+        let path = root.join("周报 第一周.md");
+        assert!(std::fs::read_to_string(&path).unwrap().contains(&format!("id: {}", note.id())));
+        let report = mirror(&mut db, &root);
+        assert_eq!((report.pushed, report.pulled), (0, 0));
+
+        // 文件的修改同步回数据库
+        let text = std::fs::read_to_string(&path).unwrap().replace("two\n", "two changed\n");
+        std::fs::write(&path, text).unwrap();
+        assert_eq!(mirror(&mut db, &root).pulled, 1);
+        assert_eq!(find(&db, note.id()).unwrap().content(), "one\ntwo changed\n");
+
+        // 新建的文件分配id后导入
+        std::fs::write(root.join("new.md"), "hello\n").unwrap();
+        assert_eq!(mirror(&mut db, &root).pulled, 1);
+        assert!(std::fs::read_to_string(root.join("new.md")).unwrap().starts_with("---\nid: "));
+
+        // 删除文件后笔记移入回收站, 恢复后重新写入文件
+        std::fs::remove_file(&path).unwrap();
+        mirror(&mut db, &root);
+        assert!(find(&db, note.id()).is_none());
+        assert_eq!(db.get_deleted_notes().unwrap().len(), 1);
+        db.restore_from_trash(note.id()).unwrap();
+        assert_eq!(mirror(&mut db, &root).pushed, 1);
+        assert!(path.exists());
+
+        // 双方修改同一行时产生冲突, 文件保持不变
+        let mut note = find(&db, note.id()).unwrap();
+        note.update_content("ours\ntwo changed\n".to_string());
+        db.save_note(&note).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap().replace("one\n", "theirs\n");
+        std::fs::write(&path, text).unwrap();
+        assert_eq!(mirror(&mut db, &root).conflicts.len(), 1);
+        assert!(std::fs::read_to_string(&path).unwrap().contains("theirs\n"));
+    }
+}
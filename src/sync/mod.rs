@@ -0,0 +1,56 @@
+pub mod engine;
+pub mod folder;
+pub mod http;
+pub mod mirror;
+
+pub use engine::SyncEngine;
+pub use folder::FolderRemote;
+pub use http::HttpRemote;
+pub use mirror::{MirrorRemote, MirrorWatcher};
+
+use anyhow::Result;
+use crate::data::{NoteMerge, NoteVersion, SyncRecord, SyncScope};
+
+// 远端变更集
+pub struct RemoteChanges {
+    pub records: Vec<SyncRecord>,
+    pub cursor: u64,        // 下次增量拉取的游标
+    pub warnings: Vec<String>,  // 无法读取而跳过的记录
+}
+
+// 推送结果
+pub enum PushOutcome {
+    Accepted(u64),          // 推送成功，返回远端新的修订号
+    Rejected(Box<SyncRecord>),  // 远端已被修改，返回远端当前记录
+}
+
+/// 同步远端(同步目录、服务器等)
+pub trait SyncRemote {
+    // 远端标识(用于区分游标)
+    fn id(&self) -> String;
+    // 拉取游标之后的变更
+    fn fetch_changes(&mut self, cursor: u64) -> Result<RemoteChanges>;
+    // 获取单条记录的当前状态
+    fn fetch_record(&mut self, note_id: &str) -> Result<Option<SyncRecord>>;
+    // 推送记录，expected 为期望的远端当前修订号(远端不存在时为None)
+    fn push(&mut self, record: &SyncRecord, expected: Option<u64>) -> Result<PushOutcome>;
+    // 获取笔记的版本历史
+    fn fetch_versions(&mut self, note_id: &str) -> Result<Vec<NoteVersion>>;
+    // 上传版本(版本不可变，按id幂等)
+    fn push_version(&mut self, version: &NoteVersion) -> Result<()>;
+    // 同步状态的作用域
+    fn scope(&self) -> SyncScope {
+        SyncScope::Remote
+    }
+}
+
+// 同步报告
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub deleted: usize,
+    pub merged: usize,
+    pub conflicts: Vec<NoteMerge>,
+    pub warnings: Vec<String>,
+}
@@ -1,22 +1,35 @@
+use std::path::Path;
 use std::sync::Arc;
 use egui::{FontData, FontDefinitions, FontFamily};
 use crate::AppState;
+use crate::data::Database;
 use crate::services::{SettingsService, Theme};
-use super::app_layout::AppLayout;
+use crate::utils::{fonts::{SOURCE_HAN_SANS_BOLD, SOURCE_HAN_SANS_REGULAR}, logger};
+use super::{app_layout::AppLayout, unlock_view::UnlockView};
 
 pub struct NoteApp {
-    state: AppState,
+    // 保险库解锁前为None
+    state: Option<AppState>,
+    unlock_view: Option<UnlockView>,
     layout: AppLayout,
 }
 
 impl NoteApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> anyhow::Result<Self> {
-        let state = AppState::new()?;
+        // 日志文件与数据库同在数据目录下
+        if let Err(e) = logger::init(Path::new(".")) {
+            crate::log_warn!("init"; "failed to create log file: {}", e);
+        }
+        let db = Database::new()?;
+        let (state, unlock_view) = if db.is_locked()? {
+            (None, Some(UnlockView::new(db)))
+        } else {
+            (Some(AppState::with_database(db)?), None)
+        };
 
-        // 设置初始主题
-        match state.theme() {
-            Theme::Dark => cc.egui_ctx.set_visuals(egui::Visuals::dark()),
-            _ => ()
+        // 设置初始主题(解锁前使用默认的暗色主题)
+        if state.as_ref().map(|s| s.theme()).unwrap_or(Theme::Dark) == Theme::Dark {
+            cc.egui_ctx.set_visuals(egui::Visuals::dark());
         }
 
         // 设置字体
@@ -24,6 +37,7 @@ impl NoteApp {
 
         Ok(Self {
             state,
+            unlock_view,
             layout: AppLayout::new(),
         })
     }
@@ -34,13 +48,13 @@ impl NoteApp {
         // 添加常规字体
         fonts.font_data.insert(
             "source_han_sans_regular".to_owned(),
-            Arc::new(FontData::from_static(include_bytes!("../../assets/fonts/SourceHanSans-Regular.ttc"))),
+            Arc::new(FontData::from_static(SOURCE_HAN_SANS_REGULAR)),
         );
 
         // 添加粗体字体
         fonts.font_data.insert(
             "source_han_sans_bold".to_owned(),
-            Arc::new(FontData::from_static(include_bytes!("../../assets/fonts/SourceHanSans-Bold.ttc"))),
+            Arc::new(FontData::from_static(SOURCE_HAN_SANS_BOLD)),
         );
 
         // 修改字体族配置
@@ -68,7 +82,28 @@ impl NoteApp {
 
 impl eframe::App for NoteApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.layout.show(ctx, &mut self.state);
+        // 保险库解锁界面
+        if let Some(unlock_view) = self.unlock_view.as_mut()
+            && let Some(db) = unlock_view.show(ctx) {
+            match AppState::with_database(db) {
+                Ok(state) => {
+                    self.state = Some(state);
+                    self.unlock_view = None;
+                }
+                Err(e) => {
+                    crate::log_error!("load_notes"; "failed to load notes after unlocking: {}", e);
+                    // 数据库已交给加载失败的状态, 重新打开以便再次解锁
+                    let db = Database::new()
+                        .inspect_err(|e| crate::log_error!("load_notes"; "failed to reopen the database: {}", e))
+                        .ok();
+                    unlock_view.reset(db, e.to_string());
+                }
+            }
+        }
+
+        if let Some(state) = self.state.as_mut() {
+            self.layout.show(ctx, state);
+        }
 
         // // 自动保存检查
         // if self.state.current_note_is_modified() {
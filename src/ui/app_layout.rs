@@ -1,6 +1,6 @@
-use super::{menu_bar::MenuBar, navigation_bar::NavigationBar, sidebar::Sidebar, editor::EditorPanel, status_bar::StatusBar, version_history_view::VersionHistoryView};
+use super::{menu_bar::MenuBar, navigation_bar::NavigationBar, sidebar::Sidebar, editor::EditorPanel, status_bar::StatusBar, version_history_view::VersionHistoryView, merge_editor_view::MergeEditorView, log_view::LogView, notification_view::NotificationView, import_report_view::ImportReportView, import_wizard_view::ImportWizardView, restore_backup_view::RestoreBackupView};
 use crate::AppState;
-use crate::services::TabService;
+use crate::services::{LockService, MirrorService, NotifyService, TabService};
 
 pub struct AppLayout {
     menu_bar: MenuBar,
@@ -11,6 +11,12 @@ pub struct AppLayout {
 
     show_view: Option<ShowView>,
     version_history: VersionHistoryView,
+    merge_editor: MergeEditorView,
+    log_view: LogView,
+    notification_view: NotificationView,
+    import_report: ImportReportView,
+    import_wizard: ImportWizardView,
+    restore_backup: RestoreBackupView,
 }
 
 impl AppLayout {
@@ -23,20 +29,50 @@ impl AppLayout {
             status_bar: StatusBar::default(),
             show_view: None,
             version_history: VersionHistoryView::default(),
+            merge_editor: MergeEditorView::default(),
+            log_view: LogView::default(),
+            notification_view: NotificationView::default(),
+            import_report: ImportReportView::default(),
+            import_wizard: ImportWizardView::default(),
+            restore_backup: RestoreBackupView::default(),
         }
     }
 
     pub fn show(&mut self, ctx: &egui::Context, state: &mut AppState) {
+        // 空闲一段时间后自动重新锁定已解锁的笔记
+        if ctx.input(|i| !i.events.is_empty() || i.pointer.is_moving()) {
+            state.touch_activity();
+        }
+        match state.relock_idle_notes() {
+            Ok(Some(remaining)) => ctx.request_repaint_after(remaining),
+            Ok(None) => (),
+            Err(e) => state.notify_error("relock_idle_notes", None, format!("Failed to relock idle notes: {}", e)),
+        }
+        // 镜像目录中的文件或笔记有变化时双向同步
+        match state.poll_mirror() {
+            Ok(Some(remaining)) => ctx.request_repaint_after(remaining),
+            Ok(None) => (),
+            Err(e) => state.notify_error("mirror", None, format!("Failed to mirror notes: {}", e)),
+        }
+
         // 顶部菜单栏
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             if let Some(view) = self.menu_bar.show(ui, state) {
+                // 先解析待导入的文件或备份清单再打开对应窗口
+                match &view {
+                    ShowView::ShowImportWizard(path) => self.import_wizard.open(path, state),
+                    ShowView::ShowRestoreBackup(path) => self.restore_backup.open(path, state),
+                    _ => (),
+                }
                 self.show_view = Some(view);
             }
         });
 
         // 底部状态栏
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
-            self.status_bar.show(ui, state);
+            if let Some(view) = self.status_bar.show(ui, state) {
+                self.show_view = Some(view);
+            }
         });
 
         // 左侧导航栏
@@ -68,13 +104,57 @@ impl AppLayout {
                         self.show_view = None;
                     }
                 }
+                ShowView::ShowMergeEditor => {
+                    self.merge_editor.open();
+                    if !self.merge_editor.show(ctx, state) {
+                        self.show_view = None;
+                    }
+                }
+                ShowView::ShowLogs => {
+                    self.log_view.open();
+                    if !self.log_view.show(ctx, state) {
+                        self.show_view = None;
+                    }
+                }
+                ShowView::ShowImportWizard(_) => {
+                    if !self.import_wizard.show(ctx, state) {
+                        // 确认导入后显示导入报告
+                        self.show_view = self.import_wizard.committed().then_some(ShowView::ShowImportReport);
+                    }
+                }
+                ShowView::ShowRestoreBackup(_) => {
+                    if !self.restore_backup.show(ctx, state) {
+                        self.show_view = None;
+                    }
+                }
+                ShowView::ShowImportReport => {
+                    self.import_report.open();
+                    if !self.import_report.show(ctx, state) {
+                        self.show_view = None;
+                    }
+                }
+                ShowView::ShowNotifications => {
+                    self.notification_view.open();
+                    if !self.notification_view.show(ctx, state) {
+                        self.show_view = None;
+                    }
+                }
             }
         }
+
+        // 右下角的通知提示框
+        self.notification_view.show_toasts(ctx, state);
     }
 }
 
 // 显示视图
 pub enum ShowView {
     ShowVersionHistory,
+    ShowMergeEditor,
+    ShowLogs,
+    ShowImportWizard(std::path::PathBuf),
+    ShowImportReport,
+    ShowRestoreBackup(std::path::PathBuf),
+    ShowNotifications,
     // 其他动作...
 }
\ No newline at end of file
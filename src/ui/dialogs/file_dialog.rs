@@ -1,9 +1,10 @@
 use std::path::PathBuf;
 use rfd::FileDialog;
+use crate::io::BACKUP_EXTENSION;
 
 pub fn pick_available_file() -> Option<PathBuf> {
     FileDialog::new()
-        .add_filter("Markdown,JSON", &["md", "markdown", "json"])
+        .add_filter("Markdown,JSON,Evernote,Joplin,Notion", &["md", "markdown", "json", "enex", "jex", "zip"])
         .pick_file()
 }
 
@@ -23,4 +24,45 @@ pub fn save_json_file(default_title: &str) -> Option<PathBuf> {
         .add_filter("JSON", &["json"])
         .set_file_name(&format!("{}.json", default_title))
         .save_file()
-}
\ No newline at end of file
+}
+
+pub fn save_pdf_file(default_title: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter("PDF", &["pdf"])
+        .set_file_name(format!("{}.pdf", default_title))
+        .save_file()
+}
+
+pub fn save_html_file(default_title: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter("HTML", &["html", "htm"])
+        .set_file_name(format!("{}.html", default_title))
+        .save_file()
+}
+
+pub fn save_docx_file(default_title: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter("Word", &["docx"])
+        .set_file_name(format!("{}.docx", default_title))
+        .save_file()
+}
+
+pub fn save_odt_file(default_title: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter("OpenDocument", &["odt"])
+        .set_file_name(format!("{}.odt", default_title))
+        .save_file()
+}
+
+pub fn save_backup_file() -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter("Cloud Notepad Backup", &[BACKUP_EXTENSION])
+        .set_file_name(format!("notes-{}.{}", chrono::Local::now().format("%Y%m%d"), BACKUP_EXTENSION))
+        .save_file()
+}
+
+pub fn pick_backup_file() -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter("Cloud Notepad Backup", &[BACKUP_EXTENSION])
+        .pick_file()
+}
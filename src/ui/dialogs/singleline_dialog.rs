@@ -6,6 +6,7 @@ pub struct SinglelineDialog {
     hint_text: String,
     error_text: String,
     is_open: bool,
+    is_password: bool,
     input: String,
     validation_error: Option<String>,
 }
@@ -18,11 +19,18 @@ impl SinglelineDialog {
             hint_text: hint_text.to_string(),
             error_text: error_text.to_string(),
             is_open: false,
+            is_password: false,
             input: String::new(),
             validation_error: None,
         }
     }
 
+    /// 以密码形式显示输入内容
+    pub fn password(mut self) -> Self {
+        self.is_password = true;
+        self
+    }
+
     pub fn set_input(&mut self, text: &str) {
         self.input = text.to_string();
     }
@@ -44,7 +52,8 @@ impl SinglelineDialog {
 
                 // 文本输入框
                 let text_edit = egui::TextEdit::singleline(&mut self.input)
-                    .hint_text(service.t(&self.hint_text));
+                    .hint_text(service.t(&self.hint_text))
+                    .password(self.is_password);
 
                 let response = ui.add(text_edit);
 
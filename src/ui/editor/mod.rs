@@ -8,13 +8,15 @@ use header::EditorHeader;
 use body::EditorBody;
 use super::dialogs::singleline_dialog::SinglelineDialog;
 
-use crate::{services::{NoteService, TabService, VersionService}, i18n::Translate};
+use crate::{services::{LockService, NoteService, NotifyService, TabService, VersionService}, i18n::Translate};
 
 pub struct EditorPanel {
     tabs: EditorTabs,
     header: EditorHeader,
     body: EditorBody,
     save_version_dialog: SinglelineDialog,
+    unlock_password: String,
+    unlock_error: Option<String>,
 }
 
 impl Default for EditorPanel {
@@ -29,14 +31,23 @@ impl Default for EditorPanel {
                 "what's changed in this version?", 
                 "comment cannot be empty!",
             ),
+            unlock_password: String::new(),
+            unlock_error: None,
         }
     }
 }
 
 impl EditorPanel {
     pub fn show<T>(&mut self, ui: &mut egui::Ui, service: &mut T) 
-        where T: NoteService + TabService + VersionService + Translate {
-        if let Some(mut note) = service.current_note() {
+        where T: NoteService + TabService + VersionService + LockService + NotifyService + Translate {
+        if let Some(note) = service.current_note()
+            && note.is_sealed() {
+            // 加锁笔记显示解锁提示而不是内容
+            ui.vertical(|ui| {
+                self.tabs.show(ui, service);
+                self.show_unlock_prompt(ui, note.id(), service);
+            });
+        } else if let Some(mut note) = service.current_note() {
             // 检测快捷键
             self.check_shortcut(ui.ctx());
             // 显示编辑区
@@ -58,14 +69,12 @@ impl EditorPanel {
             });
             if flag {
                 if let Err(e) = service.update_note(note.clone()) {
-                    eprintln!("更新笔记失败: {}", e);
-                    // 可以在这里添加错误提示到UI
-                    ui.label(egui::RichText::new("Failed to delete!").color(egui::Color32::RED));
+                    service.notify_error("update_note", Some(note.id()), format!("Failed to save note: {}", e));
                 }
             }
             if let Some(comment) = self.save_version_dialog.show(ui.ctx(), service) {
                 if let Err(e) = service.save_version(&comment, &note) {
-                    eprintln!("保存版本失败: {}", e);
+                    service.notify_error("save_version", Some(note.id()), format!("Failed to save version: {}", e));
                 }
             }
         } else {
@@ -73,6 +82,31 @@ impl EditorPanel {
         }
     }
 
+    fn show_unlock_prompt<T: LockService + Translate>(&mut self, ui: &mut egui::Ui, note_id: &str, service: &mut T) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 3.0);
+            ui.heading("🔒");
+            ui.label(service.t("this note is locked"));
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.unlock_password)
+                    .password(true)
+                    .hint_text(service.t("password"))
+                    .desired_width(200.0)
+            );
+            let submitted = ui.button(service.t("unlock")).clicked()
+                || response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if submitted {
+                self.unlock_error = service.unlock_note(note_id, &self.unlock_password)
+                    .err()
+                    .map(|e| e.to_string());
+                self.unlock_password.clear();
+            }
+            if let Some(error) = &self.unlock_error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        });
+    }
+
     // 检测快捷键
     fn check_shortcut(&mut self, ctx: &egui::Context) {
         // 检测 Ctrl+Shift+S
@@ -1,6 +1,6 @@
 use crate::{
     ui::dialogs::singleline_dialog::SinglelineDialog,
-    services::{NoteService, TabService},
+    services::{LockService, NoteService, NotifyService, TabService},
     i18n::Translate,
 };
 
@@ -10,6 +10,8 @@ pub(super) struct EditorTabs {
     pub(super) split_view: bool,
     selected_note_id: Option<String>,
     title_dialog: SinglelineDialog,
+    lock_dialog: SinglelineDialog,
+    remove_lock_dialog: SinglelineDialog,
 }
 
 impl Default for EditorTabs {
@@ -24,12 +26,24 @@ impl Default for EditorTabs {
                 "", 
                 "title cannot be empty!",
             ),
+            lock_dialog: SinglelineDialog::new(
+                "lock note",
+                "enter a password for this note",
+                "",
+                "password cannot be empty!",
+            ).password(),
+            remove_lock_dialog: SinglelineDialog::new(
+                "remove lock",
+                "enter the password of this note",
+                "",
+                "password cannot be empty!",
+            ).password(),
         }
     }
 }
 
 impl EditorTabs {
-    pub fn show<T: NoteService + TabService + Translate>(&mut self, ui: &mut egui::Ui, service: &mut T) {
+    pub fn show<T: NoteService + TabService + LockService + NotifyService + Translate>(&mut self, ui: &mut egui::Ui, service: &mut T) {
         ui.horizontal(|ui| {
             // 文件标签
             let recent_note_ids: Vec<String> = service.recent_notes().iter().map(|&s| s.to_owned()).collect();
@@ -39,8 +53,13 @@ impl EditorTabs {
 
                     for note_id in recent_note_ids.iter() {
                         if let Some(note) = service.get_note(note_id) {
+                            let title = if note.is_locked() {
+                                format!("🔒 {}", note.title())
+                            } else {
+                                note.title().to_string()
+                            };
                             let tab_response = ui.add(
-                                egui::Button::new(note.title())
+                                egui::Button::new(title)
                                     .fill(if service.current_note_id_equals(note_id) {
                                         ui.visuals().widgets.active.bg_fill
                                     } else {
@@ -68,6 +87,26 @@ impl EditorTabs {
                                     self.title_dialog.set_input(note.title());
                                     ui.close_menu();
                                 }
+                                ui.separator();
+                                if !note.is_locked() {
+                                    if ui.button(service.t("lock note")).clicked() {
+                                        self.selected_note_id = Some(note_id.clone());
+                                        self.lock_dialog.open();
+                                        ui.close_menu();
+                                    }
+                                } else {
+                                    if !note.is_sealed() && ui.button(service.t("lock now")).clicked() {
+                                        if let Err(e) = service.relock_note(note_id) {
+                                            service.notify_error("relock_note", Some(note_id), format!("Failed to lock note: {}", e));
+                                        }
+                                        ui.close_menu();
+                                    }
+                                    if ui.button(service.t("remove lock")).clicked() {
+                                        self.selected_note_id = Some(note_id.clone());
+                                        self.remove_lock_dialog.open();
+                                        ui.close_menu();
+                                    }
+                                }
                             });
 
                             // 关闭按钮
@@ -90,14 +129,22 @@ impl EditorTabs {
                 ui.separator();
             })
         });
+        if let Some(password) = self.lock_dialog.show(ui.ctx(), service)
+            && let Some(note_id) = &self.selected_note_id
+            && let Err(e) = service.lock_note(note_id, &password) {
+            service.notify_error("lock_note", Some(note_id), format!("Failed to set note password: {}", e));
+        }
+        if let Some(password) = self.remove_lock_dialog.show(ui.ctx(), service)
+            && let Some(note_id) = &self.selected_note_id
+            && let Err(e) = service.remove_note_lock(note_id, &password) {
+            service.notify_error("remove_note_lock", Some(note_id), format!("Failed to remove note password: {}", e));
+        }
         if let Some(title) = self.title_dialog.show(ui.ctx(), service) {
             if let Some(note_id) = &self.selected_note_id {
                 if let Some(mut note) = service.get_note(note_id) {
                     if note.update_title(title) {
                         if let Err(e) = service.update_note(note.clone()) {
-                            eprintln!("更新笔记标题失败: {}", e);
-                            // 可以在这里添加错误提示到UI
-                            ui.label(egui::RichText::new("Failed to update note title!").color(egui::Color32::RED));
+                            service.notify_error("rename_note", Some(note_id), format!("Failed to rename note: {}", e));
                         }
                     }
                 }
@@ -0,0 +1,73 @@
+use crate::{
+    i18n::Translate,
+    io::ImportStatus,
+    services::{IoService, NotifyService},
+};
+use super::dialogs::file_dialog;
+
+/// 导入报告窗口
+#[derive(Default)]
+pub struct ImportReportView {
+    is_open: bool,
+    problems_only: bool,
+}
+
+impl ImportReportView {
+    pub fn show<T: IoService + NotifyService + Translate>(&mut self, ctx: &egui::Context, service: &T) -> bool {
+        if !self.is_open { return false; }
+        let Some(report) = service.last_import_report() else {
+            self.is_open = false;
+            return false;
+        };
+
+        let mut is_open = self.is_open;
+        egui::Window::new(service.t("import report"))
+            .open(&mut is_open)
+            .resizable(true)
+            .default_width(720.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                ui.label(report.source.display().to_string());
+                ui.label(report.summary());
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.problems_only, service.t("problems only"));
+                    if ui.button(service.t("export report")).clicked()
+                        && let Some(path) = file_dialog::save_json_file("import_report")
+                        && let Err(e) = service.export_import_report(&path) {
+                        service.notify_error("export_import_report", None, format!("Failed to export report: {}", e));
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                    egui::Grid::new("import_report_grid").striped(true).num_columns(4).show(ui, |ui| {
+                        for entry in report.entries.iter()
+                            .filter(|e| !self.problems_only || !e.warnings.is_empty()
+                                || matches!(e.status, ImportStatus::Failed(_) | ImportStatus::Conflicted)) {
+                            let color = match entry.status {
+                                ImportStatus::Failed(_) => egui::Color32::RED,
+                                ImportStatus::Conflicted | ImportStatus::Skipped => egui::Color32::YELLOW,
+                                _ => ui.visuals().text_color(),
+                            };
+                            ui.colored_label(color, service.t(entry.status.as_str()));
+                            ui.label(entry.title.as_deref().unwrap_or("-"));
+                            ui.label(entry.source.display().to_string());
+                            // 失败原因和警告
+                            let mut details = entry.warnings.clone();
+                            if let ImportStatus::Failed(reason) = &entry.status {
+                                details.insert(0, reason.clone());
+                            }
+                            ui.label(details.join("; "));
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.is_open = is_open;
+        self.is_open
+    }
+
+    pub fn open(&mut self) {
+        self.is_open = true;
+    }
+}
@@ -0,0 +1,167 @@
+use std::path::Path;
+use crate::{
+    i18n::Translate,
+    io::{ImportConflict, ImportPlan, MergeStrategy},
+    services::{IoService, NotifyService},
+    utils::diff::{self, DiffOp},
+};
+
+/// 导入向导：预览待导入的笔记并逐个选择冲突处理方式
+#[derive(Default)]
+pub struct ImportWizardView {
+    plan: Option<ImportPlan>,
+    // 每个待导入笔记与已有笔记的差异(打开向导时计算)
+    diffs: Vec<Vec<DiffLine>>,
+    selected: Option<usize>,
+    committed: bool,
+}
+
+// 预览中的一行差异
+enum DiffLine {
+    Equal(String),
+    Insert(String),
+    Delete(String),
+}
+
+impl ImportWizardView {
+    // 解析待导入的文件(不写入), 失败时不打开向导
+    pub fn open<T: IoService + NotifyService>(&mut self, input_path: &Path, service: &T) {
+        self.selected = None;
+        self.committed = false;
+        self.plan = match service.preview_import(input_path) {
+            Ok(plan) => {
+                self.diffs = plan.candidates.iter().map(|candidate| {
+                    // 新导入的笔记显示全文
+                    let existing = candidate.existing.as_ref().map(|n| n.content()).unwrap_or_default();
+                    diff::diff_lines(existing, candidate.result.note().content()).into_iter()
+                        .map(|op| match op {
+                            DiffOp::Equal(line) => DiffLine::Equal(line.trim_end_matches('\n').to_string()),
+                            DiffOp::Insert(line) => DiffLine::Insert(line.trim_end_matches('\n').to_string()),
+                            DiffOp::Delete(line) => DiffLine::Delete(line.trim_end_matches('\n').to_string()),
+                        })
+                        .collect()
+                }).collect();
+                Some(plan)
+            }
+            Err(e) => {
+                service.notify_error("import", None, format!("Failed to import {}: {}", input_path.display(), e));
+                None
+            }
+        };
+    }
+
+    // 向导关闭时是否已确认导入
+    pub fn committed(&self) -> bool {
+        self.committed
+    }
+
+    pub fn show<T>(&mut self, ctx: &egui::Context, service: &mut T) -> bool
+        where T: IoService + NotifyService + Translate {
+        let Some(plan) = self.plan.as_mut() else { return false; };
+
+        let mut is_open = true;
+        let mut commit = false;
+        let mut cancel = false;
+        egui::Window::new(service.t("import wizard"))
+            .open(&mut is_open)
+            .resizable(true)
+            .default_width(900.0)
+            .default_height(600.0)
+            .show(ctx, |ui| {
+                ui.label(plan.report.source.display().to_string());
+                ui.label(format!(
+                    "{}: {} | {}: {} | {}: {}",
+                    service.t("notes"), plan.candidates.len(),
+                    service.t("conflicts"), plan.conflicts(),
+                    service.t("unreadable files"), plan.report.failed(),
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button(service.t("import")).clicked() {
+                        commit = true;
+                    }
+                    if ui.button(service.t("cancel")).clicked() {
+                        cancel = true;
+                    }
+                });
+                ui.separator();
+
+                egui::SidePanel::left("import_wizard_sidebar")
+                    .resizable(true)
+                    .default_width(320.0)
+                    .show_inside(ui, |ui| {
+                        // 待导入笔记列表(有冲突的笔记可选择处理方式)
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            for (idx, candidate) in plan.candidates.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let title = candidate.result.note().title();
+                                    let label = match &candidate.conflict {
+                                        Some(ImportConflict::SameId(_)) => format!("⚠ {} ({})", title, service.t("same id")),
+                                        Some(ImportConflict::SameTitle(_)) => format!("⚠ {} ({})", title, service.t("same title")),
+                                        Some(ImportConflict::Trashed(_)) => format!("⚠ {} ({})", title, service.t("in trash")),
+                                        None => title.to_string(),
+                                    };
+                                    if ui.selectable_label(self.selected == Some(idx), label).clicked() {
+                                        self.selected = Some(idx);
+                                    }
+                                    if candidate.conflict.is_some() {
+                                        egui::ComboBox::from_id_salt(("import_strategy", idx))
+                                            .selected_text(service.t(strategy_name(&candidate.strategy)))
+                                            .show_ui(ui, |ui| {
+                                                for strategy in [MergeStrategy::Skip, MergeStrategy::Overwrite, MergeStrategy::Rename, MergeStrategy::Merge] {
+                                                    let text = service.t(strategy_name(&strategy));
+                                                    ui.selectable_value(&mut candidate.strategy, strategy, text);
+                                                }
+                                            });
+                                    }
+                                });
+                            }
+                        });
+                    });
+
+                egui::CentralPanel::default()
+                    .show_inside(ui, |ui| {
+                        let Some(idx) = self.selected.filter(|idx| *idx < plan.candidates.len()) else {
+                            ui.label(service.t("select a note to preview"));
+                            return;
+                        };
+                        for warning in plan.candidates[idx].result.warnings() {
+                            ui.colored_label(egui::Color32::YELLOW, warning);
+                        }
+                        // 与已有笔记的差异
+                        egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                            for line in self.diffs[idx].iter() {
+                                match line {
+                                    DiffLine::Equal(line) => ui.label(line),
+                                    DiffLine::Insert(line) => ui.colored_label(egui::Color32::LIGHT_GREEN, format!("+ {}", line)),
+                                    DiffLine::Delete(line) => ui.colored_label(egui::Color32::LIGHT_RED, format!("- {}", line)),
+                                };
+                            }
+                        });
+                    });
+            });
+
+        if commit && let Some(plan) = self.plan.take() {
+            self.diffs.clear();
+            match service.commit_import(plan) {
+                Ok(_) => self.committed = true,
+                Err(e) => service.notify_error("import", None, format!("Failed to import: {}", e)),
+            }
+            return false;
+        }
+        if !is_open || cancel {
+            self.plan = None;
+            self.diffs.clear();
+            return false;
+        }
+        true
+    }
+}
+
+fn strategy_name(strategy: &MergeStrategy) -> &'static str {
+    match strategy {
+        MergeStrategy::Skip => "skip",
+        MergeStrategy::Overwrite => "overwrite",
+        MergeStrategy::Rename => "rename",
+        MergeStrategy::Merge => "merge",
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    i18n::Translate,
+    utils::logger::{self, Level},
+};
+
+/// 日志查看窗口
+pub struct LogView {
+    is_open: bool,
+    max_level: Level,
+    filter: String,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            max_level: Level::Info,
+            filter: String::new(),
+        }
+    }
+}
+
+impl LogView {
+    pub fn show<T: Translate>(&mut self, ctx: &egui::Context, t: &T) -> bool {
+        if !self.is_open { return false; }
+
+        let mut is_open = self.is_open;
+        egui::Window::new(t.t("logs"))
+            .open(&mut is_open)
+            .resizable(true)
+            .default_width(800.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                // 过滤条件
+                ui.horizontal(|ui| {
+                    for level in [Level::Error, Level::Warn, Level::Info, Level::Debug] {
+                        ui.radio_value(&mut self.max_level, level, level.as_str());
+                    }
+                    ui.separator();
+                    egui::TextEdit::singleline(&mut self.filter)
+                        .hint_text(t.t("filter by operation, note id or message"))
+                        .show(ui);
+                    if ui.button(t.t("clear")).clicked() {
+                        logger::clear();
+                    }
+                });
+                if let Some(path) = logger::log_file() {
+                    ui.label(format!("{}: {}", t.t("log file"), path.display()));
+                }
+                ui.separator();
+
+                let filter = self.filter.to_lowercase();
+                let records: Vec<_> = logger::records().into_iter()
+                    .rev()
+                    .filter(|r| r.level <= self.max_level)
+                    .filter(|r| filter.is_empty()
+                        || r.operation.to_lowercase().contains(&filter)
+                        || r.message.to_lowercase().contains(&filter)
+                        || r.note_id.as_ref().is_some_and(|id| id.contains(&filter)))
+                    .collect();
+
+                // 最新的日志在最上方
+                egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                    egui::Grid::new("log_grid").striped(true).num_columns(4).show(ui, |ui| {
+                        for record in records.iter() {
+                            ui.label(record.time.format("%m-%d %H:%M:%S").to_string());
+                            let color = match record.level {
+                                Level::Error => egui::Color32::RED,
+                                Level::Warn => egui::Color32::YELLOW,
+                                _ => ui.visuals().text_color(),
+                            };
+                            ui.colored_label(color, record.level.as_str());
+                            ui.label(&record.operation);
+                            match &record.note_id {
+                                Some(note_id) => ui.label(format!("[{}] {}", note_id, record.message)),
+                                None => ui.label(&record.message),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.is_open = is_open;
+        self.is_open
+    }
+
+    pub fn open(&mut self) {
+        self.is_open = true;
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    services::{NoteService, TabService, IoService},
+    services::{NoteService, TabService, IoService, MergeService, NotifyService, BackupService},
     i18n::Translate,
     io::ExportFormat,
 };
@@ -13,7 +13,7 @@ pub struct MenuBar {
 
 impl MenuBar {
     pub fn show<T>(&mut self, ui: &mut egui::Ui, service: &mut T) -> Option<ShowView>
-        where T: NoteService + TabService + IoService + Translate {
+        where T: NoteService + TabService + IoService + MergeService + NotifyService + BackupService + Translate {
         let mut result = None;
 
         egui::menu::bar(ui, |ui| {
@@ -22,7 +22,7 @@ impl MenuBar {
                 // 创建新笔记
                 if ui.button(service.t("new note")).clicked() {
                     if let Err(e) = service.create_note() {
-                        eprintln!("创建笔记失败: {}", e);
+                        service.notify_error("create_note", None, format!("Failed to create note: {}", e));
                     }
                     ui.close_menu();
                 }
@@ -34,33 +34,43 @@ impl MenuBar {
                         ui.close_menu();
                     }
                 }
+                // 合并冲突窗口
+                let pending = service.pending_merges().len();
+                if pending > 0 {
+                    ui.separator();
+                    if ui.button(format!("{} ({})", service.t("resolve merge conflicts"), pending)).clicked() {
+                        result = Some(ShowView::ShowMergeEditor);
+                        ui.close_menu();
+                    }
+                }
                 ui.separator();
                 // 导出菜单项
                 ui.menu_button(service.t("export"), |ui| {
-                    if let Some(note_id) = service.current_note_id() {
-                        if ui.button(service.t("current note")).clicked() {
-                            if let Some(path) = match service.export_config().format {
-                                ExportFormat::Markdown(_) => file_dialog::save_markdown_file(
-                                    service.get_note(note_id).unwrap().title()
-                                ),
-                                ExportFormat::Json => file_dialog::save_json_file(
-                                    service.get_note(note_id).unwrap().title()
-                                ),
-                                _ => None,
-                            }
-                            {
-                                if let Err(e) = service.export_note(note_id, &path) {
-                                    eprintln!("Export Failed: {}", e);
-                                }
+                    // 当前笔记已被删除时不显示该菜单项
+                    if let Some(note_id) = service.current_note_id()
+                        && let Some(note) = service.get_note(note_id)
+                        && ui.button(service.t("current note")).clicked() {
+                        let title = note.title();
+                        if let Some(path) = match service.export_config().format {
+                            ExportFormat::Markdown(_) => file_dialog::save_markdown_file(title),
+                            ExportFormat::Json => file_dialog::save_json_file(title),
+                            ExportFormat::Pdf => file_dialog::save_pdf_file(title),
+                            ExportFormat::Html => file_dialog::save_html_file(title),
+                            ExportFormat::Docx => file_dialog::save_docx_file(title),
+                            ExportFormat::Odt => file_dialog::save_odt_file(title),
+                        }
+                        {
+                            if let Err(e) = service.export_note(note_id, &path) {
+                                service.notify_error("export_note", Some(note_id), format!("Failed to export to {}: {}", path.display(), e));
                             }
-                            ui.close_menu();
                         }
+                        ui.close_menu();
                     }
 
                     if ui.button(service.t("all notes")).clicked() {
                         if let Some(dir) = file_dialog::pick_directroy() {
                             if let Err(e) = service.export_all_notes(&dir) {
-                                eprintln!("Export All Notes Failed: {}", e);
+                                service.notify_error("export_all_notes", None, format!("Failed to export to {}: {}", dir.display(), e));
                             }
                         }
                         ui.close_menu();
@@ -70,20 +80,40 @@ impl MenuBar {
                 ui.menu_button(service.t("import"), |ui| {
                     if ui.button(service.t("from file")).clicked() {
                         if let Some(path) = file_dialog::pick_available_file() {
-                            if let Err(e) = service.import(&path) {
-                                eprintln!("Import From File Failed: {}", e);
-                            }
+                            result = Some(ShowView::ShowImportWizard(path));
                         }
                         ui.close_menu();
                     }
                     if ui.button(service.t("from directory")).clicked() {
                         if let Some(dir) = file_dialog::pick_directroy() {
-                            if let Err(e) = service.import(&dir) {
-                                eprintln!("Import From Directory Failed: {}", e);
-                            }
+                            result = Some(ShowView::ShowImportWizard(dir));
                         }
                         ui.close_menu();   
                     }
+                    // 最近一次导入的报告
+                    if service.last_import_report().is_some() {
+                        ui.separator();
+                        if ui.button(service.t("last import report")).clicked() {
+                            result = Some(ShowView::ShowImportReport);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                // 完整备份(含回收站、版本历史、附件和设置)
+                ui.menu_button(service.t("backup"), |ui| {
+                    if ui.button(service.t("create backup")).clicked() {
+                        if let Some(path) = file_dialog::save_backup_file()
+                            && let Err(e) = service.create_backup(&path) {
+                            service.notify_error("create_backup", None, format!("Failed to back up to {}: {}", path.display(), e));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(service.t("restore backup")).clicked() {
+                        if let Some(path) = file_dialog::pick_backup_file() {
+                            result = Some(ShowView::ShowRestoreBackup(path));
+                        }
+                        ui.close_menu();
+                    }
                 });
                 ui.separator();
                 // 退出按钮
@@ -92,6 +122,13 @@ impl MenuBar {
                     ui.close_menu();
                 }
             });
+            // 帮助
+            ui.menu_button(service.t("help"), |ui| {
+                if ui.button(service.t("view logs")).clicked() {
+                    result = Some(ShowView::ShowLogs);
+                    ui.close_menu();
+                }
+            });
             // 其他菜单
         });
 
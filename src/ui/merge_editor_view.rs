@@ -0,0 +1,146 @@
+use crate::{
+    data::HunkResolution,
+    services::{MergeService, NotifyService},
+    i18n::Translate,
+    utils::diff::MergeHunk,
+};
+
+#[derive(Default)]
+pub struct MergeEditorView {
+    is_open: bool,
+    selected_merge: Option<usize>,
+}
+
+impl MergeEditorView {
+    pub fn show<T: MergeService + NotifyService + Translate>(&mut self, ctx: &egui::Context, service: &mut T) -> bool {
+        if !self.is_open { return false; }
+        if service.pending_merges().is_empty() {
+            self.is_open = false;
+            return false;
+        }
+        // 待处理列表变化后修正选中项
+        if self.selected_merge.is_none_or(|idx| idx >= service.pending_merges().len()) {
+            self.selected_merge = Some(0);
+        }
+
+        let mut is_open = self.is_open;
+        egui::Window::new(service.t("merge editor"))
+            .open(&mut is_open)
+            .resizable(true)
+            .default_width(900.0)
+            .default_height(600.0)
+            .show(ctx, |ui| {
+                egui::SidePanel::left("merge_editor_sidebar")
+                    .resizable(true)
+                    .default_width(200.0)
+                    .show_inside(ui, |ui| {
+                        // 待合并笔记列表
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            for (idx, merge) in service.pending_merges().iter().enumerate() {
+                                let is_selected = self.selected_merge == Some(idx);
+                                let response = ui.selectable_label(
+                                    is_selected,
+                                    format!(
+                                        "{} ({}/{})",
+                                        merge.title(),
+                                        merge.unresolved_count(),
+                                        merge.conflict_count(),
+                                    ),
+                                );
+                                if response.clicked() {
+                                    self.selected_merge = Some(idx);
+                                }
+                            }
+                        });
+                    });
+
+                egui::CentralPanel::default()
+                    .show_inside(ui, |ui| {
+                        if let Some(merge_idx) = self.selected_merge {
+                            self.show_merge(ui, service, merge_idx);
+                        }
+                    });
+            });
+
+        self.is_open &= is_open;
+        self.is_open
+    }
+
+    fn show_merge<T: MergeService + NotifyService + Translate>(&mut self, ui: &mut egui::Ui, service: &mut T, merge_idx: usize) {
+        let merge = &service.pending_merges()[merge_idx];
+        let hunks = merge.hunks().to_vec();
+        let resolutions: Vec<_> = (0..hunks.len()).map(|idx| merge.resolution(idx)).collect();
+        let is_resolved = merge.is_resolved();
+        // 加锁笔记只显示两个副本的更新时间, 不显示密文
+        let whole_note = merge.is_whole_note().then(|| (
+            format!("{} {}", service.t("last updated at"), merge.ours().updated_at()),
+            format!("{} {}", service.t("last updated at"), merge.theirs().updated_at()),
+        ));
+
+        ui.heading(merge.title());
+        ui.label(merge.tags().iter().cloned().collect::<Vec<_>>().join(" "));
+        ui.separator();
+
+        // 操作按钮
+        let mut finished = false;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(is_resolved, egui::Button::new(service.t("apply merge"))).clicked() {
+                if let Err(e) = service.apply_merge(merge_idx) {
+                    service.notify_error("apply_merge", None, format!("Failed to apply merge: {}", e));
+                }
+                finished = true;
+            }
+            if ui.button(service.t("keep local copy")).clicked() {
+                if let Err(e) = service.discard_merge(merge_idx) {
+                    service.notify_error("discard_merge", None, format!("Failed to discard merge: {}", e));
+                }
+                finished = true;
+            }
+        });
+        ui.separator();
+        if finished {
+            self.selected_merge = None;
+            return;
+        }
+
+        // 合并片段
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (hunk_idx, hunk) in hunks.iter().enumerate() {
+                match hunk {
+                    MergeHunk::Resolved(lines) => {
+                        ui.label(lines.concat());
+                    }
+                    MergeHunk::Conflict { ours, theirs, .. } => {
+                        let mut chosen = resolutions[hunk_idx];
+                        let (ours, theirs) = whole_note.clone().unwrap_or_else(|| (ours.concat(), theirs.concat()));
+                        egui::Frame::group(ui.style()).show(ui, |ui| {
+                            if whole_note.is_some() {
+                                ui.label(format!("🔒 {}", service.t("locked notes can only be merged as a whole")));
+                            }
+                            ui.columns(2, |columns| {
+                                columns[0].colored_label(egui::Color32::LIGHT_GREEN, service.t("ours"));
+                                columns[0].label(ours);
+                                columns[1].colored_label(egui::Color32::LIGHT_BLUE, service.t("theirs"));
+                                columns[1].label(theirs);
+                            });
+                            ui.horizontal(|ui| {
+                                ui.radio_value(&mut chosen, Some(HunkResolution::Ours), service.t("accept ours"));
+                                ui.radio_value(&mut chosen, Some(HunkResolution::Theirs), service.t("accept theirs"));
+                                if whole_note.is_none() {
+                                    ui.radio_value(&mut chosen, Some(HunkResolution::Both), service.t("accept both"));
+                                }
+                            });
+                        });
+                        if let Some(resolution) = chosen.filter(|_| chosen != resolutions[hunk_idx]) {
+                            service.resolve_hunk(merge_idx, hunk_idx, resolution);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn open(&mut self) {
+        self.is_open = true;
+    }
+}
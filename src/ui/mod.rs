@@ -6,4 +6,11 @@ pub mod navigation_bar;
 pub mod sidebar;
 pub mod status_bar;
 pub mod version_history_view;
+pub mod merge_editor_view;
+pub mod unlock_view;
+pub mod log_view;
+pub mod notification_view;
+pub mod import_report_view;
+pub mod import_wizard_view;
+pub mod restore_backup_view;
 pub mod dialogs;
@@ -1,4 +1,5 @@
-use crate::{i18n::{Language, Translate}, io::{ExportFormat, MergeStrategy}, services::{SettingsService, Theme}};
+use crate::{i18n::{Language, Translate}, io::{ExportFormat, ImportSource, MergeStrategy, PageSize}, services::{AccountService, MirrorService, NotifyService, SettingsService, SyncService, Theme, VaultService, VersionService}};
+use super::dialogs::file_dialog;
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum NavigationTab {
@@ -53,7 +54,7 @@ impl NavigationBar {
         self.selected
     }
 
-    pub fn show<T: SettingsService + Translate>(&mut self, ui: &mut egui::Ui, t: &mut T) {
+    pub fn show<T: SettingsService + SyncService + MirrorService + AccountService + VaultService + VersionService + NotifyService + Translate>(&mut self, ui: &mut egui::Ui, t: &mut T) {
         ui.vertical_centered(|ui| {
             // 主功能Tabs
             for tab in &[NavigationTab::Notes, NavigationTab::Tags, NavigationTab::Search, NavigationTab::Trash] {
@@ -110,7 +111,7 @@ impl NavigationBar {
                 // 显示主菜单窗口
                 let pos = match show_menu {
                     ShowMenu::Account => account_response.rect.right_top() + egui::vec2(10.0, -40.0),
-                    ShowMenu::Settings => settings_response.rect.right_top() + egui::vec2(10.0, -64.0),
+                    ShowMenu::Settings => settings_response.rect.right_top() + egui::vec2(10.0, -88.0),
                 };
                 let menu_ctx = show_menu.ctx(pos);
                 should_close &= self.show_menu(ui.ctx(), &menu_ctx);
@@ -199,7 +200,7 @@ impl ShowMenu {
                 width: 80.0,
                 pos,
                 id: "account_menu".to_string(),
-                submenus: vec![],
+                submenus: vec![ShowSubmenu::Account, ShowSubmenu::Sync],
             },
             ShowMenu::Settings => MenuContext {
                 width: 80.0,
@@ -211,6 +212,8 @@ impl ShowMenu {
                     ShowSubmenu::None,
                     ShowSubmenu::Export,
                     ShowSubmenu::Import,
+                    ShowSubmenu::Security,
+                    ShowSubmenu::History,
                 ],
             },
         }
@@ -231,6 +234,10 @@ enum ShowSubmenu {
     Language,
     Export,
     Import,
+    Security,
+    History,
+    Account,
+    Sync,
     None,       // 分隔符
 }
 
@@ -241,6 +248,10 @@ impl ShowSubmenu {
             ShowSubmenu::Language => Some("Language"),
             ShowSubmenu::Export => Some("Export"),
             ShowSubmenu::Import => Some("Import"),
+            ShowSubmenu::Security => Some("Security"),
+            ShowSubmenu::History => Some("History"),
+            ShowSubmenu::Account => Some("Account"),
+            ShowSubmenu::Sync => Some("Sync"),
             _ => None,
         }
     }
@@ -267,11 +278,31 @@ impl ShowSubmenu {
                 pos: pos + egui::vec2(0.0, 6.0),
                 id: "import_submenu".to_string(),
             }),
+            ShowSubmenu::Security => Some(SubmenuContext {
+                width: 240.0,
+                pos: pos + egui::vec2(0.0, 24.0),
+                id: "security_submenu".to_string(),
+            }),
+            ShowSubmenu::History => Some(SubmenuContext {
+                width: 240.0,
+                pos: pos + egui::vec2(0.0, 30.0),
+                id: "history_submenu".to_string(),
+            }),
+            ShowSubmenu::Account => Some(SubmenuContext {
+                width: 240.0,
+                pos,
+                id: "account_submenu".to_string(),
+            }),
+            ShowSubmenu::Sync => Some(SubmenuContext {
+                width: 240.0,
+                pos: pos + egui::vec2(0.0, 25.0),
+                id: "sync_submenu".to_string(),
+            }),
             _ => None,
         }
     }
 
-    fn add_contents<T: SettingsService>(&self, ui: &mut egui::Ui, t: &mut T) {
+    fn add_contents<T: SettingsService + SyncService + MirrorService + AccountService + VaultService + VersionService + NotifyService + Translate>(&self, ui: &mut egui::Ui, t: &mut T) {
         match self {
             ShowSubmenu::Theme => {
                 ui.radio_value(t.theme_mut(), Theme::Dark, "Dark");
@@ -287,13 +318,214 @@ impl ShowSubmenu {
                 ui.radio_value(&mut t.export_config_mut().format, ExportFormat::Markdown(false), "Markdown(Exclude Metadata)");
                 ui.radio_value(&mut t.export_config_mut().format, ExportFormat::Json, "Json");
                 ui.radio_value(&mut t.export_config_mut().format, ExportFormat::Html, "Html");
+                ui.radio_value(&mut t.export_config_mut().format, ExportFormat::Pdf, "Pdf");
+                ui.radio_value(&mut t.export_config_mut().format, ExportFormat::Docx, "Docx");
+                ui.radio_value(&mut t.export_config_mut().format, ExportFormat::Odt, "Odt");
+                let config = t.export_config_mut();
+                // 导出多个文件时使用文件名模板
+                if matches!(config.format, ExportFormat::Markdown(_) | ExportFormat::Pdf | ExportFormat::Docx | ExportFormat::Odt) {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Filename Template");
+                        ui.text_edit_singleline(&mut config.filename_template)
+                            .on_hover_text("{title} {id} {tag} {created:%Y-%m-%d} {updated:%Y-%m-%d}\nUse / to write into subfolders, e.g. {tag}/{title}");
+                    });
+                }
+                if config.format == ExportFormat::Pdf {
+                    ui.separator();
+                    let pdf = &mut config.pdf;
+                    ui.horizontal(|ui| {
+                        ui.label("Page Size");
+                        ui.radio_value(&mut pdf.page_size, PageSize::A4, "A4");
+                        ui.radio_value(&mut pdf.page_size, PageSize::A5, "A5");
+                        ui.radio_value(&mut pdf.page_size, PageSize::Letter, "Letter");
+                    });
+                    egui::Grid::new("pdf_layout_grid").num_columns(2).show(ui, |ui| {
+                        ui.label("Margin (mm)");
+                        ui.add(egui::DragValue::new(&mut pdf.margin_mm).range(5.0..=60.0).speed(0.5));
+                        ui.end_row();
+                        ui.label("Font Size");
+                        ui.add(egui::DragValue::new(&mut pdf.font_size).range(6.0..=24.0).speed(0.1));
+                        ui.end_row();
+                    });
+                    ui.checkbox(&mut pdf.header, "Header (Title)");
+                    ui.checkbox(&mut pdf.footer, "Footer (Page Numbers)");
+                }
             }
             ShowSubmenu::Import => {
                 ui.radio_value(&mut t.import_config_mut().merge_strategy, MergeStrategy::Skip, "Skip");
                 ui.radio_value(&mut t.import_config_mut().merge_strategy, MergeStrategy::Rename, "Rename");
                 ui.radio_value(&mut t.import_config_mut().merge_strategy, MergeStrategy::Overwrite, "Overwrite");
+                ui.radio_value(&mut t.import_config_mut().merge_strategy, MergeStrategy::Merge, "Merge");
                 ui.separator();
                 ui.checkbox(&mut t.import_config_mut().preserve_timestamps, "Preserve Timestamps");
+                ui.horizontal(|ui| {
+                    ui.label("Source");
+                    ui.radio_value(&mut t.import_config_mut().source, ImportSource::Auto, "Auto");
+                    ui.radio_value(&mut t.import_config_mut().source, ImportSource::Markdown, "Markdown");
+                    ui.radio_value(&mut t.import_config_mut().source, ImportSource::Obsidian, "Obsidian");
+                    ui.radio_value(&mut t.import_config_mut().source, ImportSource::Joplin, "Joplin");
+                });
+                ui.checkbox(&mut t.import_config_mut().recursive, "Include Subfolders");
+                ui.checkbox(&mut t.import_config_mut().folder_tags, "Folders As Tags");
+                egui::Grid::new("import_patterns_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Include");
+                    ui.add(egui::TextEdit::singleline(&mut t.import_config_mut().include).hint_text("*.md notes/**"));
+                    ui.end_row();
+                    ui.label("Exclude");
+                    ui.add(egui::TextEdit::singleline(&mut t.import_config_mut().exclude).hint_text(".obsidian drafts/**"));
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Backup Passphrase");
+                    ui.add(egui::TextEdit::singleline(&mut t.import_config_mut().backup_passphrase).password(true));
+                });
+            }
+            ShowSubmenu::Security => {
+                // 口令输入暂存在 egui 临时内存中
+                let id = egui::Id::new("vault_passphrase_form");
+                let mut form: (String, String, String) = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+                let enabled = t.is_vault_enabled();
+                let mut done = false;
+                egui::Grid::new("vault_passphrase_grid").num_columns(2).show(ui, |ui| {
+                    if enabled {
+                        ui.label(t.t("current passphrase"));
+                        ui.add(egui::TextEdit::singleline(&mut form.0).password(true));
+                        ui.end_row();
+                    }
+                    ui.label(t.t("new passphrase"));
+                    ui.add(egui::TextEdit::singleline(&mut form.1).password(true));
+                    ui.end_row();
+                    ui.label(t.t("confirm passphrase"));
+                    ui.add(egui::TextEdit::singleline(&mut form.2).password(true));
+                    ui.end_row();
+                });
+
+                let confirmed = !form.1.is_empty() && form.1 == form.2;
+                if !form.2.is_empty() && form.1 != form.2 {
+                    ui.colored_label(egui::Color32::RED, t.t("passphrases do not match"));
+                }
+                if enabled {
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(confirmed, egui::Button::new(t.t("change passphrase"))).clicked() {
+                            match t.change_passphrase(&form.0, &form.1) {
+                                Ok(()) => done = true,
+                                Err(e) => t.notify_error("change_passphrase", None, format!("Failed to change passphrase: {}", e)),
+                            }
+                        }
+                        if ui.add_enabled(!form.0.is_empty(), egui::Button::new(t.t("disable encryption"))).clicked() {
+                            match t.disable_vault(&form.0) {
+                                Ok(()) => done = true,
+                                Err(e) => t.notify_error("disable_vault", None, format!("Failed to disable encryption: {}", e)),
+                            }
+                        }
+                    });
+                } else if ui.add_enabled(confirmed, egui::Button::new(t.t("enable encryption"))).clicked() {
+                    match t.enable_vault(&form.1) {
+                        Ok(()) => done = true,
+                        Err(e) => t.notify_error("enable_vault", None, format!("Failed to enable encryption: {}", e)),
+                    }
+                }
+
+                if done {
+                    form = Default::default();
+                }
+                ui.data_mut(|d| d.insert_temp(id, form));
+            }
+            ShowSubmenu::History => {
+                // 版本历史保存在数据库或数据库所在目录的git仓库中
+                let mut enabled = t.is_git_history_enabled();
+                if ui.checkbox(&mut enabled, t.t("store versions in git"))
+                    .on_hover_text("Existing versions are migrated into the repository")
+                    .changed() {
+                    match t.set_git_history(enabled) {
+                        Ok(0) => (),
+                        Ok(migrated) if enabled => t.notify_success("set_git_history", format!("Migrated {} versions to the git repository", migrated)),
+                        Ok(migrated) => t.notify_success("set_git_history", format!("Migrated {} versions back to the database", migrated)),
+                        Err(e) => t.notify_error("set_git_history", None, format!("Failed to change version history storage: {}", e)),
+                    }
+                }
+            }
+            ShowSubmenu::Account => {
+                if let Some(account) = t.account() {
+                    ui.label(format!("{}@{}", account.username, account.server));
+                    if ui.button(t.t("logout")).clicked()
+                        && let Err(e) = t.logout() {
+                        t.notify_error("logout", None, format!("Failed to log out: {}", e));
+                    }
+                    return;
+                }
+
+                // 登录表单(输入内容暂存在 egui 临时内存中)
+                let id = egui::Id::new("account_login_form");
+                let mut form: (String, String, String) = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+                egui::Grid::new("account_login_grid").num_columns(2).show(ui, |ui| {
+                    ui.label(t.t("server"));
+                    ui.text_edit_singleline(&mut form.0);
+                    ui.end_row();
+                    ui.label(t.t("username"));
+                    ui.text_edit_singleline(&mut form.1);
+                    ui.end_row();
+                    ui.label(t.t("password"));
+                    ui.add(egui::TextEdit::singleline(&mut form.2).password(true));
+                    ui.end_row();
+                });
+                let ready = !form.0.trim().is_empty() && !form.1.trim().is_empty();
+                if ui.add_enabled(ready, egui::Button::new(t.t("login"))).clicked() {
+                    match t.login(form.0.trim(), form.1.trim(), &form.2) {
+                        Ok(()) => form.2.clear(),
+                        Err(e) => t.notify_error("login", None, format!("Failed to log in to {}: {}", form.0.trim(), e)),
+                    }
+                }
+                ui.data_mut(|d| d.insert_temp(id, form));
+            }
+            ShowSubmenu::Sync => {
+                // 同步目录
+                match t.sync_folder() {
+                    Some(folder) => ui.label(folder.display().to_string()),
+                    None => ui.label(t.t("no sync folder")),
+                };
+                ui.horizontal(|ui| {
+                    if ui.button(t.t("choose folder")).clicked()
+                        && let Some(dir) = file_dialog::pick_directroy()
+                        && let Err(e) = t.set_sync_folder(Some(&dir)) {
+                        t.notify_error("set_sync_folder", None, format!("Failed to set sync folder: {}", e));
+                    }
+                    if t.sync_folder().is_some() && ui.button(t.t("stop syncing")).clicked()
+                        && let Err(e) = t.set_sync_folder(None) {
+                        t.notify_error("set_sync_folder", None, format!("Failed to set sync folder: {}", e));
+                    }
+                });
+                ui.separator();
+                if ui.add_enabled(t.can_sync(), egui::Button::new(t.t("sync now"))).clicked()
+                    && let Err(e) = t.sync_now() {
+                    t.notify_error("sync", None, format!("Sync failed: {}", e));
+                }
+                // 最近一次同步结果
+                if let Some(report) = t.last_sync_report() {
+                    ui.label(format!(
+                        "↑{} ↓{} 🗑{} ⇄{} ⚠{}",
+                        report.pushed, report.pulled, report.deleted, report.merged, report.conflicts.len(),
+                    ));
+                }
+
+                // 镜像目录(与笔记双向同步的Markdown文件)
+                ui.separator();
+                match t.mirror_folder() {
+                    Some(folder) => ui.label(folder.display().to_string()),
+                    None => ui.label(t.t("no mirror folder")),
+                };
+                ui.horizontal(|ui| {
+                    if ui.button(t.t("choose mirror folder")).clicked()
+                        && let Some(dir) = file_dialog::pick_directroy()
+                        && let Err(e) = t.set_mirror_folder(Some(&dir)) {
+                        t.notify_error("set_mirror_folder", None, format!("Failed to set mirror folder: {}", e));
+                    }
+                    if t.mirror_folder().is_some() && ui.button(t.t("stop mirroring")).clicked()
+                        && let Err(e) = t.set_mirror_folder(None) {
+                        t.notify_error("set_mirror_folder", None, format!("Failed to set mirror folder: {}", e));
+                    }
+                });
             }
             _ => ()
         }
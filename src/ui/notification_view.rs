@@ -0,0 +1,103 @@
+use crate::{
+    i18n::Translate,
+    services::{Notice, NoticeKind, NotifyService},
+};
+
+// 同时显示的提示框数量上限
+const MAX_TOASTS: usize = 5;
+
+fn notice_icon(kind: NoticeKind) -> (&'static str, egui::Color32) {
+    match kind {
+        NoticeKind::Success => ("✔", egui::Color32::GREEN),
+        NoticeKind::Info => ("ℹ", egui::Color32::LIGHT_BLUE),
+        NoticeKind::Warning => ("⚠", egui::Color32::YELLOW),
+        NoticeKind::Error => ("✖", egui::Color32::RED),
+    }
+}
+
+/// 通知中心：右下角的临时提示框和通知历史窗口
+#[derive(Default)]
+pub struct NotificationView {
+    is_open: bool,
+    errors_only: bool,
+}
+
+impl NotificationView {
+    // 显示未过期的提示框
+    pub fn show_toasts<T: NotifyService>(&self, ctx: &egui::Context, service: &T) {
+        let toasts: Vec<(Notice, std::time::Duration)> = service.notices().into_iter()
+            .rev()
+            .filter_map(|n| n.toast_remaining().map(|remaining| (n, remaining)))
+            .take(MAX_TOASTS)
+            .collect();
+        // 提示框到期后需要重绘以将其移除
+        if let Some(next) = toasts.iter().map(|(_, remaining)| *remaining).min() {
+            ctx.request_repaint_after(next);
+        }
+
+        let mut dismissed = Vec::new();
+        egui::Area::new(egui::Id::new("notification_toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -36.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                // 最新的提示框在最下方
+                for (notice, _) in toasts.iter().rev() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_max_width(360.0);
+                        ui.horizontal(|ui| {
+                            let (icon, color) = notice_icon(notice.kind);
+                            ui.colored_label(color, icon);
+                            ui.label(&notice.message);
+                            if ui.add(egui::Button::new("×").frame(false).small()).clicked() {
+                                dismissed.push(notice.id);
+                            }
+                        });
+                    });
+                }
+            });
+        for id in dismissed {
+            service.dismiss_notice(id);
+        }
+    }
+
+    // 显示通知历史窗口
+    pub fn show<T: NotifyService + Translate>(&mut self, ctx: &egui::Context, service: &T) -> bool {
+        if !self.is_open { return false; }
+
+        let mut is_open = self.is_open;
+        egui::Window::new(service.t("notifications"))
+            .open(&mut is_open)
+            .resizable(true)
+            .default_width(600.0)
+            .default_height(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.errors_only, service.t("errors only"));
+                    if ui.button(service.t("clear")).clicked() {
+                        service.clear_notices();
+                    }
+                });
+                ui.separator();
+
+                // 最新的通知在最上方
+                egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                    egui::Grid::new("notification_grid").striped(true).num_columns(3).show(ui, |ui| {
+                        for notice in service.notices().iter().rev()
+                            .filter(|n| !self.errors_only || n.kind == NoticeKind::Error) {
+                            ui.label(notice.time.format("%m-%d %H:%M:%S").to_string());
+                            let (icon, color) = notice_icon(notice.kind);
+                            ui.colored_label(color, icon);
+                            ui.label(&notice.message);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.is_open = is_open;
+        self.is_open
+    }
+
+    pub fn open(&mut self) {
+        self.is_open = true;
+    }
+}
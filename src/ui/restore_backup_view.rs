@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+use crate::{
+    i18n::Translate,
+    io::{BackupManifest, RestoreMode},
+    services::{BackupService, NotifyService, SettingsService},
+};
+
+/// 恢复备份窗口：显示备份清单并选择替换或合并
+pub struct RestoreBackupView {
+    backup: Option<(PathBuf, BackupManifest)>,
+    mode: RestoreMode,
+}
+
+impl Default for RestoreBackupView {
+    fn default() -> Self {
+        Self {
+            backup: None,
+            mode: RestoreMode::Merge,
+        }
+    }
+}
+
+impl RestoreBackupView {
+    // 读取备份清单, 失败时不打开窗口
+    pub fn open<T: BackupService + NotifyService>(&mut self, input_path: &Path, service: &T) {
+        self.mode = RestoreMode::Merge;
+        self.backup = match service.read_backup_manifest(input_path) {
+            Ok(manifest) => Some((input_path.to_path_buf(), manifest)),
+            Err(e) => {
+                service.notify_error("restore_backup", None, format!("Failed to read backup {}: {}", input_path.display(), e));
+                None
+            }
+        };
+    }
+
+    pub fn show<T>(&mut self, ctx: &egui::Context, service: &mut T) -> bool
+        where T: BackupService + SettingsService + NotifyService + Translate {
+        let Some((path, manifest)) = self.backup.as_ref() else { return false; };
+
+        let mut is_open = true;
+        let mut restore = false;
+        let mut cancel = false;
+        egui::Window::new(service.t("restore backup"))
+            .open(&mut is_open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(path.display().to_string());
+                egui::Grid::new("restore_backup_grid").num_columns(2).show(ui, |ui| {
+                    ui.label(service.t("created at"));
+                    ui.label(manifest.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string());
+                    ui.end_row();
+                    ui.label(service.t("app version"));
+                    ui.label(format!("{} (v{})", manifest.app_version, manifest.format_version));
+                    ui.end_row();
+                    ui.label(service.t("notes"));
+                    ui.label(format!("{} (+{} {})", manifest.notes, manifest.trashed, service.t("in trash")));
+                    ui.end_row();
+                    ui.label(service.t("versions"));
+                    ui.label(manifest.versions.to_string());
+                    ui.end_row();
+                    ui.label(service.t("attachments"));
+                    ui.label(manifest.attachments.to_string());
+                    ui.end_row();
+                    // 加密的备份需要创建备份时的保险库口令
+                    if manifest.encrypted {
+                        ui.label(service.t("backup passphrase"));
+                        ui.add(egui::TextEdit::singleline(&mut service.import_config_mut().backup_passphrase).password(true));
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+
+                ui.radio_value(&mut self.mode, RestoreMode::Merge, service.t("merge into current vault"));
+                ui.radio_value(&mut self.mode, RestoreMode::Replace, service.t("replace current vault"));
+                if self.mode == RestoreMode::Replace {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        service.t("notes not in the backup will be deleted and settings will be overwritten"),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(service.t("restore")).clicked() {
+                        restore = true;
+                    }
+                    if ui.button(service.t("cancel")).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if restore && let Some((path, _)) = self.backup.take() {
+            if let Err(e) = service.restore_backup(&path, self.mode) {
+                service.notify_error("restore_backup", None, format!("Failed to restore {}: {}", path.display(), e));
+            }
+            return false;
+        }
+        if !is_open || cancel {
+            self.backup = None;
+            return false;
+        }
+        true
+    }
+}
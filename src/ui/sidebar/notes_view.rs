@@ -1,5 +1,5 @@
 use crate::{
-    services::{NoteService, TabService}, 
+    services::{NoteService, NotifyService, TabService}, 
     i18n::Translate,
 };
 
@@ -10,20 +10,30 @@ pub(super) struct NotesView {
 }
 
 impl NotesView {
-    pub fn show<T: NoteService + TabService + Translate>(&mut self, ui: &mut egui::Ui, state: &mut T) {
+    pub fn show<T: NoteService + TabService + NotifyService + Translate>(&mut self, ui: &mut egui::Ui, state: &mut T) {
         ui.vertical(|ui| {
             ui.label(state.t("note list"));
             ui.text_edit_singleline(&mut self.filter);
 
             egui::ScrollArea::vertical().show(ui, |ui| {
                 let mut to_delete = None;
-                for note in state.filter_notes(&self.filter).unwrap() {
+                let notes = match state.filter_notes(&self.filter) {
+                    Ok(notes) => notes,
+                    Err(e) => {
+                        ui.colored_label(egui::Color32::RED, e.to_string());
+                        Vec::new()
+                    }
+                };
+                for note in notes {
                     let is_selected = state.current_note_id_equals(note.id());
                     ui.horizontal(|ui| {
-                        let response = ui.selectable_label(
-                            is_selected, 
-                            note.title()
-                        );
+                        // 加锁笔记只显示标题和锁标记
+                        let title = if note.is_locked() {
+                            format!("🔒 {}", note.title())
+                        } else {
+                            note.title().to_string()
+                        };
+                        let response = ui.selectable_label(is_selected, title);
 
                         if response.clicked() {
                             state.load_note(note.id());
@@ -41,9 +51,7 @@ impl NotesView {
                 // 处理删除操作(在 notebook 锁释放后）
                 if let Some(id) = to_delete {
                     if let Err(e) = state.delete_note(&id) {
-                        eprintln!("删除笔记失败: {}", e);
-                        // 可以在这里添加错误提示到UI
-                        ui.label(egui::RichText::new("Failed to delete!").color(egui::Color32::RED));
+                        state.notify_error("delete_note", Some(&id), format!("Failed to delete note: {}", e));
                     }
                 }
             });
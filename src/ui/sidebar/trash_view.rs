@@ -1,4 +1,4 @@
-use crate::{i18n::Translate, data::DeleteNote, services::TrashService};
+use crate::{i18n::Translate, data::DeleteNote, services::{NotifyService, TrashService}};
 
 /// 标签列表视图
 #[derive(Default)]
@@ -8,17 +8,28 @@ pub(super) struct TrashView {
 }
 
 impl TrashView {
-    pub fn show<T: TrashService + Translate>(&mut self, ui: &mut egui::Ui, state: &mut T) {
-        self.deleted_notes = state.get_deleted_notes().unwrap();
+    pub fn show<T: TrashService + NotifyService + Translate>(&mut self, ui: &mut egui::Ui, state: &mut T) {
+        // 读取失败时保留上次的列表
+        let error = match state.get_deleted_notes() {
+            Ok(notes) => {
+                self.deleted_notes = notes;
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        };
 
         ui.vertical(|ui| {
             ui.label(state.t("recycle bin"));
             ui.separator();
+            if let Some(error) = error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
 
             // 操作按钮
             ui.horizontal(|ui| {
-                if ui.button(state.t("empty trash")).clicked() {
-                    let _ = state.empty_trash();
+                if ui.button(state.t("empty trash")).clicked()
+                    && let Err(e) = state.empty_trash() {
+                    state.notify_error("empty_trash", None, format!("Failed to empty trash: {}", e));
                 }
             });
 
@@ -46,11 +57,17 @@ impl TrashView {
                 ui.separator();
                 ui.horizontal(|ui| {
                     if ui.button(state.t("restore")).clicked() {
-                        let _ = state.restore_from_trash(self.deleted_notes[idx].id());
+                        let id = self.deleted_notes[idx].id();
+                        if let Err(e) = state.restore_from_trash(id) {
+                            state.notify_error("restore_from_trash", Some(id), format!("Failed to restore note: {}", e));
+                        }
                     }
 
                     if ui.button(state.t("delete permanently")).clicked() {
-                        let _ = state.delete_permanently(self.deleted_notes[idx].id());
+                        let id = self.deleted_notes[idx].id();
+                        if let Err(e) = state.delete_permanently(id) {
+                            state.notify_error("delete_permanently", Some(id), format!("Failed to delete note: {}", e));
+                        }
                     }
                 });
             }
@@ -1,4 +1,5 @@
-use crate::{services::TabService, i18n::Translate};
+use crate::{services::{AccountService, NoticeKind, NotifyService, TabService}, i18n::Translate};
+use super::app_layout::ShowView;
 
 #[derive(Default)]
 pub struct StatusBar {
@@ -6,11 +7,14 @@ pub struct StatusBar {
 }
 
 impl StatusBar {
-    pub fn show<T: TabService + Translate>(&self, ui: &mut egui::Ui, service: &T) {
+    pub fn show<T: TabService + AccountService + NotifyService + Translate>(&self, ui: &mut egui::Ui, service: &T) -> Option<ShowView> {
+        let mut result = None;
         ui.horizontal(|ui| {
             // 左侧：账户信息
-            // ...
-            ui.label(service.t("guest"));
+            match service.account() {
+                Some(account) => ui.label(format!("👤 {}", account.username)),
+                None => ui.label(service.t("guest")),
+            };
 
             ui.separator();
 
@@ -31,7 +35,22 @@ impl StatusBar {
             // 右侧：扩展区域
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 ui.label("Rust Notes v0.1");
+                ui.separator();
+                // 通知中心(有错误时标红)
+                let notices = service.notices();
+                let errors = notices.iter().filter(|n| n.kind == NoticeKind::Error).count();
+                let bell = if errors > 0 {
+                    egui::RichText::new(format!("🔔 {}", errors)).color(egui::Color32::RED)
+                } else {
+                    egui::RichText::new(format!("🔔 {}", notices.len()))
+                };
+                if ui.add(egui::Button::new(bell).frame(false))
+                    .on_hover_text(service.t("notifications"))
+                    .clicked() {
+                    result = Some(ShowView::ShowNotifications);
+                }
             });
         });
+        result
     }
 }
\ No newline at end of file
@@ -0,0 +1,82 @@
+use crate::data::Database;
+use crate::utils::i18n::{self, Language, Translate};
+
+/// 启动时的保险库解锁界面
+pub struct UnlockView {
+    db: Option<Database>,
+    passphrase: String,
+    error: Option<String>,
+    // 语言设置保存在解锁后的状态中, 解锁前使用默认语言
+    language: Language,
+}
+
+impl UnlockView {
+    pub fn new(db: Database) -> Self {
+        Self {
+            db: Some(db),
+            passphrase: String::new(),
+            error: None,
+            language: Language::English,
+        }
+    }
+
+    /// 解锁后加载笔记失败: 显示错误并换上重新打开的数据库, 允许重试
+    pub fn reset(&mut self, db: Option<Database>, error: String) {
+        self.db = db;
+        self.error = Some(format!("{}: {}", self.t("failed to load notes"), error));
+    }
+
+    /// 显示解锁界面，解锁成功时返回数据库
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Database> {
+        let mut submitted = false;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                ui.heading(format!("🔒 {}", self.t("notes are encrypted")));
+                ui.add_space(12.0);
+
+                let hint = self.t("passphrase");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.passphrase)
+                        .password(true)
+                        .hint_text(hint)
+                        .desired_width(240.0)
+                );
+                response.request_focus();
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    submitted = true;
+                }
+                ui.add_space(8.0);
+                if ui.button(self.t("unlock")).clicked() {
+                    submitted = true;
+                }
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+            });
+        });
+
+        if !submitted {
+            return None;
+        }
+        let db = self.db.as_mut()?;
+        match db.unlock(&self.passphrase) {
+            Ok(()) => {
+                self.passphrase.clear();
+                self.db.take()
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.passphrase.clear();
+                None
+            }
+        }
+    }
+}
+
+impl Translate for UnlockView {
+    fn t(&self, key: &str) -> String {
+        i18n::t(key, self.language)
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     data::NoteVersion,
-    services::VersionService,
+    services::{NotifyService, VersionService},
     i18n::Translate,
 };
 
@@ -15,9 +15,16 @@ pub struct VersionHistoryView {
 }
 
 impl VersionHistoryView {
-    pub fn show<T: VersionService + Translate>(&mut self, ctx: &egui::Context, service: &mut T) -> bool {
-        if !self.is_open || self.selected_note_id.is_none() { return false; }
-        self.versions = service.list_versions(self.selected_note_id.as_ref().unwrap()).unwrap();
+    pub fn show<T: VersionService + NotifyService + Translate>(&mut self, ctx: &egui::Context, service: &mut T) -> bool {
+        let Some(note_id) = self.selected_note_id.as_ref().filter(|_| self.is_open) else { return false; };
+        self.versions = match service.list_versions(note_id) {
+            Ok(versions) => versions,
+            Err(e) => {
+                service.notify_error("list_versions", Some(note_id), format!("Failed to load versions: {}", e));
+                self.is_open = false;
+                return false;
+            }
+        };
 
         egui::Window::new("Version Histroy")
             .open(&mut self.is_open)
@@ -58,13 +65,13 @@ impl VersionHistoryView {
                                 if let Some(idx) = self.selected_version {
                                     if ui.button(service.t("restore this version")).clicked() {
                                         if let Err(e) = service.restore_version(&self.versions[idx]) {
-                                            eprintln!("重载版本失败: {}", e);
+                                            service.notify_error("restore_version", Some(self.versions[idx].note_id()), format!("Failed to restore version: {}", e));
                                         }
                                     }
 
                                     if ui.button(service.t("delete this version")).clicked() {
                                         if let Err(e) = service.delete_version(self.versions[idx].id()) {
-                                            eprintln!("删除版本失败: {}", e);
+                                            service.notify_error("delete_version", Some(self.versions[idx].note_id()), format!("Failed to delete version: {}", e));
                                         }
                                     }
                                 }
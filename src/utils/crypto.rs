@@ -0,0 +1,210 @@
+use anyhow::Result;
+use argon2::Argon2;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, rand_core::RngCore},
+};
+use serde::{Serialize, Deserialize};
+
+// 密文前缀，用于区分已加密与未加密的字段
+const PREFIX: &str = "enc:v1:";
+// 单篇笔记加锁后的内容前缀(与保险库密文区分，两者可叠加)
+const LOCK_PREFIX: &str = "lock:v1:";
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+/// 判断字段是否为密文
+pub fn is_encrypted(text: &str) -> bool {
+    text.starts_with(PREFIX)
+}
+
+/// 判断笔记内容是否已用笔记密码加密
+pub fn is_locked_text(text: &str) -> bool {
+    text.starts_with(LOCK_PREFIX)
+}
+
+/// 数据密钥：对笔记字段进行认证加密(ChaCha20-Poly1305)
+#[derive(Clone)]
+pub struct Cipher {
+    key: Key,
+}
+
+impl std::fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Cipher(..)")
+    }
+}
+
+impl Cipher {
+    fn generate() -> Self {
+        Self { key: ChaCha20Poly1305::generate_key(&mut OsRng) }
+    }
+
+    fn seal_bytes(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        // 加密内存数据不会失败
+        let ciphertext = ChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, plaintext)
+            .expect("encryption failed");
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        sealed
+    }
+
+    fn open_bytes(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return Err(anyhow::anyhow!("ciphertext is truncated"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        ChaCha20Poly1305::new(&self.key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("decryption failed: wrong key or corrupted data"))
+    }
+
+    fn encrypt_with(&self, prefix: &str, plaintext: &str) -> String {
+        format!("{}{}", prefix, BASE64.encode(self.seal_bytes(plaintext.as_bytes())))
+    }
+
+    fn decrypt_with(&self, prefix: &str, text: &str) -> Result<String> {
+        let encoded = text.strip_prefix(prefix)
+            .ok_or_else(|| anyhow::anyhow!("text is not encrypted"))?;
+        let plaintext = self.open_bytes(&BASE64.decode(encoded)?)?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+
+    /// 加密文本，返回带前缀的 base64 密文
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        self.encrypt_with(PREFIX, plaintext)
+    }
+
+    /// 解密带前缀的密文
+    pub fn decrypt(&self, text: &str) -> Result<String> {
+        self.decrypt_with(PREFIX, text)
+    }
+
+    /// 用笔记密钥加密笔记内容
+    pub fn lock_text(&self, plaintext: &str) -> String {
+        self.encrypt_with(LOCK_PREFIX, plaintext)
+    }
+
+    /// 用笔记密钥解密笔记内容
+    pub fn unlock_text(&self, text: &str) -> Result<String> {
+        self.decrypt_with(LOCK_PREFIX, text)
+    }
+}
+
+/// 保险库头：口令派生密钥(Argon2id)包装后的数据密钥
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultHeader {
+    salt: String,
+    wrapped_key: String,
+}
+
+impl VaultHeader {
+    /// 生成新的数据密钥并用口令包装
+    pub fn create(passphrase: &str) -> Result<(Self, Cipher)> {
+        let cipher = Cipher::generate();
+        Ok((Self::wrap(&cipher, passphrase)?, cipher))
+    }
+
+    /// 用口令解开数据密钥
+    pub fn unlock(&self, passphrase: &str) -> Result<Cipher> {
+        let salt = BASE64.decode(&self.salt)?;
+        let kek = derive_key(passphrase, &salt)?;
+        let key = kek.open_bytes(&BASE64.decode(&self.wrapped_key)?)
+            .map_err(|_| anyhow::anyhow!("wrong passphrase"))?;
+        if key.len() != 32 {
+            return Err(anyhow::anyhow!("invalid vault key"));
+        }
+        Ok(Cipher { key: *Key::from_slice(&key) })
+    }
+
+    /// 更换口令(数据密钥不变，无需重新加密笔记)
+    pub fn rewrap(&self, old_passphrase: &str, new_passphrase: &str) -> Result<Self> {
+        let cipher = self.unlock(old_passphrase)?;
+        Self::wrap(&cipher, new_passphrase)
+    }
+
+    fn wrap(cipher: &Cipher, passphrase: &str) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let kek = derive_key(passphrase, &salt)?;
+        Ok(Self {
+            salt: BASE64.encode(salt),
+            wrapped_key: BASE64.encode(kek.seal_bytes(cipher.key.as_slice())),
+        })
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Cipher> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut_slice())
+        .map_err(|e| anyhow::anyhow!("derive key failed: {}", e))?;
+    Ok(Cipher { key })
+}
+
+/// 加密备份文件格式
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedBackup {
+    pub format: String,
+    pub vault: VaultHeader,
+    pub payload: String,
+}
+
+impl EncryptedBackup {
+    pub const FORMAT: &'static str = "cloud-notepad-encrypted-backup/v1";
+
+    pub fn seal(header: &VaultHeader, cipher: &Cipher, plaintext: &str) -> Self {
+        Self {
+            format: Self::FORMAT.to_string(),
+            vault: header.clone(),
+            payload: cipher.encrypt(plaintext),
+        }
+    }
+
+    pub fn open(&self, passphrase: &str) -> Result<String> {
+        self.vault.unlock(passphrase)?.decrypt(&self.payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let (header, cipher) = VaultHeader::create("correct horse").unwrap();
+        let sealed = cipher.encrypt("密码: hunter2");
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("hunter2"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "密码: hunter2");
+
+        // 错误口令无法解开，修改口令后数据密钥不变
+        assert!(header.unlock("wrong").is_err());
+        let header = header.rewrap("correct horse", "battery staple").unwrap();
+        assert!(header.unlock("correct horse").is_err());
+        let cipher = header.unlock("battery staple").unwrap();
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "密码: hunter2");
+    }
+
+    #[test]
+    fn test_lock_text_is_not_vault_text() {
+        let (_, cipher) = VaultHeader::create("pass").unwrap();
+        let locked = cipher.lock_text("secret");
+        assert!(is_locked_text(&locked) && !is_encrypted(&locked));
+        assert!(cipher.decrypt(&locked).is_err());
+        assert_eq!(cipher.unlock_text(&locked).unwrap(), "secret");
+    }
+
+    #[test]
+    fn test_tampered_ciphertext() {
+        let (_, cipher) = VaultHeader::create("pass").unwrap();
+        let mut sealed = cipher.encrypt("hello");
+        // 替换为不同的字符, 保证密文确实被修改
+        let last = sealed.pop().unwrap();
+        sealed.push(if last == 'A' { 'B' } else { 'A' });
+        assert!(cipher.decrypt(&sealed).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+/// 行级差异操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffOp<'a> {
+    Equal(&'a str),
+    Insert(&'a str),
+    Delete(&'a str),
+}
+
+/// 三方合并后的片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeHunk {
+    // 无冲突片段(双方一致或只有一方修改)
+    Resolved(Vec<String>),
+    // 冲突片段(双方对同一区域做了不同修改)
+    Conflict {
+        base: Vec<String>,
+        ours: Vec<String>,
+        theirs: Vec<String>,
+    },
+}
+
+impl MergeHunk {
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, Self::Conflict { .. })
+    }
+}
+
+/// 按行切分文本(保留换行符，保证合并后可以原样拼接)
+pub fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+// 完整动态规划表的最大单元数(约16MB), 超过时改用线性空间的 Hirschberg 算法
+const TABLE_LIMIT: usize = 4_000_000;
+
+/// 计算两组行的最长公共子序列匹配，返回 (a中下标, b中下标) 的递增序列
+fn lcs_matches(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    lcs_matches_within(a, b, TABLE_LIMIT)
+}
+
+fn lcs_matches_within(a: &[&str], b: &[&str], limit: usize) -> Vec<(usize, usize)> {
+    // 去掉公共前缀和后缀，缩小计算规模
+    let prefix = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let mut matches: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    hirschberg(a_mid, b_mid, (prefix, prefix), limit, &mut matches);
+    for k in 0..suffix {
+        matches.push((a.len() - suffix + k, b.len() - suffix + k));
+    }
+    matches
+}
+
+// 分治求LCS: 规模足够小时使用完整的动态规划表, 否则在a的中点处按两侧LCS长度之和最大的位置切分b
+fn hirschberg(a: &[&str], b: &[&str], offset: (usize, usize), limit: usize, matches: &mut Vec<(usize, usize)>) {
+    if a.is_empty() || b.is_empty() {
+        return;
+    }
+    if a.len() == 1 {
+        if let Some(j) = b.iter().position(|line| *line == a[0]) {
+            matches.push((offset.0, offset.1 + j));
+        }
+        return;
+    }
+    if (a.len() + 1) * (b.len() + 1) <= limit {
+        matches.extend(table_matches(a, b).into_iter().map(|(i, j)| (offset.0 + i, offset.1 + j)));
+        return;
+    }
+
+    let mid = a.len() / 2;
+    let forward = lcs_lengths(a[..mid].iter().copied(), b);
+    let b_reversed: Vec<&str> = b.iter().rev().copied().collect();
+    let backward = lcs_lengths(a[mid..].iter().rev().copied(), &b_reversed);
+    let split = (0..=b.len())
+        .max_by_key(|&j| (forward[j] + backward[b.len() - j], std::cmp::Reverse(j)))
+        .unwrap_or(0);
+    hirschberg(&a[..mid], &b[..split], offset, limit, matches);
+    hirschberg(&a[mid..], &b[split..], (offset.0 + mid, offset.1 + split), limit, matches);
+}
+
+// a与b每个前缀的LCS长度(只保留一行)
+fn lcs_lengths<'a>(a: impl Iterator<Item = &'a str>, b: &[&str]) -> Vec<u32> {
+    let mut prev = vec![0u32; b.len() + 1];
+    let mut cur = vec![0u32; b.len() + 1];
+    for x in a {
+        for (j, y) in b.iter().enumerate() {
+            cur[j + 1] = if x == *y { prev[j] + 1 } else { u32::max(prev[j + 1], cur[j]) };
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev
+}
+
+// 完整动态规划表求LCS
+fn table_matches(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    let (n, m) = (a.len(), b.len());
+    let mut table = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i * (m + 1) + j] = if a[i] == b[j] {
+                table[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                u32::max(table[(i + 1) * (m + 1) + j], table[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let mut matches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * (m + 1) + j] >= table[i * (m + 1) + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
+
+/// 计算两段文本的行级差异
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffOp<'a>> {
+    let a = split_lines(old);
+    let b = split_lines(new);
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (mi, mj) in lcs_matches(&a, &b) {
+        ops.extend(a[i..mi].iter().map(|line| DiffOp::Delete(line)));
+        ops.extend(b[j..mj].iter().map(|line| DiffOp::Insert(line)));
+        ops.push(DiffOp::Equal(a[mi]));
+        i = mi + 1;
+        j = mj + 1;
+    }
+    ops.extend(a[i..].iter().map(|line| DiffOp::Delete(line)));
+    ops.extend(b[j..].iter().map(|line| DiffOp::Insert(line)));
+    ops
+}
+
+/// 基于共同祖先的三方行合并(diff3)
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Vec<MergeHunk> {
+    let base_lines = split_lines(base);
+    let our_lines = split_lines(ours);
+    let their_lines = split_lines(theirs);
+
+    // 祖先中每一行在双方中的对应位置
+    let mut to_ours = vec![None; base_lines.len()];
+    for (b, o) in lcs_matches(&base_lines, &our_lines) {
+        to_ours[b] = Some(o);
+    }
+    let mut to_theirs = vec![None; base_lines.len()];
+    for (b, t) in lcs_matches(&base_lines, &their_lines) {
+        to_theirs[b] = Some(t);
+    }
+
+    let owned = |lines: &[&str]| lines.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let mut hunks: Vec<MergeHunk> = Vec::new();
+    let push_resolved = |hunks: &mut Vec<MergeHunk>, lines: Vec<String>| {
+        if lines.is_empty() { return; }
+        if let Some(MergeHunk::Resolved(prev)) = hunks.last_mut() {
+            prev.extend(lines);
+        } else {
+            hunks.push(MergeHunk::Resolved(lines));
+        }
+    };
+
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        // 找到下一个在三方中都保持不变的稳定行
+        let stable = (b..base_lines.len()).find(|&i| to_ours[i].is_some() && to_theirs[i].is_some());
+        let (b_end, o_end, t_end) = match stable {
+            Some(i) => (i, to_ours[i].unwrap(), to_theirs[i].unwrap()),
+            None => (base_lines.len(), our_lines.len(), their_lines.len()),
+        };
+
+        // 处理稳定行之前的不稳定区域
+        let base_chunk = &base_lines[b..b_end];
+        let our_chunk = &our_lines[o..o_end];
+        let their_chunk = &their_lines[t..t_end];
+        if our_chunk == their_chunk || their_chunk == base_chunk {
+            push_resolved(&mut hunks, owned(our_chunk));
+        } else if our_chunk == base_chunk {
+            push_resolved(&mut hunks, owned(their_chunk));
+        } else {
+            hunks.push(MergeHunk::Conflict {
+                base: owned(base_chunk),
+                ours: owned(our_chunk),
+                theirs: owned(their_chunk),
+            });
+        }
+
+        match stable {
+            Some(i) => {
+                push_resolved(&mut hunks, vec![base_lines[i].to_string()]);
+                b = i + 1;
+                o = o_end + 1;
+                t = t_end + 1;
+            }
+            None => break,
+        }
+    }
+
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved_text(hunks: &[MergeHunk]) -> Option<String> {
+        let mut text = String::new();
+        for hunk in hunks {
+            match hunk {
+                MergeHunk::Resolved(lines) => text.extend(lines.iter().cloned()),
+                MergeHunk::Conflict { .. } => return None,
+            }
+        }
+        Some(text)
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let ops = diff_lines("a\nb\nc\n", "a\nc\nd\n");
+        assert_eq!(ops, vec![
+            DiffOp::Equal("a\n"),
+            DiffOp::Delete("b\n"),
+            DiffOp::Equal("c\n"),
+            DiffOp::Insert("d\n"),
+        ]);
+    }
+
+    #[test]
+    fn test_lcs_linear_space() {
+        // 超过表格上限时分治计算, 结果长度与完整动态规划一致且匹配有效
+        let a: Vec<String> = (0..300).map(|i| format!("{}\n", (i * 7) % 23)).collect();
+        let b: Vec<String> = (0..250).map(|i| format!("{}\n", (i * 5) % 19)).collect();
+        let a: Vec<&str> = a.iter().map(String::as_str).collect();
+        let b: Vec<&str> = b.iter().map(String::as_str).collect();
+        let full = lcs_matches_within(&a, &b, usize::MAX);
+        let split = lcs_matches_within(&a, &b, 64);
+        assert_eq!(split.len(), full.len());
+        assert!(split.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1));
+        assert!(split.iter().all(|&(i, j)| a[i] == b[j]));
+    }
+
+    #[test]
+    fn test_merge_non_overlapping_changes() {
+        let base = "title\nline1\nline2\nline3\n";
+        let ours = "title\nline1 changed\nline2\nline3\n";
+        let theirs = "title\nline1\nline2\nline3 changed\nline4\n";
+        let hunks = merge3(base, ours, theirs);
+        assert_eq!(
+            resolved_text(&hunks),
+            Some("title\nline1 changed\nline2\nline3 changed\nline4\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_merge_identical_changes() {
+        let hunks = merge3("a\nb\n", "a\nc\n", "a\nc\n");
+        assert_eq!(resolved_text(&hunks), Some("a\nc\n".to_string()));
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let hunks = merge3("a\nb\nc\n", "a\nours\nc\n", "a\ntheirs\nc\n");
+        assert_eq!(hunks, vec![
+            MergeHunk::Resolved(vec!["a\n".to_string()]),
+            MergeHunk::Conflict {
+                base: vec!["b\n".to_string()],
+                ours: vec!["ours\n".to_string()],
+                theirs: vec!["theirs\n".to_string()],
+            },
+            MergeHunk::Resolved(vec!["c\n".to_string()]),
+        ]);
+    }
+
+    #[test]
+    fn test_merge_without_base() {
+        // 没有共同祖先时，所有不同之处都是冲突
+        let hunks = merge3("", "same\nours\n", "same\ntheirs\n");
+        assert_eq!(hunks.iter().filter(|h| h.is_conflict()).count(), 1);
+    }
+}
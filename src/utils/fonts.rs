@@ -0,0 +1,9 @@
+// 内置字体, 界面和PDF导出共用
+
+// 思源黑体(中日韩字符)
+pub static SOURCE_HAN_SANS_REGULAR: &[u8] = include_bytes!("../../assets/fonts/SourceHanSans-Regular.ttc");
+pub static SOURCE_HAN_SANS_BOLD: &[u8] = include_bytes!("../../assets/fonts/SourceHanSans-Bold.ttc");
+
+// egui 自带的西文字体, 作为缺字时的后备
+pub static UBUNTU_LIGHT: &[u8] = epaint_default_fonts::UBUNTU_LIGHT;
+pub static HACK_REGULAR: &[u8] = epaint_default_fonts::HACK_REGULAR;
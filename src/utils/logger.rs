@@ -0,0 +1,234 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+
+// 单个日志文件的最大字节数，超过后轮转
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+// 保留的历史日志文件数(app.log.1 ~ app.log.N)
+const MAX_ROTATED_FILES: usize = 3;
+// 内存中保留的日志条数(供日志窗口查看)
+const BUFFER_CAPACITY: usize = 1000;
+
+/// 日志级别(越靠前越严重)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// 一条日志：级别、操作名、相关笔记和消息
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub operation: String,
+    pub note_id: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:<5} [{}]", self.time.format("%Y-%m-%d %H:%M:%S%.3f"), self.level, self.operation)?;
+        if let Some(note_id) = &self.note_id {
+            write!(f, " note={}", note_id)?;
+        }
+        write!(f, " {}", self.message)
+    }
+}
+
+// 按大小轮转的日志文件
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size + line.len() as u64 + 1 > MAX_FILE_SIZE {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    // app.log -> app.log.1 -> app.log.2 ...，最旧的文件被覆盖
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        for idx in (1..MAX_ROTATED_FILES).rev() {
+            let from = Self::rotated_path(&self.path, idx);
+            if from.exists() {
+                std::fs::rename(&from, Self::rotated_path(&self.path, idx + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, Self::rotated_path(&self.path, 1))?;
+        *self = Self::open(self.path.clone())?;
+        Ok(())
+    }
+
+    fn rotated_path(path: &Path, idx: usize) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", idx));
+        PathBuf::from(name)
+    }
+}
+
+struct Logger {
+    max_level: Level,
+    buffer: VecDeque<LogRecord>,
+    file: Option<LogFile>,
+}
+
+static LOGGER: Lazy<Mutex<Logger>> = Lazy::new(|| Mutex::new(Logger {
+    max_level: Level::Info,
+    buffer: VecDeque::with_capacity(BUFFER_CAPACITY),
+    file: None,
+}));
+
+/// 在数据目录下创建日志文件，返回日志文件路径
+pub fn init(data_dir: &Path) -> anyhow::Result<PathBuf> {
+    let dir = data_dir.join("logs");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("app.log");
+    LOGGER.lock().unwrap().file = Some(LogFile::open(path.clone())?);
+    Ok(path)
+}
+
+/// 当前日志文件路径(未初始化时为None)
+pub fn log_file() -> Option<PathBuf> {
+    LOGGER.lock().unwrap().file.as_ref().map(|f| f.path.clone())
+}
+
+pub fn set_max_level(level: Level) {
+    LOGGER.lock().unwrap().max_level = level;
+}
+
+/// 记录日志
+pub fn log(level: Level, operation: &str, note_id: Option<&str>, message: String) {
+    let mut logger = LOGGER.lock().unwrap();
+    if level > logger.max_level {
+        return;
+    }
+
+    let record = LogRecord {
+        time: Local::now(),
+        level,
+        operation: operation.to_string(),
+        note_id: note_id.map(|id| id.to_string()),
+        message,
+    };
+    let line = record.to_string();
+    // 写文件失败时只保留内存中的记录
+    if let Some(file) = logger.file.as_mut()
+        && file.write_line(&line).is_err() {
+        logger.file = None;
+    }
+
+    if logger.buffer.len() == BUFFER_CAPACITY {
+        logger.buffer.pop_front();
+    }
+    logger.buffer.push_back(record);
+}
+
+/// 内存中最近的日志(按时间顺序)
+pub fn records() -> Vec<LogRecord> {
+    LOGGER.lock().unwrap().buffer.iter().cloned().collect()
+}
+
+/// 清空内存中的日志(不影响日志文件)
+pub fn clear() {
+    LOGGER.lock().unwrap().buffer.clear();
+}
+
+/// 按级别记录日志：`log_at!(level, "operation", note = id; "format", args..)`，`note = id` 可省略
+#[macro_export]
+macro_rules! log_at {
+    ($level:expr, $operation:expr, note = $note:expr; $($arg:tt)+) => {
+        $crate::utils::logger::log(
+            $level,
+            $operation,
+            Some(::std::convert::AsRef::<str>::as_ref(&$note)),
+            format!($($arg)+),
+        )
+    };
+    ($level:expr, $operation:expr; $($arg:tt)+) => {
+        $crate::utils::logger::log($level, $operation, None, format!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)+) => { $crate::log_at!($crate::utils::logger::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)+) => { $crate::log_at!($crate::utils::logger::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)+) => { $crate::log_at!($crate::utils::logger::Level::Info, $($arg)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_file_rotation() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let mut file = LogFile::open(dir.join("app.log")).unwrap();
+        let line = "x".repeat(1023);
+        for _ in 0..(MAX_FILE_SIZE / 1024) * 2 + 1 {
+            file.write_line(&line).unwrap();
+        }
+        assert!(dir.join("app.log.1").exists());
+        assert!(dir.join("app.log.2").exists());
+        assert!(std::fs::metadata(dir.join("app.log")).unwrap().len() <= MAX_FILE_SIZE);
+    }
+
+    #[test]
+    fn test_record_format() {
+        let record = LogRecord {
+            time: Local::now(),
+            level: Level::Error,
+            operation: "save_note".to_string(),
+            note_id: Some("n1".to_string()),
+            message: "disk full".to_string(),
+        };
+        assert!(record.to_string().ends_with("ERROR [save_note] note=n1 disk full"));
+    }
+}
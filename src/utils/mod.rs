@@ -1,2 +1,6 @@
 pub mod i18n;
-pub mod tab_manager;
\ No newline at end of file
+pub mod tab_manager;
+pub mod diff;
+pub mod crypto;
+pub mod logger;
+pub mod fonts;
\ No newline at end of file
@@ -0,0 +1,169 @@
+use cloud_notepad::{AppState, NotepadError};
+use cloud_notepad::data::Note;
+use cloud_notepad::io::ExportFormat;
+use cloud_notepad::services::{IoService, MirrorService, NoteService, SettingsService, SyncService, TrashService, VaultService, VersionService};
+
+fn new_note(title: &str, content: &str, tags: &[&str]) -> Note {
+    let mut note = Note::new(title.to_string());
+    note.update_content(content.to_string());
+    for tag in tags.iter() {
+        note.add_tag(tag.to_string());
+    }
+    note
+}
+
+// 写入内存和数据库
+fn store(state: &mut AppState, note: &Note) {
+    state.update_note(note.clone()).unwrap();
+    state.save_note(note).unwrap();
+}
+
+#[test]
+fn test_create_and_update() {
+    let mut state = AppState::in_memory().unwrap();
+    state.create_note().unwrap();
+    assert_eq!(state.filter_notes("").unwrap().len(), 1);
+
+    let mut note = new_note("周报", "本周完成了同步", &["工作"]);
+    store(&mut state, &note);
+    note.update_title("周报 第42周".to_string());
+    note.update_content("本周完成了同步和镜像".to_string());
+    store(&mut state, &note);
+
+    let saved = state.get_note(note.id()).unwrap();
+    assert_eq!((saved.title(), saved.content()), ("周报 第42周", "本周完成了同步和镜像"));
+    assert_eq!(state.filter_notes("镜像").unwrap().len(), 1);
+}
+
+#[test]
+fn test_trash_restore_and_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.db");
+    let mut state = AppState::open(&path).unwrap();
+    let kept = new_note("kept", "", &["a"]);
+    let dropped = new_note("dropped", "", &["a"]);
+    store(&mut state, &kept);
+    store(&mut state, &dropped);
+
+    state.move_to_trash(kept.id()).unwrap();
+    state.move_to_trash(dropped.id()).unwrap();
+    assert!(state.get_note(kept.id()).is_none());
+    assert_eq!(state.get_deleted_notes().unwrap().len(), 2);
+
+    state.restore_from_trash(kept.id()).unwrap();
+    assert_eq!(state.get_note(kept.id()).unwrap().title(), "kept");
+    // 不在回收站中的笔记不能恢复
+    assert!(state.restore_from_trash(kept.id()).is_err());
+    let missing = state.restore_from_trash("missing").unwrap_err();
+    assert!(matches!(missing, NotepadError::NotFound(id) if id == "missing"));
+
+    state.empty_trash().unwrap();
+    assert!(state.get_deleted_notes().unwrap().is_empty());
+
+    // 重新打开后状态保持
+    let state = AppState::open(&path).unwrap();
+    let notes = state.filter_notes("").unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].id(), kept.id());
+}
+
+#[test]
+fn test_versions() {
+    let mut state = AppState::in_memory().unwrap();
+    let mut note = new_note("plan", "v1", &["work", "2024"]);
+    store(&mut state, &note);
+    state.save_version("first", &note).unwrap();
+    note.update_content("v2".to_string());
+    note.remove_tag("2024");
+    store(&mut state, &note);
+    state.save_version("second", &note).unwrap();
+
+    // 从新到旧, 标签与保存时一致
+    let versions = state.list_versions(note.id()).unwrap();
+    assert_eq!(versions.len(), 2);
+    let oldest = versions.iter().find(|v| v.comment() == "first").unwrap();
+    assert_eq!(oldest.content(), "v1");
+    assert!(oldest.tags().contains("2024") && oldest.tags().contains("work"));
+
+    state.restore_version(oldest).unwrap();
+    let restored = state.get_note(note.id()).unwrap();
+    assert_eq!(restored.content(), "v1");
+    assert!(restored.tags().contains("2024"));
+
+    let newest = versions.iter().find(|v| v.comment() == "second").unwrap();
+    state.delete_version(newest.id()).unwrap();
+    assert_eq!(state.list_versions(note.id()).unwrap().len(), 1);
+}
+
+#[test]
+fn test_markdown_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = AppState::in_memory().unwrap();
+    let note = new_note("读书笔记", "# 读书笔记\n\n- 第一章\n", &["阅读"]);
+    store(&mut state, &note);
+    state.export_config_mut().format = ExportFormat::Markdown(true);
+    let path = dir.path().join("note.md");
+    state.export_note(note.id(), &path).unwrap();
+
+    let mut other = AppState::in_memory().unwrap();
+    other.import(&path).unwrap();
+    let imported = other.filter_notes("").unwrap();
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].title(), "读书笔记");
+    assert!(imported[0].content().contains("- 第一章"));
+    assert!(imported[0].tags().contains("阅读"));
+}
+
+#[test]
+fn test_json_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = AppState::in_memory().unwrap();
+    for i in 0..3 {
+        store(&mut state, &new_note(&format!("note {}", i), &format!("content {}", i), &["batch"]));
+    }
+    state.export_config_mut().format = ExportFormat::Json;
+    state.export_all_notes(dir.path()).unwrap();
+
+    let mut other = AppState::in_memory().unwrap();
+    other.import(&dir.path().join("notes_backup.json")).unwrap();
+    let mut titles: Vec<String> = other.filter_notes("").unwrap().iter().map(|n| n.title().to_string()).collect();
+    titles.sort();
+    assert_eq!(titles, ["note 0", "note 1", "note 2"]);
+    assert!(other.filter_notes("").unwrap().iter().all(|n| n.tags().contains("batch")));
+}
+
+#[test]
+fn test_vault_refuses_plaintext_sync() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = AppState::in_memory().unwrap();
+    state.set_sync_folder(Some(dir.path())).unwrap();
+    assert!(state.enable_vault("secret").is_err());
+
+    // 启用保险库后不能再写出明文副本
+    state.set_sync_folder(None).unwrap();
+    state.enable_vault("secret").unwrap();
+    assert!(state.set_sync_folder(Some(dir.path())).is_err());
+    assert!(state.set_mirror_folder(Some(dir.path())).is_err());
+    assert!(state.mirror_folder().is_none());
+}
+
+#[test]
+fn test_git_history_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = AppState::open(dir.path().join("notes.db")).unwrap();
+    let mut note = new_note("plan", "v1\n", &[]);
+    store(&mut state, &note);
+    state.save_version("first", &note).unwrap();
+
+    assert_eq!(state.set_git_history(true).unwrap(), 1);
+    note.update_content("v2\n".to_string());
+    store(&mut state, &note);
+    state.save_version("second", &note).unwrap();
+    assert_eq!(state.list_versions(note.id()).unwrap().len(), 2);
+
+    // 关闭后版本迁回数据库, 重新打开也能读取
+    assert_eq!(state.set_git_history(false).unwrap(), 2);
+    let state = AppState::open(dir.path().join("notes.db")).unwrap();
+    let versions = state.list_versions(note.id()).unwrap();
+    assert_eq!(versions.iter().map(|v| v.comment()).collect::<Vec<_>>(), ["second", "first"]);
+}